@@ -0,0 +1,116 @@
+use std::collections::HashSet;
+use std::hint::black_box;
+
+use criterion::criterion_group;
+use criterion::criterion_main;
+use criterion::Criterion;
+
+use grandfathers_of_the_sahara::game::entities::movement::BoardGraph;
+use grandfathers_of_the_sahara::game::entities::movement::STARTING_POSITIONS;
+use grandfathers_of_the_sahara::util::flags::CardinalDirectionFlags;
+
+const BOARD_SIZE: u8 = 11;
+const SEED: u64 = 0x5a4a_5241;
+
+/// xorshift64, so simulated games are the same on every run
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+/// Tile placements of a game that runs until the board is full: the starting
+/// cross, then random tiles explored next to tiles already on the board.
+fn simulated_placements() -> Vec<((u8, u8), CardinalDirectionFlags)> {
+    let mut rng = Rng(SEED);
+    let mut placed: HashSet<(u8, u8)> = HashSet::new();
+    let mut placements = vec![];
+
+    for idx in 0..BOARD_SIZE {
+        for coordinates in [(5, idx), (idx, 5)] {
+            if placed.insert(coordinates) {
+                placements.push((coordinates, CardinalDirectionFlags::empty()));
+            }
+        }
+    }
+
+    while placed.len() < (BOARD_SIZE as usize).pow(2) {
+        let frontier: Vec<(u8, u8)> = (0..BOARD_SIZE)
+            .flat_map(|x| (0..BOARD_SIZE).map(move |y| (x, y)))
+            .filter(|c| !placed.contains(c))
+            .filter(|(x, y)| {
+                [(0, 1), (1, 0), (0, -1), (-1, 0)].iter().any(|(dx, dy)| {
+                    let neighbor = (*x as i32 + dx, *y as i32 + dy);
+
+                    neighbor.0 >= 0
+                        && neighbor.1 >= 0
+                        && placed.contains(&(neighbor.0 as u8, neighbor.1 as u8))
+                })
+            })
+            .collect();
+
+        let coordinates = frontier[rng.next() as usize % frontier.len()];
+        let oasis = CardinalDirectionFlags::from_bits_truncate(rng.next() as u8);
+
+        placed.insert(coordinates);
+        placements.push((coordinates, oasis));
+    }
+
+    placements
+}
+
+/// Every placement is followed by a move phase for the next player, as in a
+/// game where no caravan ever stops on a desert tile.
+fn play(placements: &[((u8, u8), CardinalDirectionFlags)]) -> usize {
+    let mut graph = BoardGraph::default();
+    let mut positions: Vec<(u8, u8)> = STARTING_POSITIONS.iter().map(|(c, _)| *c).collect();
+    let mut reachable_total = 0;
+
+    for (turn, (coordinates, oasis)) in placements.iter().enumerate() {
+        graph.insert_tile(*coordinates, oasis.clone(), CardinalDirectionFlags::empty());
+
+        let active = turn % positions.len();
+        let occupied: HashSet<(u8, u8)> = positions
+            .iter()
+            .enumerate()
+            .filter(|(player, _)| *player != active)
+            .map(|(_, c)| *c)
+            .collect();
+
+        let reachable = graph.reachable_tiles(positions[active], &occupied);
+
+        if let Some(destination) = reachable.first() {
+            positions[active] = *destination;
+        }
+
+        reachable_total += reachable.len();
+    }
+
+    reachable_total
+}
+
+fn long_game(c: &mut Criterion) {
+    let placements = simulated_placements();
+
+    c.bench_function("simulated game on a full board", |b| {
+        b.iter(|| play(black_box(&placements)))
+    });
+
+    let mut graph = BoardGraph::default();
+
+    for (coordinates, oasis) in &placements {
+        graph.insert_tile(*coordinates, oasis.clone(), CardinalDirectionFlags::empty());
+    }
+
+    c.bench_function("move phase on a full board", |b| {
+        b.iter(|| graph.reachable_tiles(black_box((5, 5)), &HashSet::new()))
+    });
+}
+
+criterion_group!(benches, long_game);
+criterion_main!(benches);
@@ -0,0 +1,22 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use toml::Value;
+
+use grandfathers_of_the_sahara::util::flags::CardinalDirectionFlags;
+use grandfathers_of_the_sahara::util::tileset::ConfigOasisFlags;
+use grandfathers_of_the_sahara::util::tileset::ConfigOasisLayoutFlags;
+
+// One oasis entry, e.g. `N | W`, or a whole layout split by `:`
+fuzz_target!(|input: &str| {
+    if let Ok(flags) = ConfigOasisFlags::try_from(&Value::String(input.to_owned())) {
+        assert!(CardinalDirectionFlags::all().contains(flags.into()));
+    }
+
+    let entries = input
+        .split(':')
+        .map(|entry| Value::String(entry.to_owned()))
+        .collect();
+
+    let _ = ConfigOasisLayoutFlags::try_from(&Value::Array(entries));
+});
@@ -0,0 +1,10 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use grandfathers_of_the_sahara::util::tileset::format::from_json;
+
+// A tileset exported with `sahara-tileset export --format json`
+fuzz_target!(|input: &str| {
+    let _ = from_json(input);
+});
@@ -0,0 +1,10 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use grandfathers_of_the_sahara::util::tileset::notation::from_notation;
+
+// A tileset exported with `sahara-tileset export --format notation`
+fuzz_target!(|input: &str| {
+    let _ = from_notation(input);
+});
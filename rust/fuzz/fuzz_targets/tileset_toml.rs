@@ -0,0 +1,37 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use toml::Table;
+
+use grandfathers_of_the_sahara::util::tileset::format::from_json;
+use grandfathers_of_the_sahara::util::tileset::format::from_toml;
+use grandfathers_of_the_sahara::util::tileset::format::to_canonical_toml;
+use grandfathers_of_the_sahara::util::tileset::format::to_json;
+use grandfathers_of_the_sahara::util::tileset::lint::lint;
+use grandfathers_of_the_sahara::util::tileset::render::render_tileset;
+use grandfathers_of_the_sahara::util::tileset::stats::TilesetStats;
+use grandfathers_of_the_sahara::util::treasures::TreasureCatalog;
+
+const TREASURES: &str = include_str!("../../../godot/config/treasures.toml");
+
+// A whole tileset.toml, through everything `sahara-tileset` does with one
+fuzz_target!(|input: &str| {
+    let Ok(table) = toml::from_str::<Table>(input) else {
+        return;
+    };
+    let treasures = toml::from_str::<Table>(TREASURES).unwrap();
+    let catalog = TreasureCatalog::try_from(&treasures).unwrap();
+
+    let _ = lint(&table, &catalog);
+
+    let Ok(tileset) = from_toml(input) else {
+        return;
+    };
+
+    let _ = TilesetStats::from(&tileset);
+    let _ = render_tileset(&tileset, None);
+
+    // Writing a tileset out has to read back as the same tileset
+    assert_eq!(from_toml(&to_canonical_toml(&tileset)).unwrap(), tileset);
+    assert_eq!(from_json(&to_json(&tileset)).unwrap(), tileset);
+});
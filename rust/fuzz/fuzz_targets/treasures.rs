@@ -0,0 +1,16 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use toml::Table;
+
+use grandfathers_of_the_sahara::util::treasures::TreasureCatalog;
+use grandfathers_of_the_sahara::util::treasures::TreasureCategory;
+
+// A whole treasures.toml, or a single category name
+fuzz_target!(|input: &str| {
+    let _ = TreasureCategory::try_from(input);
+
+    if let Ok(table) = toml::from_str::<Table>(input) {
+        let _ = TreasureCatalog::try_from(&table);
+    }
+});
@@ -0,0 +1,287 @@
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use toml::Table;
+
+use grandfathers_of_the_sahara::util::tileset::format::from_json;
+use grandfathers_of_the_sahara::util::tileset::format::from_toml;
+use grandfathers_of_the_sahara::util::tileset::format::lost_comments;
+use grandfathers_of_the_sahara::util::tileset::format::to_canonical_toml;
+use grandfathers_of_the_sahara::util::tileset::format::to_json;
+use grandfathers_of_the_sahara::util::tileset::generate::generate;
+use grandfathers_of_the_sahara::util::tileset::generate::keep_deck_metadata;
+use grandfathers_of_the_sahara::util::tileset::generate::GeneratorTargets;
+use grandfathers_of_the_sahara::util::tileset::lint::lint;
+use grandfathers_of_the_sahara::util::tileset::notation::from_notation;
+use grandfathers_of_the_sahara::util::tileset::notation::to_notation;
+use grandfathers_of_the_sahara::util::tileset::render::render_tileset;
+use grandfathers_of_the_sahara::util::tileset::stats::TilesetStats;
+use grandfathers_of_the_sahara::util::tileset::TilesetConfig;
+use grandfathers_of_the_sahara::util::treasures::TreasureCatalog;
+
+const DEFAULT_TILESET: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../godot/config/tileset.toml");
+
+const USAGE: &str = "Usage: sahara-tileset <COMMAND> [TILESET] [OPTIONS]
+
+Commands:
+  lint       Check a tileset the way the game does, plus likely mistakes
+             --treasures PATH  treasures.toml to check against, defaults to
+                               the one next to the tileset
+  normalize  Print the tileset in canonical form
+             --write           rewrite the file in place instead, refused
+                               when it has comments normalizing would drop
+             --drop-comments   rewrite it even then
+             --check           fail when the file isn't canonical
+  stats      Print per-deck and overall balance: tile types, desert share,
+             oasis connections and treasures
+  render     Draw the tiles as ASCII art
+             --deck N          only draw deck N (from 1)
+  generate   Print a random tileset balanced like TILESET, with its deck names
+             --seed N          seed to generate from, random by default
+             --desert PERCENT  share of pure-desert tiles in each deck
+  export     Print the tileset in another format
+             --format FORMAT   toml, json or notation (one line per tile)
+  import     Print a tileset written in another format as tileset.toml
+             --format FORMAT   defaults to the file's extension, .json,
+                               .toml or anything else for notation
+
+TILESET defaults to godot/config/tileset.toml";
+
+struct Args {
+    command: String,
+    tileset: PathBuf,
+    options: Vec<String>,
+}
+
+impl Args {
+    fn parse() -> Result<Self, String> {
+        let mut args = std::env::args().skip(1);
+        let command = args.next().ok_or(USAGE)?;
+        let mut tileset = None;
+        let mut options = vec![];
+
+        while let Some(arg) = args.next() {
+            if ["--treasures", "--deck", "--seed", "--desert", "--format"].contains(&arg.as_str()) {
+                let value = args.next().ok_or(format!("{arg} needs a value"))?;
+
+                options.extend([arg, value]);
+            } else if arg.starts_with("--") {
+                options.push(arg);
+            } else if tileset.is_none() {
+                tileset = Some(PathBuf::from(arg));
+            } else {
+                return Err(format!("Unexpected argument {arg}\n\n{USAGE}"));
+            }
+        }
+
+        Ok(Self {
+            command,
+            tileset: tileset.unwrap_or_else(|| PathBuf::from(DEFAULT_TILESET)),
+            options,
+        })
+    }
+    fn flag(&self, name: &str) -> bool {
+        self.options.iter().any(|option| option == name)
+    }
+    fn value(&self, name: &str) -> Option<&str> {
+        self.options
+            .iter()
+            .position(|option| option == name)
+            .and_then(|idx| self.options.get(idx + 1))
+            .map(String::as_str)
+    }
+}
+
+fn read_table(path: &Path) -> Result<(String, Table), String> {
+    let contents =
+        fs::read_to_string(path).map_err(|e| format!("Couldn't read {}: {e}", path.display()))?;
+    let table = toml::from_str::<Table>(&contents)
+        .map_err(|e| format!("Couldn't parse {}: {e}", path.display()))?;
+
+    Ok((contents, table))
+}
+
+fn read_tileset(path: &Path) -> Result<(String, TilesetConfig), String> {
+    let (contents, table) = read_table(path)?;
+    let tileset = TilesetConfig::try_from(&table)
+        .map_err(|e| format!("Couldn't parse {}: {e}", path.display()))?;
+
+    Ok((contents, tileset))
+}
+
+fn run_lint(args: &Args) -> Result<ExitCode, String> {
+    let treasures_path = args
+        .value("--treasures")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| args.tileset.with_file_name("treasures.toml"));
+    let (_, treasures) = read_table(&treasures_path)?;
+    let treasures = TreasureCatalog::try_from(&treasures)
+        .map_err(|e| format!("Couldn't parse {}: {e}", treasures_path.display()))?;
+    let (_, table) = read_table(&args.tileset)?;
+
+    let (_, warnings) = lint(&table, &treasures).map_err(|e| e.to_string())?;
+
+    for warning in &warnings {
+        println!("warning: {warning}");
+    }
+
+    if warnings.is_empty() {
+        println!("{} is clean", args.tileset.display());
+        Ok(ExitCode::SUCCESS)
+    } else {
+        Ok(ExitCode::FAILURE)
+    }
+}
+
+fn run_normalize(args: &Args) -> Result<ExitCode, String> {
+    let (contents, tileset) = read_tileset(&args.tileset)?;
+    let canonical = to_canonical_toml(&tileset);
+
+    if args.flag("--check") {
+        if canonical == contents {
+            return Ok(ExitCode::SUCCESS);
+        }
+
+        eprintln!(
+            "{} isn't canonical, run `just tileset normalize --write`",
+            args.tileset.display()
+        );
+        return Ok(ExitCode::FAILURE);
+    }
+
+    if args.flag("--write") {
+        let lost = lost_comments(&contents, &canonical);
+
+        if !lost.is_empty() && !args.flag("--drop-comments") {
+            return Err(format!(
+                "Normalizing {} would drop {} comment(s), starting with \"# {}\"; \
+                 pass --drop-comments to rewrite it anyway",
+                args.tileset.display(),
+                lost.len(),
+                lost[0]
+            ));
+        }
+
+        fs::write(&args.tileset, canonical)
+            .map_err(|e| format!("Couldn't write {}: {e}", args.tileset.display()))?;
+    } else {
+        print!("{canonical}");
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn run_stats(args: &Args) -> Result<ExitCode, String> {
+    let (_, tileset) = read_tileset(&args.tileset)?;
+
+    print!("{}", TilesetStats::from(&tileset));
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn run_render(args: &Args) -> Result<ExitCode, String> {
+    let (_, tileset) = read_tileset(&args.tileset)?;
+    let deck = match args.value("--deck") {
+        Some(deck) => match deck.parse::<usize>() {
+            Ok(deck) if (1..=tileset.deck.len()).contains(&deck) => Some(deck - 1),
+            _ => {
+                return Err(format!(
+                    "--deck must be between 1 and {}",
+                    tileset.deck.len()
+                ))
+            }
+        },
+        None => None,
+    };
+
+    print!("{}", render_tileset(&tileset, deck));
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn run_generate(args: &Args) -> Result<ExitCode, String> {
+    let (_, template) = read_tileset(&args.tileset)?;
+    let mut targets = GeneratorTargets::from(&TilesetStats::from(&template));
+    let seed = match args.value("--seed") {
+        Some(seed) => seed
+            .parse::<u64>()
+            .map_err(|_| String::from("--seed must be a non-negative integer"))?,
+        None => SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_nanos() as u64),
+    };
+
+    if let Some(desert) = args.value("--desert") {
+        targets.desert_percent = desert
+            .parse::<u8>()
+            .ok()
+            .filter(|desert| *desert <= 100)
+            .ok_or("--desert must be a percentage from 0 to 100")?;
+    }
+
+    let mut tileset = generate(seed, &targets).map_err(|e| e.to_string())?;
+
+    keep_deck_metadata(&mut tileset, &template);
+
+    // On stderr, so the output stays a canonical tileset.toml
+    eprintln!("Generated from seed {seed}");
+    print!("{}", to_canonical_toml(&tileset));
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn run_export(args: &Args) -> Result<ExitCode, String> {
+    let (_, tileset) = read_tileset(&args.tileset)?;
+
+    match args.value("--format") {
+        Some("toml") => print!("{}", to_canonical_toml(&tileset)),
+        Some("json") => println!("{}", to_json(&tileset)),
+        Some("notation") => print!("{}", to_notation(&tileset)),
+        _ => return Err(String::from("--format must be toml, json or notation")),
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn run_import(args: &Args) -> Result<ExitCode, String> {
+    let path = &args.tileset;
+    let contents =
+        fs::read_to_string(path).map_err(|e| format!("Couldn't read {}: {e}", path.display()))?;
+    let extension = path.extension().and_then(|e| e.to_str());
+    let tileset = match args.value("--format").or(extension) {
+        Some("toml") => from_toml(&contents),
+        Some("json") => from_json(&contents),
+        _ => from_notation(&contents),
+    }
+    .map_err(|e| format!("Couldn't import {}: {e}", path.display()))?;
+
+    print!("{}", to_canonical_toml(&tileset));
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn main() -> ExitCode {
+    let result = Args::parse().and_then(|args| match args.command.as_str() {
+        "lint" => run_lint(&args),
+        "normalize" => run_normalize(&args),
+        "stats" => run_stats(&args),
+        "render" => run_render(&args),
+        "generate" => run_generate(&args),
+        "export" => run_export(&args),
+        "import" => run_import(&args),
+        "help" | "--help" | "-h" => {
+            println!("{USAGE}");
+            Ok(ExitCode::SUCCESS)
+        }
+        command => Err(format!("Unknown command {command}\n\n{USAGE}")),
+    });
+
+    result.unwrap_or_else(|message| {
+        eprintln!("{message}");
+        ExitCode::FAILURE
+    })
+}
@@ -1,4 +1,5 @@
 use godot::builtin::Vector2;
+use godot::classes::Container;
 use godot::classes::INode2D;
 use godot::classes::Node;
 use godot::classes::Node2D;
@@ -10,14 +11,18 @@ use godot::prelude::godot_api;
 use godot::prelude::load;
 use godot::prelude::GodotClass;
 
-use crate::game::entities::movement::STARTING_POSITIONS;
+use crate::game::entities::deck::TileDeck;
+use crate::game::entities::entity_id::EntityScope;
 use crate::game::entities::player_token::PlayerToken;
 use crate::game::entities::BoardComponent;
+use crate::game::entities::EntityManager;
 use crate::util::Logger;
 use crate::util::RootWindow;
 
 pub mod components;
+pub mod console;
 pub mod entities;
+pub mod integration;
 
 #[derive(Debug, GodotClass)]
 #[class(init, base=Node2D)]
@@ -31,13 +36,36 @@ impl RunningGameScene {
 
         root.get_node_as("./Running")
     }
+    /// Puts a TileDeck on the deck board for each deck of the tileset.
+    fn place_tile_decks(&mut self) {
+        let deck_count = BoardComponent::get(&self.base()).bind().deck_count();
+        let slot_scene = load::<PackedScene>("res://game/objects/tile_deck_slot.tscn");
+        let mut container = self
+            .base()
+            .get_node_as::<Container>("./Control/TileDeckBoard/MarginContainer/HBoxContainer");
+        let running = self.to_gd();
+
+        for deck_index in 0..deck_count as u8 {
+            let mut slot = slot_scene.instantiate_as::<Container>();
+
+            slot.get_node_as::<TileDeck>("./CenterContainer/TileDeck")
+                .bind_mut()
+                .assign_deck(deck_index);
+            container.add_child(&slot);
+            slot.set_owner(&running);
+        }
+
+        Logger::info(&format!("Placed {deck_count} tile decks"));
+    }
     fn place_starting_tokens(&mut self) {
         let mut gd_board = BoardComponent::get(&self.base());
         let token_scene = load::<PackedScene>("res://game/objects/player_token.tscn");
         let mut container = self.base().get_node_as::<Node2D>("./PlayerTokens");
         let running = self.to_gd();
 
-        for (coordinates, player) in STARTING_POSITIONS {
+        let starting_positions = gd_board.bind().rules().starting_positions;
+
+        for (coordinates, player) in starting_positions {
             if let Err(error) = gd_board.bind().get_tile_at(coordinates.0, coordinates.1) {
                 Logger::error(&format!("Couldn't place starting token: {error:?}"));
                 continue;
@@ -64,6 +92,13 @@ impl RunningGameScene {
 #[godot_api]
 impl INode2D for RunningGameScene {
     fn ready(&mut self) {
+        self.place_tile_decks();
         self.place_starting_tokens();
     }
+    fn exit_tree(&mut self) {
+        // Everything in the Running scope is freed along with this scene
+        EntityManager::get_manager(&self.base())
+            .bind_mut()
+            .free(EntityScope::Running);
+    }
 }
@@ -1,16 +1,19 @@
-use godot::builtin::Variant;
 use godot::classes::Area2D;
 use godot::classes::CollisionPolygon2D;
 use godot::classes::CollisionShape2D;
 use godot::classes::IArea2D;
+use godot::classes::Node;
 use godot::classes::PanelContainer;
 use godot::obj::Base;
 use godot::obj::Gd;
+use godot::obj::Inherits;
+use godot::obj::InstanceId;
 use godot::obj::WithBaseField;
 use godot::prelude::godot_api;
 use godot::prelude::GodotClass;
 
 use crate::game::entities::tile::Tile;
+use crate::game::RunningGameScene;
 
 #[derive(Debug, GodotClass)]
 #[class(init, base=Area2D)]
@@ -85,30 +88,6 @@ pub struct CollisionOutline {
 
 #[godot_api]
 impl CollisionOutline {
-    #[func]
-    fn emit_active_collision(&self) {
-        let mut scene_tree = self.base().get_tree();
-
-        // TODO: Replace this with a global manager implemented in code
-        scene_tree.call_group(
-            "Tiles",
-            "insert_active_collision",
-            &[Variant::from(self.to_gd().instance_id())],
-        );
-    }
-
-    #[func]
-    fn cancel_collision(&self) {
-        let mut scene_tree = self.base().get_tree();
-
-        // TODO: Replace this with a global manager implemented in code
-        scene_tree.call_group(
-            "Tiles",
-            "remove_active_collision",
-            &[Variant::from(self.to_gd().instance_id())],
-        );
-    }
-
     #[signal]
     pub fn submitted_at();
 }
@@ -182,7 +161,9 @@ impl IArea2D for CollisionOutline {
         self.base()
             .signals()
             .mouse_entered()
-            .connect_other(&*self, |this| this.emit_active_collision());
+            .connect_other(&*self, |this| {
+                HoverManager::enter(&this.to_gd(), HoverKind::PlacementOutline)
+            });
 
         self.base()
             .signals()
@@ -192,12 +173,16 @@ impl IArea2D for CollisionOutline {
         self.base()
             .signals()
             .mouse_exited()
-            .connect_other(&*self, |this| this.cancel_collision());
+            .connect_other(&*self, |this| HoverManager::exit(&this.to_gd()));
     }
     fn process(&mut self, _dt: f64) {
         let mut outline: Gd<PanelContainer> = self.get_outline_node();
 
-        outline.set_visible(self.outline_visible);
+        // Only the outline winning an overlap is drawn, as that's the one a
+        // click places the active tile against
+        let is_top = HoverManager::is_top(&self.to_gd(), HoverKind::PlacementOutline);
+
+        outline.set_visible(self.outline_visible && is_top);
     }
 }
 
@@ -206,3 +191,218 @@ impl IArea2D for CollisionOutline {
 pub struct ActionCollisionSquare {
     base: Base<Area2D>,
 }
+
+#[godot_api]
+impl IArea2D for ActionCollisionSquare {
+    fn ready(&mut self) {
+        self.base()
+            .signals()
+            .mouse_entered()
+            .connect_other(&*self, |this| {
+                HoverManager::enter(&this.to_gd(), HoverKind::Action)
+            });
+
+        self.base()
+            .signals()
+            .mouse_exited()
+            .connect_other(&*self, |this| HoverManager::exit(&this.to_gd()));
+    }
+}
+
+/// What a hovered area is used for. Areas of different kinds may overlap
+/// freely, e.g. a move destination under a treasure tooltip.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HoverKind {
+    /// A `CollisionOutline` the active tile can be placed against
+    PlacementOutline,
+    /// A tile's `MoveArea` while it's a legal move destination
+    MoveDestination,
+    /// An `ActionCollisionSquare`, e.g. a treasure's tooltip trigger
+    Action,
+}
+
+#[derive(Debug, Clone)]
+struct HoverEntry<K> {
+    key: K,
+    kind: HoverKind,
+    entered_at: u64,
+}
+
+/// Tracks every area under the cursor and resolves overlaps between areas of
+/// the same kind.
+///
+/// Neighbouring tiles' areas overlap along their shared edge, so the cursor is
+/// often inside several at once. The area entered last wins, which is the one
+/// the cursor is moving into.
+#[derive(Debug, Clone)]
+pub struct HoverTracker<K> {
+    entries: Vec<HoverEntry<K>>,
+    clock: u64,
+}
+
+impl<K> Default for HoverTracker<K> {
+    fn default() -> Self {
+        Self {
+            entries: vec![],
+            clock: 0,
+        }
+    }
+}
+
+impl<K> HoverTracker<K>
+where
+    K: Copy + Eq,
+{
+    pub fn enter(&mut self, key: K, kind: HoverKind) {
+        self.exit(key);
+
+        self.clock += 1;
+        self.entries.push(HoverEntry {
+            key,
+            kind,
+            entered_at: self.clock,
+        });
+    }
+    pub fn exit(&mut self, key: K) {
+        self.entries.retain(|entry| entry.key != key);
+    }
+    /// Drops entries whose areas went away without reporting an exit, e.g.
+    /// because they were freed while hovered.
+    pub fn retain(&mut self, mut keep: impl FnMut(K) -> bool) {
+        self.entries.retain(|entry| keep(entry.key));
+    }
+    pub fn top(&self, kind: HoverKind) -> Option<K> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.kind == kind)
+            .max_by_key(|entry| entry.entered_at)
+            .map(|entry| entry.key)
+    }
+    pub fn is_top(&self, key: K, kind: HoverKind) -> bool {
+        self.top(kind) == Some(key)
+    }
+}
+
+/// Keeps track of the `CollisionOutline`, `MoveArea` and
+/// `ActionCollisionSquare` areas under the cursor. Areas report entering and
+/// exiting here, and whoever reacts to a hover asks which area of a kind wins
+/// the overlap.
+#[derive(Debug, GodotClass)]
+#[class(init, base=Node)]
+pub struct HoverManager {
+    base: Base<Node>,
+
+    hovered: HoverTracker<InstanceId>,
+}
+
+impl HoverManager {
+    pub fn get(node: &Node) -> Gd<HoverManager> {
+        let running_scene = RunningGameScene::get_running_game(node);
+
+        running_scene.get_node_as::<HoverManager>("./HoverManager")
+    }
+    pub fn enter(area: &Gd<impl Inherits<Area2D>>, kind: HoverKind) {
+        let area = area.clone().upcast::<Area2D>();
+
+        Self::get(&area)
+            .bind_mut()
+            .hovered
+            .enter(area.instance_id(), kind);
+    }
+    pub fn exit(area: &Gd<impl Inherits<Area2D>>) {
+        let area = area.clone().upcast::<Area2D>();
+
+        Self::get(&area).bind_mut().hovered.exit(area.instance_id());
+    }
+    pub fn is_top(area: &Gd<impl Inherits<Area2D>>, kind: HoverKind) -> bool {
+        let area = area.clone().upcast::<Area2D>();
+
+        Self::get(&area)
+            .bind()
+            .hovered
+            .is_top(area.instance_id(), kind)
+    }
+    /// The area of `kind` winning the overlap under the cursor. Areas freed
+    /// while hovered never report their exit, so they are dropped here.
+    pub fn top<T>(&mut self, kind: HoverKind) -> Option<Gd<T>>
+    where
+        T: Inherits<Area2D>,
+    {
+        self.hovered
+            .retain(|id| Gd::<Area2D>::try_from_instance_id(id).is_ok());
+
+        self.hovered
+            .top(kind)
+            .and_then(|id| Gd::try_from_instance_id(id).ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HoverKind;
+    use super::HoverTracker;
+
+    #[test]
+    fn nothing_is_hovered_initially() {
+        let tracker = HoverTracker::<u32>::default();
+
+        assert_eq!(tracker.top(HoverKind::PlacementOutline), None);
+    }
+
+    #[test]
+    fn the_last_entered_area_wins_an_overlap() {
+        let mut tracker = HoverTracker::default();
+
+        tracker.enter(1, HoverKind::PlacementOutline);
+        tracker.enter(2, HoverKind::PlacementOutline);
+
+        assert_eq!(tracker.top(HoverKind::PlacementOutline), Some(2));
+    }
+
+    #[test]
+    fn exiting_the_top_area_falls_back_to_the_one_below() {
+        let mut tracker = HoverTracker::default();
+
+        tracker.enter(1, HoverKind::PlacementOutline);
+        tracker.enter(2, HoverKind::PlacementOutline);
+        tracker.exit(2);
+
+        assert!(tracker.is_top(1, HoverKind::PlacementOutline));
+    }
+
+    #[test]
+    fn reentering_an_area_moves_it_to_the_top() {
+        let mut tracker = HoverTracker::default();
+
+        tracker.enter(1, HoverKind::MoveDestination);
+        tracker.enter(2, HoverKind::MoveDestination);
+        tracker.enter(1, HoverKind::MoveDestination);
+        assert!(tracker.is_top(1, HoverKind::MoveDestination));
+
+        tracker.exit(1);
+        assert!(tracker.is_top(2, HoverKind::MoveDestination));
+    }
+
+    #[test]
+    fn kinds_are_resolved_independently() {
+        let mut tracker = HoverTracker::default();
+
+        tracker.enter(1, HoverKind::MoveDestination);
+        tracker.enter(2, HoverKind::Action);
+
+        assert!(tracker.is_top(1, HoverKind::MoveDestination));
+        assert!(tracker.is_top(2, HoverKind::Action));
+        assert_eq!(tracker.top(HoverKind::PlacementOutline), None);
+    }
+
+    #[test]
+    fn retain_drops_areas_that_went_away() {
+        let mut tracker = HoverTracker::default();
+
+        tracker.enter(1, HoverKind::Action);
+        tracker.enter(2, HoverKind::Action);
+        tracker.retain(|key| key != 2);
+
+        assert_eq!(tracker.top(HoverKind::Action), Some(1));
+    }
+}
@@ -9,11 +9,10 @@ use godot::obj::Gd;
 use godot::prelude::godot_api;
 use godot::prelude::GodotClass;
 
+use crate::util::errors::GameError;
 use crate::util::flags::OasisLayoutFlags;
-use crate::util::loader::GameConfig;
-use crate::util::loader::TileConfig;
-use crate::util::loader::TilesetConfig;
 use crate::util::loader::TomlLoader;
+use crate::util::tileset::TileConfig;
 
 #[derive(Derivative, Debug, Clone)]
 #[derivative(Default)]
@@ -153,28 +152,33 @@ impl NextTileData for TileDeckComponent {
 
 #[godot_api]
 impl TileDeckComponent {
-    pub fn from_tile_deck_index(node: &Node, deck_index: u8) -> Gd<Self> {
-        Gd::from_init_fn(|base| {
-            let tileset = TomlLoader::get(node, GameConfig::Tileset)
-                .expect("Couldn't load tileset. Check if config/tileset.toml exists");
-
-            let parsed_config = TilesetConfig::try_from(&tileset)
-                .expect("Couldn't parse tileset. Check syntax of config/tileset.toml");
-
-            let tiles = parsed_config
-                .deck
-                .get(deck_index as usize)
-                .expect("Couldn't parse tileset. Check syntax of config/tileset.toml")
-                .0
-                .clone()
-                .map(TileData::from);
-
-            Self {
-                base,
-                index: 0,
-                tiles,
-            }
-        })
+    /// Makes the `nth` tile still in the deck, from 0, the next one drawn.
+    /// Returns false if fewer tiles are left.
+    pub fn move_to_top(&mut self, nth: u8) -> bool {
+        let next = self.index as usize;
+
+        if next + nth as usize >= self.tiles.len() {
+            return false;
+        }
+
+        self.tiles[next..=next + nth as usize].rotate_right(1);
+
+        true
+    }
+    pub fn from_tile_deck_index(node: &Node, deck_index: u8) -> Result<Gd<Self>, GameError> {
+        let tiles = TomlLoader::tileset(node)?
+            .deck
+            .get(deck_index as usize)
+            .ok_or(GameError::MissingDeck(deck_index + 1))?
+            .tiles
+            .clone()
+            .map(TileData::from);
+
+        Ok(Gd::from_init_fn(|base| Self {
+            base,
+            index: 0,
+            tiles,
+        }))
     }
 }
 
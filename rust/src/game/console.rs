@@ -0,0 +1,145 @@
+use godot::builtin::GString;
+use godot::classes::Control;
+use godot::classes::IControl;
+use godot::classes::InputEvent;
+use godot::classes::LineEdit;
+use godot::classes::RichTextLabel;
+use godot::obj::Base;
+use godot::obj::Gd;
+use godot::obj::WithBaseField;
+use godot::prelude::godot_api;
+use godot::prelude::GodotClass;
+
+use crate::game::entities::deck::TileDeck;
+use crate::game::entities::BoardComponent;
+use crate::util::console::ConsoleCommand;
+use crate::util::debug_tools_enabled;
+use crate::util::input::InputActions;
+use crate::util::loader::TomlLoader;
+use crate::util::Logger;
+
+/// Toggleable console for poking at a running game. `move`, `draw` and
+/// `skip-move` play the active player's turn the way clicking does, so the
+/// rules can reject them. Only available in debug builds or with the `DEBUG`
+/// environment flag set.
+#[derive(GodotClass)]
+#[class(init, base=Control)]
+pub struct DebugConsole {
+    base: Base<Control>,
+}
+
+impl DebugConsole {
+    fn get_input(&self) -> Gd<LineEdit> {
+        self.base().get_node_as("./Panel/VBoxContainer/Input")
+    }
+    fn get_output(&self) -> Gd<RichTextLabel> {
+        self.base().get_node_as("./Panel/VBoxContainer/Output")
+    }
+    fn print(&self, text: &str) {
+        self.get_output().add_text(&format!("{text}\n"));
+    }
+    fn toggle(&mut self) {
+        let visible = !self.base().is_visible();
+
+        self.base_mut().set_visible(visible);
+
+        if visible {
+            self.get_input().grab_focus();
+        }
+    }
+    fn submit(&mut self, line: GString) {
+        let line = line.to_string();
+
+        self.get_input().clear();
+
+        if line.trim().is_empty() {
+            return;
+        }
+
+        self.print(&format!("> {line}"));
+        Logger::debug(&format!("Console: {line}"));
+
+        let result = ConsoleCommand::try_from(line.as_str())
+            .map_err(|error| error.to_string())
+            .and_then(|command| self.execute(command));
+
+        match result {
+            Ok(output) => self.print(&output),
+            Err(error) => self.print(&format!("error: {error}")),
+        }
+    }
+    fn execute(&self, command: ConsoleCommand) -> Result<String, String> {
+        let base = self.base();
+        let mut gd_board = BoardComponent::get(&base);
+
+        match command {
+            ConsoleCommand::Move(coordinates) => {
+                gd_board
+                    .bind_mut()
+                    .move_active_caravan(coordinates)
+                    .map_err(|error| error.to_string())?;
+
+                Ok(format!("Moved to {coordinates:?}"))
+            }
+            ConsoleCommand::Draw { deck, tile } => {
+                let mut gd_deck = TileDeck::find(&base, deck)
+                    .ok_or(format!("Couldn't find deck {}", deck + 1))?;
+
+                gd_deck.bind_mut().draw_tile(tile)?;
+
+                Ok(format!("Drew tile {} of deck {}", tile + 1, deck + 1))
+            }
+            ConsoleCommand::SkipMove => {
+                gd_board
+                    .bind_mut()
+                    .skip_move()
+                    .map_err(|error| error.to_string())?;
+
+                Ok(String::from("Skipped the move"))
+            }
+            ConsoleCommand::DumpBoard(charset) => Ok(gd_board.bind().dump(charset)),
+            ConsoleCommand::Seed => Ok(match TomlLoader::tileset_seed(&base) {
+                Some(seed) => format!("Seed {seed}, the random tileset was generated from it"),
+                None => String::from("No seed, this game plays a fixed tileset"),
+            }),
+            ConsoleCommand::ReloadConfig => {
+                TomlLoader::reload_configs(&base);
+
+                Ok(String::from(
+                    "Reloaded the configs, the log lists any that were kept",
+                ))
+            }
+            ConsoleCommand::Help => Ok(ConsoleCommand::help()),
+        }
+    }
+}
+
+#[godot_api]
+impl IControl for DebugConsole {
+    fn ready(&mut self) {
+        if !debug_tools_enabled() {
+            return self.base_mut().queue_free();
+        }
+
+        self.base_mut().set_visible(false);
+
+        let gd_self = self.to_gd();
+
+        self.get_input()
+            .signals()
+            .text_submitted()
+            .connect_other(&gd_self, Self::submit);
+    }
+    fn input(&mut self, event: Gd<InputEvent>) {
+        if !event.is_action_pressed(&String::from(InputActions::ToggleConsole)) {
+            return;
+        }
+
+        self.toggle();
+
+        // Keep the key from being typed into the console
+        if let Some(mut viewport) = self.base().get_viewport() {
+            viewport.set_input_as_handled();
+        }
+    }
+}
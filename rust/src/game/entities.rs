@@ -1,22 +1,42 @@
 use std::collections::HashMap;
-use std::collections::HashSet;
 use std::num::TryFromIntError;
 
+use godot::builtin::GString;
+use godot::builtin::PackedVector2Array;
+use godot::builtin::Vector2;
 use godot::classes::INode;
+use godot::classes::Label;
+use godot::classes::Line2D;
 use godot::classes::Node2D;
+use godot::meta::ToGodot;
 use godot::obj::WithBaseField;
 use godot::obj::WithUserSignals;
 use godot::prelude::godot_api;
 use thiserror::Error;
 
+use crate::game::entities::board_dump::dump_board;
+use crate::game::entities::board_dump::BoardOverlay;
+use crate::game::entities::board_dump::Charset;
+use crate::game::entities::board_model::BoardModel;
+use crate::game::entities::board_model::BoardModelError;
+use crate::game::entities::entity_id::EntityId;
+use crate::game::entities::entity_id::EntityLookupError;
+use crate::game::entities::entity_id::EntityRegistry;
+use crate::game::entities::entity_id::EntityScope;
 use crate::game::entities::movement::BoardGraph;
+use crate::game::entities::movement::CaravanMove;
 use crate::game::entities::player::PlayerName;
 use crate::game::entities::player_token::PlayerToken;
 use crate::game::entities::tile::Tile;
-use crate::game::entities::turn::TurnState;
 use crate::game::RunningGameScene;
+use crate::util::errors::GameError;
 use crate::util::flags::CardinalDirectionFlags;
 use crate::util::flags::DIRECTIONS;
+use crate::util::loader::GameConfig;
+use crate::util::loader::TomlLoader;
+use crate::util::rules::RulesConfig;
+use crate::util::ErrorReporter;
+use crate::util::LogLevel;
 use crate::util::Logger;
 use godot::classes::Node;
 use godot::obj::Base;
@@ -26,7 +46,10 @@ use godot::prelude::GodotClass;
 
 use crate::util::RootWindow;
 
+pub mod board_dump;
+pub mod board_model;
 pub mod deck;
+pub mod entity_id;
 pub mod movement;
 pub mod player;
 pub mod player_token;
@@ -34,6 +57,9 @@ pub mod tile;
 pub mod treasure;
 pub mod turn;
 
+// Seconds a caravan takes to move one tile along its route
+const ROUTE_STEP_SECONDS: f64 = 0.15;
+
 trait Entity
 where
     Self: GodotClass,
@@ -41,95 +67,88 @@ where
     fn register(&mut self);
 }
 
-pub enum EntityScope {
-    Global,
-    Running,
-}
-
 #[derive(Debug, GodotClass)]
-#[class(init, base=Node)]
+#[class(base=Node)]
 pub struct EntityManager {
     base: Base<Node>,
 
-    // Lists are scoped to enable freeing when the lifetime of a scope is over
+    // Registries are scoped to enable freeing when the lifetime of a scope is over
     // E.g. on game exit/reset, the "running" scope can be cleared without worry
-    #[init(val=vec![])]
-    _global: Vec<u64>,
-    #[init(val=vec![])]
-    running: Vec<Option<InstanceId>>,
+    global: EntityRegistry<InstanceId>,
+    running: EntityRegistry<InstanceId>,
+}
+
+#[godot_api]
+impl INode for EntityManager {
+    fn init(base: Base<Node>) -> Self {
+        Self {
+            base,
+            global: EntityRegistry::new(EntityScope::Global),
+            running: EntityRegistry::new(EntityScope::Running),
+        }
+    }
 }
 
 impl EntityManager {
-    fn get_manager(node: &Node) -> Gd<EntityManager> {
+    pub fn get_manager(node: &Node) -> Gd<EntityManager> {
         let root = node.get_tree_root();
 
         root.get_node_as("./GlobalEntityManager")
     }
-    fn register(&mut self, instance_id: InstanceId, scope: EntityScope) -> u64 {
+    fn registry(&self, scope: EntityScope) -> &EntityRegistry<InstanceId> {
         match scope {
-            EntityScope::Global => todo!(),
-            EntityScope::Running => {
-                self.running.push(Some(instance_id));
-                Logger::debug(&format!(
-                    "Registered instance id {instance_id:?} with entity id {}",
-                    self.running.len()
-                ));
-                self.running.len() as u64
-            }
+            EntityScope::Global => &self.global,
+            EntityScope::Running => &self.running,
         }
     }
-    fn _get(&self, id: u64, scope: EntityScope) -> Option<InstanceId> {
+    fn registry_mut(&mut self, scope: EntityScope) -> &mut EntityRegistry<InstanceId> {
         match scope {
-            EntityScope::Global => todo!(),
-            EntityScope::Running => {
-                Logger::debug(&format!(
-                    "Got instance id {:?} for entity id {id}",
-                    self.running[(id - 1) as usize],
-                ));
-
-                self.running[(id - 1) as usize]
-            }
+            EntityScope::Global => &mut self.global,
+            EntityScope::Running => &mut self.running,
         }
     }
-    fn get_instance<T>(&self, id: u64, scope: EntityScope) -> Option<Gd<T>>
+    pub fn register(&mut self, instance_id: InstanceId, scope: EntityScope) -> EntityId {
+        let id = self.registry_mut(scope).insert(instance_id);
+
+        Logger::debug(&format!(
+            "Registered instance id {instance_id:?} with entity id {id}"
+        ));
+
+        id
+    }
+    pub fn get(&self, id: EntityId) -> Result<InstanceId, EntityLookupError> {
+        let instance_id = *self.registry(id.scope()).get(id)?;
+
+        Logger::debug(&format!(
+            "Got instance id {instance_id:?} for entity id {id}"
+        ));
+
+        Ok(instance_id)
+    }
+    pub fn get_instance<T>(&self, id: EntityId) -> Result<Gd<T>, EntityLookupError>
     where
         T: GodotClass,
     {
-        let instance_id = match scope {
-            EntityScope::Global => todo!(),
-            EntityScope::Running => self.running[(id - 1) as usize],
-        }?;
+        let instance_id = *self.registry(id.scope()).get(id)?;
 
-        Gd::try_from_instance_id(instance_id).ok()
-    }
-    fn _remove(&mut self, id: u64, scope: EntityScope) -> Result<(), &'static str> {
-        match scope {
-            EntityScope::Global => todo!(),
-            EntityScope::Running => {
-                if self.running[(id - 1) as usize].is_some() {
-                    self.running[(id - 1) as usize] = None;
-
-                    Ok(())
-                } else {
-                    Err("Attempted to remove Entity that is already set to None")
-                }
-            }
-        }
+        Gd::try_from_instance_id(instance_id)
+            .map_err(|_| EntityLookupError::InstanceNotFoundError(id))
     }
-    fn _clear_scope(&mut self, scope: EntityScope) {
-        match scope {
-            EntityScope::Global => todo!(),
-            EntityScope::Running => self.running.clear(),
-        }
+    pub fn remove(&mut self, id: EntityId) -> Result<InstanceId, EntityLookupError> {
+        self.registry_mut(id.scope()).remove(id)
     }
-    fn _free(&mut self, scope: EntityScope) {
-        match scope {
-            EntityScope::Global => todo!(),
-            EntityScope::Running => {
-                self._clear_scope(EntityScope::Running);
-                self.running.shrink_to_fit()
-            }
-        }
+    /// Forgets every entity of `scope`. Their ids turn stale, so lookups
+    /// through ids kept past the scope's lifetime fail instead of resolving
+    /// to freed instances.
+    pub fn free(&mut self, scope: EntityScope) {
+        let registry = self.registry_mut(scope);
+
+        Logger::debug(&format!(
+            "Freeing {} entities of the {scope:?} scope",
+            registry.len()
+        ));
+
+        registry.clear();
     }
 }
 
@@ -148,9 +167,11 @@ pub enum TileGetError {
     #[error("Tile does not exist at requested position - x:{0}, y:{1}")]
     TileCoordinateNotFoundError(u8, u8),
     #[error("Tile does not exist with requested id: {0}")]
-    TileIdNotFoundError(u64),
-    #[error("Tile instance was not found with requested id: {0}\nThis is an entity manager ID; not a Godot built-in Instance Id")]
-    TileInstanceNotFoundError(i64),
+    TileIdNotFoundError(EntityId),
+    #[error("Tile was not registered with the entity manager yet")]
+    TileNotRegisteredError,
+    #[error("Tile instance was not found: {0}")]
+    TileInstanceNotFoundError(#[from] EntityLookupError),
     #[error("{0}")]
     IntegerConversionError(#[from] TryFromIntError),
 }
@@ -167,18 +188,36 @@ pub enum TileGetError {
 pub struct BoardComponent {
     base: Base<Node>,
 
-    #[init(val = [[0; 11]; 11])]
-    placed_tiles: [[u64; 11]; 11],
+    #[init(val = [[None; 11]; 11])]
+    placed_tiles: [[Option<EntityId>; 11]; 11],
     #[init(val=HashMap::new())]
-    tile_coordinates: HashMap<u64, (usize, usize)>,
-    active_tile_deck: u8,
+    tile_coordinates: HashMap<EntityId, (usize, usize)>,
+    #[init(val = vec![])]
+    exhausted_decks: Vec<bool>,
+    // For each deck, the deck that must be empty before it can be drawn from
+    #[init(val = vec![])]
+    deck_unlocks: Vec<Option<u8>>,
 
     #[init(val = 4)]
     player_count: u8,
-    turn: TurnState,
+    // The rules of the game, which the scene mirrors
+    model: BoardModel,
+    // Tiles whose highlights are currently switched on. Phase changes only
+    // touch these and freshly placed tiles, instead of every tile on the board.
+    #[init(val = HashMap::new())]
+    highlighted_destinations: HashMap<(u8, u8), bool>,
+    #[init(val = None)]
+    highlighted_caravan: Option<(u8, u8)>,
+    #[init(val = None)]
+    explorable_tile: Option<(u8, u8)>,
+    #[init(val = vec![])]
+    fresh_tiles: Vec<(u8, u8)>,
 
+    // Move to each highlighted destination, from the move search
     #[init(val = HashMap::new())]
-    player_positions: HashMap<PlayerName, (u8, u8)>,
+    moves: HashMap<(u8, u8), CaravanMove>,
+    #[init(val = None)]
+    route_preview: Option<(u8, u8)>,
 
     // A Tile queues these while it's borrowed by its own callback. Applying
     // the change now would borrow it again, so it runs later via `call_deferred`.
@@ -191,6 +230,19 @@ pub struct BoardComponent {
 #[godot_api]
 impl INode for BoardComponent {
     fn ready(&mut self) {
+        self.model = BoardModel::new(TomlLoader::rules(&self.base()));
+        self.deck_unlocks = match TomlLoader::tileset(&self.base()) {
+            Ok(tileset) => tileset.deck_unlocks(),
+            Err(error) => return ErrorReporter::report(&self.base(), GameError::from(error)),
+        };
+        self.exhausted_decks = vec![false; self.deck_unlocks.len()];
+
+        TomlLoader::global(&self.base())
+            .signals()
+            .config_reloaded()
+            .connect_other(&*self, Self::on_config_reloaded);
+        self.refresh_desert_chain();
+
         self.signals()
             .tile_placed()
             .connect_self(Self::on_tile_placed);
@@ -204,13 +256,23 @@ impl BoardComponent {
 
         running_scene.get_node_as::<BoardComponent>("./BoardComponent")
     }
-    pub fn add_tile_at(&mut self, id: u64, x: u8, y: u8) -> Result<(), TileAddError> {
-        if self.placed_tiles[x as usize][y as usize] != 0 {
+    pub fn add_tile_at(
+        &mut self,
+        id: EntityId,
+        x: u8,
+        y: u8,
+        oasis_directions: CardinalDirectionFlags,
+        treasure_directions: CardinalDirectionFlags,
+    ) -> Result<(), TileAddError> {
+        if self.placed_tiles[x as usize][y as usize].is_some() {
             return Err(TileAddError::TileExistsError(x, y));
         }
 
-        self.placed_tiles[x as usize][y as usize] = id;
+        self.placed_tiles[x as usize][y as usize] = Some(id);
         self.tile_coordinates.insert(id, (x as usize, y as usize));
+        self.model
+            .place_tile((x, y), oasis_directions, treasure_directions);
+        self.fresh_tiles.push((x, y));
 
         Logger::debug(&format!("Placed tile {id} at {x}, {y}"));
 
@@ -221,18 +283,14 @@ impl BoardComponent {
             return Err(TileGetError::TileCoordinateOutOfBoundsError);
         }
 
-        let id = self.placed_tiles[x as usize][y as usize];
+        let id = self.placed_tiles[x as usize][y as usize]
+            .ok_or(TileGetError::TileCoordinateNotFoundError(x, y))?;
 
-        if id == 0 {
-            return Err(TileGetError::TileCoordinateNotFoundError(x, y));
-        }
-
-        EntityManager::get_manager(&self.base())
+        Ok(EntityManager::get_manager(&self.base())
             .bind()
-            .get_instance(id, EntityScope::Running)
-            .ok_or(TileGetError::TileIdNotFoundError(id))
+            .get_instance(id)?)
     }
-    pub fn get_tile_coordinates(&self, id: u64) -> Result<(u8, u8), TileGetError> {
+    pub fn get_tile_coordinates(&self, id: EntityId) -> Result<(u8, u8), TileGetError> {
         let (x, y) = self
             .tile_coordinates
             .get(&id)
@@ -241,40 +299,82 @@ impl BoardComponent {
         Ok(((*x).try_into()?, (*y).try_into()?))
     }
     pub fn set_player_position(&mut self, player: PlayerName, coordinates: (u8, u8)) {
-        self.player_positions.insert(player, coordinates);
+        self.model.set_position(player, coordinates);
     }
     pub fn get_player_position(&self, player: PlayerName) -> Option<(u8, u8)> {
-        self.player_positions.get(&player).copied()
+        self.model.position(player)
     }
     pub fn active_player(&self) -> PlayerName {
-        self.turn.active_player()
+        self.model.turn().active_player()
     }
     pub fn can_move(&self) -> bool {
-        self.turn.can_move()
+        self.model.turn().can_move()
+    }
+    pub fn graph(&self) -> &BoardGraph {
+        self.model.graph()
+    }
+    fn with_tile_at(&self, coordinates: (u8, u8), update: impl FnOnce(&mut Tile)) {
+        if let Ok(mut gd_tile) = self.get_tile_at(coordinates.0, coordinates.1) {
+            update(&mut gd_tile.bind_mut());
+        }
     }
-    fn placed_coordinates(&self) -> Vec<(u8, u8)> {
-        self.tile_coordinates
-            .values()
-            .map(|&(x, y)| (x as u8, y as u8))
-            .collect()
+    /// Resets the highlights of tiles placed since the last phase change,
+    /// which are placed with every collision enabled.
+    fn settle_fresh_tiles(&mut self) {
+        for coordinates in std::mem::take(&mut self.fresh_tiles) {
+            self.with_tile_at(coordinates, |tile| {
+                tile.set_move_destination(false);
+                tile.set_explorable_edges(CardinalDirectionFlags::empty());
+                tile.set_active_caravan(false);
+            });
+        }
     }
-    fn build_board_graph(&self) -> BoardGraph {
-        let mut graph = BoardGraph::default();
+    fn highlight_caravan(&mut self, coordinates: Option<(u8, u8)>) {
+        if self.highlighted_caravan == coordinates {
+            return;
+        }
 
-        for coordinates in self.placed_coordinates() {
-            if let Ok(tile) = self.get_tile_at(coordinates.0, coordinates.1) {
-                graph.insert_tile(coordinates, tile.bind().oasis_directions());
+        if let Some(previous) = self.highlighted_caravan {
+            self.with_tile_at(previous, |tile| tile.set_active_caravan(false));
+        }
+        if let Some(coordinates) = coordinates {
+            self.with_tile_at(coordinates, |tile| tile.set_active_caravan(true));
+        }
+
+        self.highlighted_caravan = coordinates;
+    }
+    /// Highlights `destinations`, each flagged with whether moving there
+    /// interacts with another caravan.
+    fn highlight_destinations(&mut self, destinations: HashMap<(u8, u8), bool>) {
+        for coordinates in self.highlighted_destinations.keys() {
+            if !destinations.contains_key(coordinates) {
+                self.with_tile_at(*coordinates, |tile| tile.set_move_destination(false));
+            }
+        }
+        for (coordinates, interacts) in &destinations {
+            if self.highlighted_destinations.get(coordinates) != Some(interacts) {
+                self.with_tile_at(*coordinates, |tile| {
+                    tile.set_move_interaction(*interacts);
+                    tile.set_move_destination(true);
+                });
             }
         }
 
-        graph
+        self.highlighted_destinations = destinations;
     }
-    fn occupied_by_others(&self, active: PlayerName) -> HashSet<(u8, u8)> {
-        self.player_positions
-            .iter()
-            .filter(|(player, _)| **player != active)
-            .map(|(_, coordinates)| *coordinates)
-            .collect()
+    fn highlight_explorable_tile(&mut self, coordinates: Option<(u8, u8)>) {
+        if let Some(previous) = self.explorable_tile.take() {
+            self.with_tile_at(previous, |tile| {
+                tile.set_explorable_edges(CardinalDirectionFlags::empty())
+            });
+        }
+
+        if let Some(coordinates) = coordinates {
+            let explorable = self.explorable_edges(coordinates);
+
+            self.with_tile_at(coordinates, |tile| tile.set_explorable_edges(explorable));
+            self.explorable_tile = Some(coordinates);
+        }
     }
     /// Sides of the tile at `coordinates` a caravan may explore along. Sides
     /// facing the board border are excluded: no tile may be placed off-board.
@@ -290,13 +390,66 @@ impl BoardComponent {
                 continue;
             }
 
-            if self.placed_tiles[x as usize][y as usize] == 0 {
+            if self.placed_tiles[x as usize][y as usize].is_none() {
                 explorable |= CardinalDirectionFlags::from(&direction);
             }
         }
 
         explorable
     }
+    pub fn rules(&self) -> &RulesConfig {
+        self.model.rules()
+    }
+    /// Index of the deck tiles are drawn from, following the deck order of
+    /// the rules and the tileset's unlock rules. `None` once every deck is
+    /// exhausted.
+    pub fn active_deck(&self) -> Option<u8> {
+        self.rules()
+            .active_deck(&self.deck_unlocks, &self.exhausted_decks)
+    }
+    /// Number of decks in the tileset the game was started with.
+    pub fn deck_count(&self) -> usize {
+        self.exhausted_decks.len()
+    }
+    /// Picks up the unlock rules of an edited tileset. Decks it adds aren't
+    /// drawn from, as there's no TileDeck on the board for them.
+    fn on_config_reloaded(&mut self, path: GString) {
+        if path.to_string() != GameConfig::Tileset.path() {
+            return;
+        }
+
+        match TomlLoader::tileset(&self.base()) {
+            Ok(tileset) => {
+                self.deck_unlocks = tileset.deck_unlocks();
+                self.deck_unlocks.resize(self.deck_count(), None);
+            }
+            Err(error) => Logger::error(&error.to_string()),
+        }
+    }
+    /// Marks `deck` as empty, moving on to the next deck that can be drawn from.
+    pub fn exhaust_deck(&mut self, deck: u8) {
+        if let Some(exhausted) = self.exhausted_decks.get_mut(deck as usize) {
+            *exhausted = true;
+        }
+    }
+    /// Shows how many desert tiles the active player has chained this turn,
+    /// out of the maximum if there is one. Hidden while there's no chain.
+    fn refresh_desert_chain(&self) {
+        let mut label =
+            RunningGameScene::get_running_game(&self.base()).get_node_as::<Label>("./DesertChain");
+        let chain = self.model.turn().desert_chain();
+
+        let text = match self.model.turn().rules().max_chain {
+            Some(max_chain) => format!("Desert chain: {chain}/{max_chain}"),
+            None => format!("Desert chain: {chain}"),
+        };
+
+        label.set_text(&text);
+        label.set_visible(chain > 0);
+    }
+    pub fn get_player_water(&self, player: PlayerName) -> u32 {
+        self.model.water(player)
+    }
     /// Highlights every tile the active caravan may legally move to.
     pub fn enter_move_phase(&mut self) {
         let active = self.active_player();
@@ -305,26 +458,80 @@ impl BoardComponent {
             return;
         };
 
-        let graph = self.build_board_graph();
-        let occupied = self.occupied_by_others(active);
-        let reachable: HashSet<(u8, u8)> =
-            graph.reachable_tiles(from, &occupied).into_iter().collect();
+        let moves = self.model.moves();
+        let reachable: HashMap<(u8, u8), bool> = moves
+            .iter()
+            .map(|(coordinates, caravan_move)| (*coordinates, caravan_move.interacts()))
+            .collect();
+
+        self.moves = moves;
 
         Logger::info(&format!(
             "{active:?} to move from {from:?}: {} reachable tile(s)",
             reachable.len()
         ));
-        Logger::debug(&format!("{active:?} reachable tiles: {reachable:?}"));
+        Logger::debug(&format!(
+            "{active:?} reachable tiles: {:?}",
+            reachable.keys().collect::<Vec<_>>()
+        ));
+
+        if Logger::is_enabled(LogLevel::Debug) {
+            Logger::debug(&format!("Board:\n{}", self.dump(Charset::Ascii)));
+        }
 
-        for coordinates in self.placed_coordinates() {
-            if let Ok(mut gd_tile) = self.get_tile_at(coordinates.0, coordinates.1) {
-                let mut tile = gd_tile.bind_mut();
+        self.settle_fresh_tiles();
+        self.highlight_explorable_tile(None);
+        self.highlight_destinations(reachable);
+        self.highlight_caravan(Some(from));
+    }
+    /// Re-highlights the current phase, e.g. after a placement the rules
+    /// rejected.
+    fn refresh_phase(&mut self) {
+        if self.can_move() {
+            self.enter_move_phase();
+        } else {
+            self.enter_explore_phase();
+        }
+    }
+    /// The turn and every caravan, then the board drawn by [`dump_board`]
+    /// with the active caravan's destinations highlighted.
+    pub fn dump(&self, charset: Charset) -> String {
+        let active = self.active_player();
+        let phase = if self.can_move() { "move" } else { "explore" };
+        let mut lines = vec![format!(
+            "{active:?} to {phase}, active deck {:?}",
+            self.active_deck()
+        )];
 
-                tile.set_move_destination(reachable.contains(&coordinates));
-                tile.set_explorable_edges(CardinalDirectionFlags::empty());
-                tile.set_active_caravan(coordinates == from);
+        let mut players: Vec<(&PlayerName, &(u8, u8))> = self.model.positions().iter().collect();
+
+        players.sort_by_key(|(player, _)| **player as u8);
+
+        for (player, coordinates) in &players {
+            lines.push(format!(
+                "{player:?} at {coordinates:?}, {} water",
+                self.get_player_water(**player)
+            ));
+        }
+
+        let mut treasure_ids = HashMap::new();
+
+        for x in 0..11 {
+            for y in 0..11 {
+                if let Ok(tile) = self.get_tile_at(x, y) {
+                    treasure_ids.insert((x, y), tile.bind().treasure_ids());
+                }
             }
         }
+
+        let overlay = BoardOverlay {
+            caravans: self.model.positions().clone(),
+            reachable: self.moves.keys().copied().collect(),
+            treasure_ids,
+        };
+
+        lines.push(dump_board(self.graph(), &overlay, charset));
+        lines.join("\n")
     }
     /// Clears movement highlights and confines tile placement to the
     /// explorable edges of the active caravan's current tile.
@@ -334,40 +541,62 @@ impl BoardComponent {
 
         Logger::debug(&format!("{active:?} to explore from {active_position:?}"));
 
-        for coordinates in self.placed_coordinates() {
-            let explorable = self.explorable_edges(coordinates);
-
-            if let Ok(mut gd_tile) = self.get_tile_at(coordinates.0, coordinates.1) {
-                let mut tile = gd_tile.bind_mut();
+        self.moves.clear();
+        self.route_preview = None;
+        self.refresh_route_preview();
 
-                tile.set_move_destination(false);
-
-                if Some(coordinates) == active_position {
-                    tile.set_explorable_edges(explorable);
-                } else {
-                    tile.set_explorable_edges(CardinalDirectionFlags::empty());
-                }
+        self.settle_fresh_tiles();
+        self.highlight_destinations(HashMap::new());
+        self.highlight_explorable_tile(active_position);
+        self.highlight_caravan(active_position);
+    }
+    fn get_player_token(&self, player: PlayerName) -> Option<Gd<PlayerToken>> {
+        let container = RunningGameScene::get_running_game(&self.base())
+            .get_node_as::<Node2D>("./PlayerTokens");
 
-                tile.set_active_caravan(Some(coordinates) == active_position);
-            }
-        }
+        container
+            .get_children()
+            .iter_shared()
+            .filter_map(|child| child.try_cast::<PlayerToken>().ok())
+            .find(|token| token.bind().player == player)
+    }
+    fn get_route_preview(&self) -> Gd<Line2D> {
+        RunningGameScene::get_running_game(&self.base()).get_node_as::<Line2D>("./RoutePreview")
+    }
+    /// Global position of the center of the tile at `coordinates`.
+    pub fn get_tile_center(&self, coordinates: (u8, u8)) -> Option<Vector2> {
+        self.get_tile_at(coordinates.0, coordinates.1)
+            .ok()
+            .map(|tile| tile.bind().center())
     }
     /// Moves the player's token onto the center of the tile at `coordinates`.
     fn move_token_to_tile(&self, player: PlayerName, coordinates: (u8, u8)) {
-        let target = match self.get_tile_at(coordinates.0, coordinates.1) {
-            Ok(tile) => tile.bind().center(),
-            Err(_) => return,
+        let Some(target) = self.get_tile_center(coordinates) else {
+            return;
         };
 
-        let container = RunningGameScene::get_running_game(&self.base())
-            .get_node_as::<Node2D>("./PlayerTokens");
+        if let Some(mut token) = self.get_player_token(player) {
+            token.bind_mut().stop_moving();
+            token.set_global_position(target);
+        }
+    }
+    /// Walks the player's token through the centers of the tiles on `route`,
+    /// one tile per step.
+    fn move_token_along(&self, player: PlayerName, route: &[(u8, u8)]) {
+        let Some(mut token) = self.get_player_token(player) else {
+            return;
+        };
 
-        for child in container.get_children().iter_shared() {
-            if let Ok(mut token) = child.try_cast::<PlayerToken>() {
-                if token.bind().player == player {
-                    token.set_global_position(target);
-                    break;
-                }
+        let mut tween = token.bind_mut().start_route();
+
+        for coordinates in route {
+            if let Some(target) = self.get_tile_center(*coordinates) {
+                tween.tween_property(
+                    &token,
+                    "global_position",
+                    &target.to_variant(),
+                    ROUTE_STEP_SECONDS,
+                );
             }
         }
     }
@@ -376,22 +605,84 @@ impl BoardComponent {
     /// correct.
     #[func]
     fn reposition_tokens(&mut self) {
-        for (player, coordinates) in self.player_positions.clone() {
+        for (player, coordinates) in self.model.positions().clone() {
             self.move_token_to_tile(player, coordinates);
         }
     }
-    fn advance_caravan_to(&mut self, player: PlayerName, coordinates: (u8, u8)) {
-        self.set_player_position(player, coordinates);
-        self.move_token_to_tile(player, coordinates);
-    }
-    fn move_active_player_to(&mut self, coordinates: (u8, u8)) {
+    /// Moves the active caravan to `coordinates` if the rules allow it, the
+    /// way clicking the tile does, then moves on to exploring.
+    pub fn move_active_caravan(&mut self, coordinates: (u8, u8)) -> Result<(), BoardModelError> {
         let player = self.active_player();
+        let outcome = self.model.move_caravan(coordinates)?;
 
         Logger::info(&format!("{player:?} moved caravan to {coordinates:?}"));
+        Logger::debug(&format!("{player:?} caravan route: {:?}", outcome.route));
+
+        for (payee, paid) in &outcome.tolls {
+            Logger::info(&format!("{player:?} paid {paid} water to {payee:?}"));
+        }
 
-        self.advance_caravan_to(player, coordinates);
-        self.turn.advance_to_explore();
+        if let Some((displaced, target)) = outcome.displaced {
+            Logger::info(&format!("{player:?} displaced {displaced:?} to {target:?}"));
+
+            self.move_token_along(displaced, &[target]);
+        }
+
+        self.move_token_along(player, &outcome.route);
         self.enter_explore_phase();
+
+        Ok(())
+    }
+    /// Shows the route to `coordinates` while its tile is hovered, or hides
+    /// it once the tile is no longer hovered. Applied by
+    /// [`Self::refresh_route_preview`].
+    pub fn queue_route_preview(&mut self, coordinates: (u8, u8), hovered: bool) {
+        if hovered {
+            self.route_preview = Some(coordinates);
+        } else if self.route_preview == Some(coordinates) {
+            self.route_preview = None;
+        }
+    }
+    /// Draws the route to the hovered destination, starting on the active
+    /// caravan's tile. An oasis crossing at the end of the route is drawn in
+    /// the oasis line color, so it's clear which line the move crosses.
+    ///
+    /// Godot invokes this by name (the `"refresh_route_preview"` string) from
+    /// [`Tile`]'s `process` through `call_deferred`. Renaming the method
+    /// without updating that string breaks the deferred call at runtime.
+    #[func]
+    fn refresh_route_preview(&mut self) {
+        let mut preview = self.get_route_preview();
+        let mut crossing = preview.get_node_as::<Line2D>("./OasisCrossing");
+
+        let route = self.route_preview.and_then(|destination| {
+            let from = self.get_player_position(self.active_player())?;
+            let route = &self.moves.get(&destination)?.route;
+
+            Some((
+                [vec![from], route.tiles.clone()].concat(),
+                route.crosses_oasis,
+            ))
+        });
+
+        let Some((route, crosses_oasis)) = route else {
+            preview.set_visible(false);
+            return;
+        };
+
+        let points: Vec<Vector2> = route
+            .iter()
+            .filter_map(|coordinates| self.get_tile_center(*coordinates))
+            .map(|center| preview.to_local(center))
+            .collect();
+
+        crossing.set_points(&PackedVector2Array::from(
+            &points[points.len().saturating_sub(2)..],
+        ));
+        crossing.set_visible(crosses_oasis);
+
+        preview.set_points(&PackedVector2Array::from(points.as_slice()));
+        preview.set_visible(true);
     }
     /// Applies a queued caravan move.
     ///
@@ -400,8 +691,12 @@ impl BoardComponent {
     /// without updating that string breaks the deferred call at runtime.
     #[func]
     fn apply_pending_move(&mut self) {
-        if let Some(coordinates) = self.pending_move.take() {
-            self.move_active_player_to(coordinates);
+        let Some(coordinates) = self.pending_move.take() else {
+            return;
+        };
+
+        if let Err(error) = self.move_active_caravan(coordinates) {
+            Logger::warn(&error.to_string());
         }
     }
     /// Resolves the turn once a tile has been placed.
@@ -416,31 +711,55 @@ impl BoardComponent {
         };
 
         // Rules: the caravan advances onto the tile it just explored, and a
-        // desert tile grants the same player another move instead of passing.
+        // desert tile grants the same player another move (or explore)
+        // instead of passing, until the chain limit is reached.
         let player = self.active_player();
 
+        let granted = match self.model.enter_explored_tile(coordinates, was_desert_tile) {
+            Ok(granted) => granted,
+            Err(error) => {
+                Logger::error(&error.to_string());
+                return self.refresh_phase();
+            }
+        };
+
+        self.move_token_along(player, &[coordinates]);
+
         Logger::info(&format!(
             "{player:?} explored {coordinates:?}{}",
-            if was_desert_tile {
-                " (desert: same player moves again)"
-            } else {
-                ""
+            match (was_desert_tile, granted) {
+                (true, true) => format!(
+                    " (desert {}: same player gets another {})",
+                    self.model.turn().desert_chain(),
+                    self.model.turn().rules().reward.name()
+                ),
+                (true, false) => " (desert: chain limit reached)".to_owned(),
+                _ => String::new(),
             }
         ));
 
-        self.advance_caravan_to(player, coordinates);
-        self.turn.advance_turn(was_desert_tile);
-        self.enter_move_phase();
+        self.refresh_desert_chain();
+        self.refresh_phase();
     }
     /// Called when the active player draws a tile to explore. Uses up their
     /// move (if unused) and limits placement to their current tile's edges.
     pub fn begin_exploration(&mut self) {
-        if self.turn.can_move() {
-            self.turn.advance_to_explore();
-        }
+        // Fails only once the move is already used up
+        let _ = self.model.skip_move();
 
         self.enter_explore_phase();
     }
+    /// Gives up the active caravan's move to explore from where it stands,
+    /// unless the move is already used up.
+    pub fn skip_move(&mut self) -> Result<(), BoardModelError> {
+        self.model.skip_move()?;
+
+        Logger::info(&format!("{:?} skipped their move", self.active_player()));
+
+        self.enter_explore_phase();
+
+        Ok(())
+    }
     pub fn queue_move(&mut self, coordinates: (u8, u8)) {
         self.pending_move = Some(coordinates);
     }
@@ -0,0 +1,314 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use crate::game::entities::movement::BoardGraph;
+use crate::game::entities::movement::TileState;
+use crate::game::entities::movement::BOARD_SIZE;
+use crate::game::entities::player::PlayerName;
+use crate::util::flags::CardinalDirectionFlags;
+use crate::util::flags::DIRECTIONS;
+use crate::util::tileset::render::TreasureCodes;
+use crate::util::treasures::is_no_treasure;
+
+// A tile is a 5x3 box: its four sides around the caravan and the reachable
+// highlight
+const TILE_COLUMNS: usize = 5;
+const TILE_ROWS: usize = 3;
+// Room for the row numbers left of the board
+const MARGIN: usize = 3;
+// Stands in for treasures whose id the caller didn't pass
+const UNKNOWN_TREASURE: char = '*';
+
+/// Characters to draw the board with. `Ascii` is for logs and terminals that
+/// mangle box drawing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Charset {
+    #[default]
+    Unicode,
+    Ascii,
+}
+
+struct Strokes {
+    /// Desert, then oasis
+    horizontal: [char; 2],
+    vertical: [char; 2],
+    /// Top left, top right, bottom left and bottom right corner, each indexed
+    /// by whether its horizontal and vertical side are oasis, e.g. `╒` joins
+    /// an oasis top side to a desert left side
+    corners: [[char; 4]; 4],
+    reachable: char,
+    empty: char,
+}
+
+const UNICODE: Strokes = Strokes {
+    horizontal: ['─', '═'],
+    vertical: ['│', '║'],
+    corners: [
+        ['┌', '╓', '╒', '╔'],
+        ['┐', '╖', '╕', '╗'],
+        ['└', '╙', '╘', '╚'],
+        ['┘', '╜', '╛', '╝'],
+    ],
+    reachable: '░',
+    empty: '·',
+};
+
+const ASCII: Strokes = Strokes {
+    horizontal: ['-', '='],
+    vertical: ['|', '#'],
+    corners: [['+'; 4]; 4],
+    reachable: ':',
+    empty: '.',
+};
+
+impl Charset {
+    fn strokes(&self) -> &'static Strokes {
+        match self {
+            Self::Unicode => &UNICODE,
+            Self::Ascii => &ASCII,
+        }
+    }
+}
+
+/// What the board model doesn't know but a dump should show.
+#[derive(Debug, Clone, Default)]
+pub struct BoardOverlay {
+    pub caravans: HashMap<PlayerName, (u8, u8)>,
+    /// Highlighted as destinations of the active caravan
+    pub reachable: HashSet<(u8, u8)>,
+    /// Treasure id on the N, E, S and W side of each tile. Uncollected
+    /// treasures of tiles missing here are drawn as `*`.
+    pub treasure_ids: HashMap<(u8, u8), [String; 4]>,
+}
+
+fn initial(player: &PlayerName) -> char {
+    format!("{player:?}").chars().next().unwrap_or('?')
+}
+
+fn render_tile(
+    coordinates: (u8, u8),
+    tile: &TileState,
+    overlay: &BoardOverlay,
+    strokes: &Strokes,
+    codes: &mut TreasureCodes,
+) -> [[char; TILE_COLUMNS]; TILE_ROWS] {
+    let [n, e, s, w] = DIRECTIONS.map(|direction| {
+        tile.oasis_directions
+            .contains(CardinalDirectionFlags::from(&direction)) as usize
+    });
+    let corner = |corner: usize, horizontal: usize, vertical: usize| {
+        strokes.corners[corner][horizontal * 2 + vertical]
+    };
+    let fill = if overlay.reachable.contains(&coordinates) {
+        strokes.reachable
+    } else {
+        ' '
+    };
+    let caravan = overlay
+        .caravans
+        .iter()
+        .find(|(_, position)| **position == coordinates)
+        .map(|(player, _)| initial(player));
+
+    let mut grid = [
+        [
+            corner(0, n, w),
+            strokes.horizontal[n],
+            strokes.horizontal[n],
+            strokes.horizontal[n],
+            corner(1, n, e),
+        ],
+        [
+            strokes.vertical[w],
+            fill,
+            caravan.unwrap_or(fill),
+            fill,
+            strokes.vertical[e],
+        ],
+        [
+            corner(2, s, w),
+            strokes.horizontal[s],
+            strokes.horizontal[s],
+            strokes.horizontal[s],
+            corner(3, s, e),
+        ],
+    ];
+
+    // A treasure takes the middle of its side
+    let sides = [(0, 2), (1, 4), (2, 2), (1, 0)];
+
+    for (idx, (direction, (row, column))) in DIRECTIONS.iter().zip(sides).enumerate() {
+        if !tile
+            .treasure_directions
+            .contains(CardinalDirectionFlags::from(direction))
+        {
+            continue;
+        }
+
+        grid[row][column] = match overlay.treasure_ids.get(&coordinates) {
+            Some(ids) if !is_no_treasure(&ids[idx]) => codes.code(&ids[idx]),
+            _ => UNKNOWN_TREASURE,
+        };
+    }
+
+    grid
+}
+
+/// Draws every position of the board as a text grid, columns numbered by x
+/// and rows by y. Each placed tile is a box whose oasis sides are double
+/// lines and desert sides single ones, with uncollected treasures as letters
+/// on their sides. Inside it is a caravan as its player's initial, and
+/// reachable tiles are shaded.
+/// A legend of the treasure letters follows the grid.
+pub fn dump_board(graph: &BoardGraph, overlay: &BoardOverlay, charset: Charset) -> String {
+    let strokes = charset.strokes();
+    let mut codes = TreasureCodes::default();
+    let columns: String = (0..BOARD_SIZE)
+        .map(|x| format!("{x:^TILE_COLUMNS$}"))
+        .collect();
+    let mut lines = vec![format!("{:MARGIN$}{}", "", columns.trim_end())];
+
+    for y in 0..BOARD_SIZE {
+        let mut rows: [String; TILE_ROWS] = [
+            format!("{:MARGIN$}", ""),
+            format!("{y:>2} "),
+            format!("{:MARGIN$}", ""),
+        ];
+
+        for x in 0..BOARD_SIZE {
+            let grid = match graph.tile((x, y)) {
+                Some(tile) => render_tile((x, y), tile, overlay, strokes, &mut codes),
+                None => {
+                    let mut grid = [[' '; TILE_COLUMNS]; TILE_ROWS];
+
+                    grid[1][2] = strokes.empty;
+                    grid
+                }
+            };
+
+            for (row, tile_row) in rows.iter_mut().zip(grid) {
+                row.extend(tile_row);
+            }
+        }
+
+        lines.extend(rows.map(|row| row.trim_end().to_owned()));
+    }
+
+    let legend = codes.legend();
+
+    if !legend.is_empty() {
+        lines.push(format!("Treasures: {legend}"));
+    }
+
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::*;
+
+    /// The three rows drawn for the position at `coordinates`.
+    fn tile_rows(dump: &str, coordinates: (u8, u8)) -> [String; TILE_ROWS] {
+        let lines: Vec<&str> = dump.split('\n').collect();
+        let first_row = 1 + coordinates.1 as usize * TILE_ROWS;
+        let first_column = MARGIN + coordinates.0 as usize * TILE_COLUMNS;
+
+        [0, 1, 2].map(|row| {
+            format!("{:<width$}", lines[first_row + row], width = 80)
+                .chars()
+                .skip(first_column)
+                .take(TILE_COLUMNS)
+                .collect()
+        })
+    }
+
+    fn graph(tiles: &[((u8, u8), CardinalDirectionFlags)]) -> BoardGraph {
+        let mut graph = BoardGraph::default();
+
+        for (coordinates, oasis) in tiles {
+            graph.insert_tile(*coordinates, oasis.clone(), CardinalDirectionFlags::empty());
+        }
+
+        graph
+    }
+
+    #[test_case(Charset::Unicode => ["╔═══╕", "║   │", "╙───┘"] ; "unicode")]
+    #[test_case(Charset::Ascii => ["+===+", "#   |", "+---+"] ; "ascii")]
+    fn draws_oasis_sides_double_and_desert_sides_single(charset: Charset) -> [String; 3] {
+        let graph = graph(&[(
+            (5, 5),
+            CardinalDirectionFlags::N | CardinalDirectionFlags::W,
+        )]);
+
+        tile_rows(
+            &dump_board(&graph, &BoardOverlay::default(), charset),
+            (5, 5),
+        )
+    }
+
+    #[test]
+    fn draws_caravans_and_reachable_tiles() {
+        let graph = graph(&[
+            ((5, 5), CardinalDirectionFlags::empty()),
+            ((6, 5), CardinalDirectionFlags::empty()),
+        ]);
+        let overlay = BoardOverlay {
+            caravans: HashMap::from([(PlayerName::White, (5, 5))]),
+            reachable: HashSet::from([(6, 5)]),
+            ..BoardOverlay::default()
+        };
+
+        let dump = dump_board(&graph, &overlay, Charset::Unicode);
+
+        assert_eq!(tile_rows(&dump, (5, 5))[1], "│ W │");
+        assert_eq!(tile_rows(&dump, (6, 5))[1], "│░░░│");
+    }
+
+    #[test]
+    fn draws_treasures_as_letters_on_their_sides() {
+        let mut graph = BoardGraph::default();
+        let overlay = BoardOverlay {
+            treasure_ids: HashMap::from([(
+                (5, 5),
+                ["goods:salt", "rumors", "none", "none"].map(String::from),
+            )]),
+            ..BoardOverlay::default()
+        };
+
+        graph.insert_tile(
+            (5, 5),
+            CardinalDirectionFlags::N | CardinalDirectionFlags::E,
+            CardinalDirectionFlags::N | CardinalDirectionFlags::E,
+        );
+        graph.insert_tile((6, 5), CardinalDirectionFlags::W, CardinalDirectionFlags::W);
+
+        let dump = dump_board(&graph, &overlay, Charset::Ascii);
+
+        assert_eq!(tile_rows(&dump, (5, 5)), ["+=s=+", "|   r", "+---+"]);
+        assert_eq!(tile_rows(&dump, (6, 5))[1], "*   |");
+        assert!(dump.ends_with("Treasures: r = rumors, s = goods:salt"));
+    }
+
+    #[test]
+    fn draws_every_position_of_the_board() {
+        let dump = dump_board(
+            &BoardGraph::default(),
+            &BoardOverlay::default(),
+            Charset::Ascii,
+        );
+        let lines: Vec<&str> = dump.split('\n').collect();
+
+        assert_eq!(lines.len(), 1 + BOARD_SIZE as usize * TILE_ROWS);
+        assert_eq!(
+            lines[0].trim(),
+            "0    1    2    3    4    5    6    7    8    9   10"
+        );
+        assert_eq!(
+            lines[17],
+            " 5   .    .    .    .    .    .    .    .    .    .    ."
+        );
+        assert_eq!(tile_rows(&dump, (10, 10)), ["     ", "  .  ", "     "]);
+    }
+}
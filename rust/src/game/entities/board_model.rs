@@ -0,0 +1,430 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use thiserror::Error;
+
+use crate::game::entities::board_dump::dump_board;
+use crate::game::entities::board_dump::BoardOverlay;
+use crate::game::entities::board_dump::Charset;
+use crate::game::entities::movement::BoardGraph;
+use crate::game::entities::movement::CaravanMove;
+use crate::game::entities::movement::InteractionRule;
+use crate::game::entities::movement::BOARD_SIZE;
+use crate::game::entities::movement::STARTING_WATER;
+use crate::game::entities::player::PlayerName;
+use crate::game::entities::turn::TurnPhase;
+use crate::game::entities::turn::TurnState;
+use crate::util::flags::CardinalDirectionFlags;
+use crate::util::flags::DIRECTIONS;
+use crate::util::rules::RulesConfig;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum BoardModelError {
+    #[error("Not allowed in the {0:?} phase")]
+    WrongPhase(TurnPhase),
+    #[error("{0:?} isn't reachable")]
+    Unreachable((u8, u8)),
+    #[error("{0:?} isn't a position next to the active caravan")]
+    NotExplorable((u8, u8)),
+}
+
+/// What a caravan move did besides moving the caravan, for animating and
+/// logging it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MoveOutcome {
+    /// Tiles the caravan walks through, ending on its destination
+    pub route: Vec<(u8, u8)>,
+    /// Water paid to each caravan passed on the way
+    pub tolls: Vec<(PlayerName, u32)>,
+    /// The caravan pushed aside from the destination, and where to
+    pub displaced: Option<(PlayerName, (u8, u8))>,
+}
+
+/// The rules of a game without the scene: the board graph, the caravans,
+/// what they hold and the turn. `BoardComponent` plays through it, and so do
+/// the scenario tests that replay whole turns.
+#[derive(Debug, Clone)]
+pub struct BoardModel {
+    rules: RulesConfig,
+    graph: BoardGraph,
+    positions: HashMap<PlayerName, (u8, u8)>,
+    water: HashMap<PlayerName, u32>,
+    turn: TurnState,
+}
+
+impl Default for BoardModel {
+    fn default() -> Self {
+        Self::new(RulesConfig::default())
+    }
+}
+
+impl BoardModel {
+    /// An empty board without caravans.
+    pub fn new(rules: RulesConfig) -> Self {
+        Self {
+            graph: BoardGraph::new(rules.oasis_crossings),
+            turn: TurnState::new(rules.extra_moves),
+            positions: HashMap::new(),
+            water: HashMap::new(),
+            rules,
+        }
+    }
+    pub fn rules(&self) -> &RulesConfig {
+        &self.rules
+    }
+    pub fn graph(&self) -> &BoardGraph {
+        &self.graph
+    }
+    pub fn turn(&self) -> &TurnState {
+        &self.turn
+    }
+    pub fn position(&self, player: PlayerName) -> Option<(u8, u8)> {
+        self.positions.get(&player).copied()
+    }
+    pub fn positions(&self) -> &HashMap<PlayerName, (u8, u8)> {
+        &self.positions
+    }
+    /// Puts the player's caravan on `coordinates` outside of the turn order,
+    /// to start the game or for debugging.
+    pub fn set_position(&mut self, player: PlayerName, coordinates: (u8, u8)) {
+        self.positions.insert(player, coordinates);
+    }
+    /// Puts every caravan on its starting position.
+    pub fn place_starting_caravans(&mut self) {
+        for (coordinates, player) in self.rules.starting_positions {
+            self.set_position(player, coordinates);
+        }
+    }
+    /// Water the player holds. Caravans set out with [`STARTING_WATER`].
+    pub fn water(&self, player: PlayerName) -> u32 {
+        self.water.get(&player).copied().unwrap_or(STARTING_WATER)
+    }
+    fn set_water(&mut self, player: PlayerName, water: u32) {
+        self.water.insert(player, water);
+    }
+    /// Puts a tile on the board outside of any turn, e.g. to set up the
+    /// starting cross, or before [`Self::enter_explored_tile`].
+    pub fn place_tile(
+        &mut self,
+        coordinates: (u8, u8),
+        oasis_directions: CardinalDirectionFlags,
+        treasure_directions: CardinalDirectionFlags,
+    ) {
+        self.graph
+            .insert_tile(coordinates, oasis_directions, treasure_directions);
+    }
+    fn occupied_by_others(&self, active: PlayerName) -> HashSet<(u8, u8)> {
+        self.positions
+            .iter()
+            .filter(|(player, _)| **player != active)
+            .map(|(_, coordinates)| *coordinates)
+            .collect()
+    }
+    fn occupant_at(&self, coordinates: (u8, u8), mover: PlayerName) -> Option<PlayerName> {
+        self.positions
+            .iter()
+            .find(|(occupant, position)| **occupant != mover && **position == coordinates)
+            .map(|(occupant, _)| *occupant)
+    }
+    /// Every move the active caravan may make, none outside the move phase.
+    pub fn moves(&self) -> HashMap<(u8, u8), CaravanMove> {
+        let active = self.turn.active_player();
+
+        let Some(from) = self.position(active).filter(|_| self.turn.can_move()) else {
+            return HashMap::new();
+        };
+
+        self.graph.caravan_moves(
+            from,
+            &self.occupied_by_others(active),
+            self.rules.interaction,
+            self.water(active),
+        )
+    }
+    /// Moves the active caravan to `coordinates`, paying its tolls and
+    /// pushing aside the caravan standing there, then moves on to exploring.
+    pub fn move_caravan(&mut self, coordinates: (u8, u8)) -> Result<MoveOutcome, BoardModelError> {
+        if !self.turn.can_move() {
+            return Err(BoardModelError::WrongPhase(TurnPhase::Explore));
+        }
+
+        let player = self.turn.active_player();
+        let caravan_move = self
+            .moves()
+            .remove(&coordinates)
+            .ok_or(BoardModelError::Unreachable(coordinates))?;
+
+        let payees: Vec<PlayerName> = caravan_move
+            .tolls
+            .iter()
+            .filter_map(|coordinates| self.occupant_at(*coordinates, player))
+            .collect();
+        let mut tolls = vec![];
+
+        if let InteractionRule::WaterToll(toll) = self.rules.interaction {
+            for payee in payees {
+                let paid = toll.min(self.water(player));
+
+                self.set_water(player, self.water(player) - paid);
+                self.set_water(payee, self.water(payee) + paid);
+                tolls.push((payee, paid));
+            }
+        }
+
+        let displaced = self
+            .occupant_at(coordinates, player)
+            .zip(caravan_move.displaced_to);
+
+        if let Some((displaced, target)) = displaced {
+            self.set_position(displaced, target);
+        }
+
+        self.set_position(player, coordinates);
+        self.turn.advance_to_explore();
+
+        Ok(MoveOutcome {
+            route: caravan_move.route.tiles,
+            tolls,
+            displaced,
+        })
+    }
+    /// Gives up the active caravan's move to explore from where it stands.
+    pub fn skip_move(&mut self) -> Result<(), BoardModelError> {
+        if !self.turn.can_move() {
+            return Err(BoardModelError::WrongPhase(TurnPhase::Explore));
+        }
+
+        self.turn.advance_to_explore();
+
+        Ok(())
+    }
+    fn check_explorable(&self, coordinates: (u8, u8)) -> Result<(), BoardModelError> {
+        if self.turn.can_move() {
+            return Err(BoardModelError::WrongPhase(TurnPhase::Move));
+        }
+
+        let player = self.turn.active_player();
+        let is_next_to_caravan = self.position(player).is_some_and(|(x, y)| {
+            DIRECTIONS.iter().any(|direction| {
+                let (dx, dy) = direction.get_coordinate_offset();
+
+                (x as i32 + dx, y as i32 + dy) == (coordinates.0 as i32, coordinates.1 as i32)
+            })
+        });
+        let is_on_board = coordinates.0 < BOARD_SIZE && coordinates.1 < BOARD_SIZE;
+
+        if !is_next_to_caravan || !is_on_board {
+            return Err(BoardModelError::NotExplorable(coordinates));
+        }
+
+        Ok(())
+    }
+    /// Places the explored tile next to the active caravan, then enters it
+    /// like [`Self::enter_explored_tile`].
+    pub fn explore(
+        &mut self,
+        coordinates: (u8, u8),
+        oasis_directions: CardinalDirectionFlags,
+        treasure_directions: CardinalDirectionFlags,
+        is_desert: bool,
+    ) -> Result<bool, BoardModelError> {
+        self.check_explorable(coordinates)?;
+
+        if self.graph.tile(coordinates).is_some() {
+            return Err(BoardModelError::NotExplorable(coordinates));
+        }
+
+        self.place_tile(coordinates, oasis_directions, treasure_directions);
+        self.enter_explored_tile(coordinates, is_desert)
+    }
+    /// Advances the active caravan onto the tile it just explored, already
+    /// placed next to it, and ends the turn. Returns whether a desert tile
+    /// granted the same player another move or explore.
+    pub fn enter_explored_tile(
+        &mut self,
+        coordinates: (u8, u8),
+        is_desert: bool,
+    ) -> Result<bool, BoardModelError> {
+        self.check_explorable(coordinates)?;
+
+        self.set_position(self.turn.active_player(), coordinates);
+
+        Ok(self.turn.advance_turn(is_desert))
+    }
+    /// The turn, every caravan with its water, the active caravan's moves and
+    /// the board, for comparing against a golden file.
+    pub fn snapshot(&self) -> String {
+        let phase = if self.turn.can_move() {
+            "move"
+        } else {
+            "explore"
+        };
+        let mut lines = vec![format!(
+            "{:?} to {phase}, desert chain {}",
+            self.turn.active_player(),
+            self.turn.desert_chain()
+        )];
+
+        for (_, player) in &self.rules.starting_positions {
+            let position = self.position(*player).unwrap_or_default();
+
+            lines.push(format!(
+                "{player:?} at {position:?}, {} water",
+                self.water(*player)
+            ));
+        }
+
+        let mut moves: Vec<((u8, u8), CaravanMove)> = self.moves().into_iter().collect();
+
+        moves.sort_by_key(|(coordinates, _)| *coordinates);
+        lines.push(format!("Reachable: {}", moves.len()));
+
+        for (coordinates, caravan_move) in &moves {
+            let mut details = vec![format!("{} step(s)", caravan_move.route.cost())];
+
+            if caravan_move.route.crosses_oasis {
+                details.push(String::from("crosses oasis"));
+            }
+
+            if !caravan_move.tolls.is_empty() {
+                details.push(format!("{} toll(s)", caravan_move.tolls.len()));
+            }
+
+            if let Some(target) = caravan_move.displaced_to {
+                details.push(format!("displaces to {target:?}"));
+            }
+
+            lines.push(format!("  {coordinates:?}: {}", details.join(", ")));
+        }
+
+        let overlay = BoardOverlay {
+            caravans: self.positions.clone(),
+            reachable: moves.iter().map(|(coordinates, _)| *coordinates).collect(),
+            treasure_ids: HashMap::new(),
+        };
+
+        lines.push(dump_board(&self.graph, &overlay, Charset::Unicode));
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+
+    use super::*;
+
+    /// A game on a west-to-east strip through the center, with White's
+    /// caravan on its west end.
+    fn strip(rules: RulesConfig, tile_count: u8) -> BoardModel {
+        let mut game = BoardModel::new(RulesConfig {
+            starting_positions: [
+                ((0, 5), PlayerName::White),
+                ((10, 0), PlayerName::Orange),
+                ((10, 1), PlayerName::Red),
+                ((10, 2), PlayerName::Blue),
+            ],
+            ..rules
+        });
+
+        game.place_starting_caravans();
+
+        for x in 0..tile_count {
+            game.place_tile(
+                (x, 5),
+                CardinalDirectionFlags::empty(),
+                CardinalDirectionFlags::empty(),
+            );
+        }
+
+        game
+    }
+
+    #[test]
+    fn moves_then_explores_then_passes_the_turn() {
+        let mut game = strip(RulesConfig::default(), 3);
+
+        let outcome = game.move_caravan((2, 5)).unwrap();
+
+        assert_eq!(outcome.route, vec![(1, 5), (2, 5)]);
+        assert_eq!(game.position(PlayerName::White), Some((2, 5)));
+        assert!(game.moves().is_empty());
+
+        let granted = game
+            .explore(
+                (3, 5),
+                CardinalDirectionFlags::empty(),
+                CardinalDirectionFlags::empty(),
+                false,
+            )
+            .unwrap();
+
+        assert!(!granted);
+        assert_eq!(game.position(PlayerName::White), Some((3, 5)));
+        assert_eq!(game.turn().active_player(), PlayerName::Orange);
+    }
+
+    #[test]
+    fn pays_tolls_to_the_caravans_passed() {
+        let rules = RulesConfig {
+            interaction: InteractionRule::WaterToll(2),
+            ..RulesConfig::default()
+        };
+        let mut game = strip(rules, 4);
+
+        game.set_position(PlayerName::Orange, (1, 5));
+
+        let outcome = game.move_caravan((3, 5)).unwrap();
+
+        assert_eq!(outcome.tolls, vec![(PlayerName::Orange, 2)]);
+        assert_eq!(game.water(PlayerName::White), STARTING_WATER - 2);
+        assert_eq!(game.water(PlayerName::Orange), STARTING_WATER + 2);
+    }
+
+    #[test]
+    fn enters_a_tile_placed_by_the_scene() {
+        let mut game = strip(RulesConfig::default(), 3);
+
+        game.skip_move().unwrap();
+        game.place_tile(
+            (0, 4),
+            CardinalDirectionFlags::empty(),
+            CardinalDirectionFlags::empty(),
+        );
+
+        assert_eq!(game.enter_explored_tile((0, 4), true), Ok(true));
+        assert_eq!(game.position(PlayerName::White), Some((0, 4)));
+        assert_eq!(game.turn().active_player(), PlayerName::White);
+    }
+
+    #[test]
+    fn rejects_actions_out_of_turn() {
+        let mut game = strip(RulesConfig::default(), 3);
+
+        assert_matches!(
+            game.explore(
+                (0, 4),
+                CardinalDirectionFlags::empty(),
+                CardinalDirectionFlags::empty(),
+                false
+            ),
+            Err(BoardModelError::WrongPhase(TurnPhase::Move))
+        );
+        assert_eq!(
+            game.move_caravan((5, 5)),
+            Err(BoardModelError::Unreachable((5, 5)))
+        );
+
+        game.skip_move().unwrap();
+
+        assert_eq!(
+            game.explore(
+                (2, 5),
+                CardinalDirectionFlags::empty(),
+                CardinalDirectionFlags::empty(),
+                false
+            ),
+            Err(BoardModelError::NotExplorable((2, 5)))
+        );
+    }
+}
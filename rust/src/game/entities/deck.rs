@@ -1,41 +1,43 @@
 use godot::builtin::Color;
+use godot::builtin::GString;
 use godot::builtin::Vector2;
 use godot::classes::ColorRect;
+use godot::classes::Control;
 use godot::classes::INode2D;
 use godot::classes::Label;
+use godot::classes::Node;
 use godot::classes::Node2D;
+use godot::classes::Texture2D;
+use godot::classes::TextureRect;
 use godot::obj::Base;
 use godot::obj::Gd;
 use godot::obj::WithBaseField;
 use godot::prelude::godot_api;
+use godot::prelude::try_load;
 use godot::prelude::GodotClass;
-use phf::phf_map;
-use phf::Map;
 
 use crate::game::components::hover_outline::HoverableOutline;
 use crate::game::components::tile_component::NextTileData;
 use crate::game::components::tile_component::NextTileDataRemaining;
 use crate::game::components::tile_component::TileComponent;
+use crate::game::components::tile_component::TileData;
 use crate::game::components::tile_component::TileDeckComponent;
 use crate::game::entities::tile::Tile;
 use crate::game::entities::BoardComponent;
 use crate::game::RunningGameScene;
 use crate::ui::LabelTooltip;
+use crate::util::errors::GameError;
+use crate::util::loader::GameConfig;
 use crate::util::loader::SceneLoader;
+use crate::util::loader::TomlLoader;
+use crate::util::tileset::stats::TileStats;
+use crate::util::tileset::DeckConfig;
+use crate::util::ErrorReporter;
 use crate::util::Logger;
 
-// TODO: Move this color information to the mapping in config/tileset.toml
-const TILE_COLOR_MAP: Map<&'static str, &'static str> = phf_map! {
-    "1" => "#fee17c",
-    "2" => "#b3d7ed",
-    "3" => "#99d761",
-    "4" => "#f89b49",
-    "5" => "#c97db4",
-};
-
 #[derive(Debug, GodotClass)]
 #[class(init, base=Node2D)]
-struct TileDeck {
+pub struct TileDeck {
     base: Base<Node2D>,
 
     #[export]
@@ -43,6 +45,47 @@ struct TileDeck {
 }
 
 impl TileDeck {
+    /// Makes this node show the tileset's deck at `deck_index`. Only takes
+    /// effect before the node enters the tree.
+    pub fn assign_deck(&mut self, deck_index: u8) {
+        self.deck_index = deck_index;
+    }
+    /// The deck at `deck_index` on the running game's deck board.
+    pub fn find(node: &Node, deck_index: u8) -> Option<Gd<TileDeck>> {
+        RunningGameScene::get_running_game(node)
+            .find_children_ex("*")
+            .type_("TileDeck")
+            .owned(false)
+            .done()
+            .iter_shared()
+            .filter_map(|child| child.try_cast::<TileDeck>().ok())
+            .find(|deck| deck.bind().deck_index == deck_index)
+    }
+    /// Draws the deck's `nth` remaining tile, from 0, the way clicking the
+    /// deck draws the next one, for debugging.
+    pub fn draw_tile(&mut self, nth: u8) -> Result<(), String> {
+        let active_deck = BoardComponent::get(&self.base()).bind().active_deck();
+
+        if active_deck != Some(self.deck_index) {
+            return Err(format!(
+                "Deck {} can't be drawn from yet, the active deck is {}",
+                self.deck_index + 1,
+                active_deck.map_or(String::from("none"), |deck| (deck + 1).to_string())
+            ));
+        }
+
+        if !self.get_tile_deck_component().bind_mut().move_to_top(nth) {
+            return Err(format!(
+                "Deck {} has fewer than {} tiles left",
+                self.deck_index + 1,
+                nth + 1
+            ));
+        }
+
+        self.get_next_tile();
+
+        Ok(())
+    }
     fn get_hover_outline(&self) -> Gd<HoverableOutline> {
         self.base()
             .get_node_as::<HoverableOutline>("./HoverOutline")
@@ -116,7 +159,7 @@ impl TileDeck {
     fn get_next_tile(&mut self) {
         let mut board_component = BoardComponent::get(&self.to_gd());
 
-        if board_component.bind().active_tile_deck != self.deck_index {
+        if board_component.bind().active_deck() != Some(self.deck_index) {
             return;
         }
 
@@ -142,11 +185,11 @@ impl TileDeck {
 
             if new_remaining == 0 {
                 self.disable_outline();
-                board_component.bind_mut().active_tile_deck += 1;
+                board_component.bind_mut().exhaust_deck(self.deck_index);
             }
         } else {
             Logger::error("Attempted to spawn tile from exhausted deck");
-            board_component.bind_mut().active_tile_deck += 1;
+            board_component.bind_mut().exhaust_deck(self.deck_index);
             self.disable_outline();
         }
     }
@@ -158,6 +201,69 @@ impl TileDeck {
         self.base()
             .get_node_as::<Label>("./Control/VBoxContainer/CenterContainer/Label")
     }
+    fn get_icon_container(&self) -> Gd<Control> {
+        self.base()
+            .get_node_as::<Control>("./Control/VBoxContainer/IconContainer")
+    }
+    fn get_icon(&self) -> Gd<TextureRect> {
+        self.base()
+            .get_node_as::<TextureRect>("./Control/VBoxContainer/IconContainer/Icon")
+    }
+    /// Shows the deck's name in its color, and its icon if it has one. Debug
+    /// builds also list the deck's balance stats in its tooltip.
+    fn render_metadata(&self, deck: &DeckConfig) {
+        let mut label = self.get_idx_label();
+
+        label.set_text(&deck.name);
+
+        if cfg!(debug_assertions) {
+            let stats: TileStats = deck.tiles.iter().collect();
+
+            self.get_tooltip().bind_mut().set_value(&stats.summary());
+        }
+
+        match Color::from_html(deck.color.as_str()) {
+            Some(color) => label.add_theme_color_override("font_color", color),
+            None => Logger::error(&format!(
+                "Couldn't parse color {} of deck {}",
+                deck.color, deck.name
+            )),
+        }
+
+        let Some(icon) = &deck.icon else {
+            return self.get_icon_container().set_visible(false);
+        };
+
+        match try_load::<Texture2D>(icon) {
+            Ok(texture) => {
+                self.get_icon().set_texture(&texture);
+                self.get_icon_container().set_visible(true);
+            }
+            Err(error) => Logger::error(&format!(
+                "Couldn't load icon {icon} of deck {}: {error}",
+                deck.name
+            )),
+        }
+    }
+    /// Picks up an edited tileset: the deck's name, color and icon, and the
+    /// tiles that haven't been drawn yet.
+    fn on_config_reloaded(&mut self, path: GString) {
+        if path.to_string() != GameConfig::Tileset.path() {
+            return;
+        }
+
+        let tileset = match TomlLoader::tileset(&self.base()) {
+            Ok(tileset) => tileset,
+            Err(error) => return Logger::error(&error.to_string()),
+        };
+
+        let Some(deck) = tileset.deck.get(self.deck_index as usize) else {
+            return;
+        };
+
+        self.render_metadata(deck);
+        self.get_tile_deck_component().bind_mut().tiles = deck.tiles.clone().map(TileData::from);
+    }
     fn get_remaining_label(&self) -> Gd<Label> {
         self.base()
             .get_node_as::<Label>("./Control/VBoxContainer/CenterContainer2/Remaining")
@@ -172,14 +278,20 @@ impl INode2D for TileDeck {
     fn ready(&mut self) {
         let board_component = BoardComponent::get(&self.base());
 
-        if board_component.bind().active_tile_deck != self.deck_index {
+        if board_component.bind().active_deck() != Some(self.deck_index) {
             self.disable_outline();
         }
 
-        let mut tile_deck_component: Gd<TileDeckComponent>;
-        let base = self.base();
+        let tileset = match TomlLoader::tileset(&self.base()) {
+            Ok(tileset) => tileset,
+            Err(error) => return ErrorReporter::report(&self.base(), GameError::from(error)),
+        };
+        let mut tile_deck_component =
+            match TileDeckComponent::from_tile_deck_index(&self.base(), self.deck_index) {
+                Ok(tile_deck_component) => tile_deck_component,
+                Err(error) => return ErrorReporter::report(&self.base(), error),
+            };
 
-        tile_deck_component = TileDeckComponent::from_tile_deck_index(&base, self.deck_index);
         tile_deck_component.set_name("TileDeckComponent");
 
         {
@@ -190,22 +302,20 @@ impl INode2D for TileDeck {
         let tile_deck = self.to_gd();
         tile_deck_component.set_owner(&tile_deck);
 
-        let mut label = self.get_idx_label();
-        let index_string = (self.deck_index + 1).to_string();
-        let color_string = *TILE_COLOR_MAP
-            .get(&index_string)
-            .expect("Expected valid deck index");
+        match tileset.deck.get(self.deck_index as usize) {
+            Some(deck) => self.render_metadata(deck),
+            None => Logger::error(&format!("No deck {} in tileset", self.deck_index + 1)),
+        }
 
-        label.set_text(&index_string);
-        label.add_theme_color_override(
-            &format!("Deck {index_string} color"),
-            Color::from_html(color_string).expect("Couldn't parse color string for deck"),
-        );
+        TomlLoader::global(&self.base())
+            .signals()
+            .config_reloaded()
+            .connect_other(&*self, Self::on_config_reloaded);
     }
     fn process(&mut self, _dt: f64) {
-        let active_index = BoardComponent::get(&self.base()).bind().active_tile_deck;
+        let active_deck = BoardComponent::get(&self.base()).bind().active_deck();
 
-        if active_index == self.deck_index {
+        if active_deck == Some(self.deck_index) {
             self.enable_collision();
         }
     }
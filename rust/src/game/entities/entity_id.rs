@@ -0,0 +1,235 @@
+use std::fmt::Display;
+
+use thiserror::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EntityScope {
+    Global,
+    Running,
+}
+
+/// Handle to an entity registered with the `EntityManager`.
+///
+/// A slot is reused once its entity is removed, so the handle also carries
+/// the generation of the slot it was issued for. Looking up a handle after its
+/// entity was removed, or its scope was cleared, fails instead of returning
+/// whatever took the slot over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EntityId {
+    scope: EntityScope,
+    index: u32,
+    generation: u32,
+}
+
+impl EntityId {
+    pub fn scope(&self) -> EntityScope {
+        self.scope
+    }
+}
+
+impl Display for EntityId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}#{}v{}", self.scope, self.index, self.generation)
+    }
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum EntityLookupError {
+    #[error("Entity {0} was looked up in the {1:?} scope")]
+    WrongScopeError(EntityId, EntityScope),
+    #[error("Entity {0} was never registered")]
+    UnknownEntityError(EntityId),
+    #[error("Entity {0} is stale; it was removed or its scope was freed")]
+    StaleEntityError(EntityId),
+    #[error("Godot instance of entity {0} no longer exists or has another type")]
+    InstanceNotFoundError(EntityId),
+}
+
+#[derive(Debug, Clone)]
+struct Slot<T> {
+    generation: u32,
+    value: Option<T>,
+}
+
+/// Generational slot storage backing a single `EntityScope`.
+#[derive(Debug, Clone)]
+pub struct EntityRegistry<T> {
+    scope: EntityScope,
+    slots: Vec<Slot<T>>,
+    free_slots: Vec<u32>,
+}
+
+impl<T> EntityRegistry<T> {
+    pub fn new(scope: EntityScope) -> Self {
+        Self {
+            scope,
+            slots: vec![],
+            free_slots: vec![],
+        }
+    }
+    pub fn insert(&mut self, value: T) -> EntityId {
+        let index = match self.free_slots.pop() {
+            Some(index) => {
+                self.slots[index as usize].value = Some(value);
+                index
+            }
+            None => {
+                self.slots.push(Slot {
+                    generation: 0,
+                    value: Some(value),
+                });
+                (self.slots.len() - 1) as u32
+            }
+        };
+
+        EntityId {
+            scope: self.scope,
+            index,
+            generation: self.slots[index as usize].generation,
+        }
+    }
+    fn slot(&self, id: EntityId) -> Result<&Slot<T>, EntityLookupError> {
+        if id.scope != self.scope {
+            return Err(EntityLookupError::WrongScopeError(id, self.scope));
+        }
+
+        let slot = self
+            .slots
+            .get(id.index as usize)
+            .ok_or(EntityLookupError::UnknownEntityError(id))?;
+
+        if slot.generation != id.generation || slot.value.is_none() {
+            return Err(EntityLookupError::StaleEntityError(id));
+        }
+
+        Ok(slot)
+    }
+    pub fn get(&self, id: EntityId) -> Result<&T, EntityLookupError> {
+        self.slot(id)?
+            .value
+            .as_ref()
+            .ok_or(EntityLookupError::StaleEntityError(id))
+    }
+    pub fn remove(&mut self, id: EntityId) -> Result<T, EntityLookupError> {
+        self.slot(id)?;
+
+        let slot = &mut self.slots[id.index as usize];
+
+        // Bumping the generation invalidates every copy of `id` still around
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free_slots.push(id.index);
+
+        slot.value
+            .take()
+            .ok_or(EntityLookupError::StaleEntityError(id))
+    }
+    /// Removes every entity, leaving all of their ids stale.
+    ///
+    /// Slots are kept, as dropping them would reset their generations and let
+    /// old ids alias new entities.
+    pub fn clear(&mut self) {
+        for (index, slot) in self.slots.iter_mut().enumerate() {
+            if slot.value.take().is_some() {
+                slot.generation = slot.generation.wrapping_add(1);
+                self.free_slots.push(index as u32);
+            }
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.slots.len() - self.free_slots.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+
+    use super::EntityLookupError;
+    use super::EntityRegistry;
+    use super::EntityScope;
+
+    #[test]
+    fn hands_out_distinct_ids() {
+        let mut registry = EntityRegistry::new(EntityScope::Running);
+
+        let first = registry.insert("first");
+        let second = registry.insert("second");
+
+        assert_ne!(first, second);
+        assert_eq!(registry.get(first), Ok(&"first"));
+        assert_eq!(registry.get(second), Ok(&"second"));
+        assert_eq!(registry.len(), 2);
+    }
+
+    #[test]
+    fn removed_ids_are_stale_after_their_slot_is_reused() {
+        let mut registry = EntityRegistry::new(EntityScope::Running);
+
+        let removed = registry.insert("removed");
+        assert_eq!(registry.remove(removed), Ok("removed"));
+
+        let reused = registry.insert("reused");
+
+        assert_matches!(
+            registry.get(removed),
+            Err(EntityLookupError::StaleEntityError(_))
+        );
+        assert_matches!(
+            registry.remove(removed),
+            Err(EntityLookupError::StaleEntityError(_))
+        );
+        assert_eq!(registry.get(reused), Ok(&"reused"));
+        assert_eq!(registry.len(), 1);
+    }
+
+    #[test]
+    fn clearing_leaves_every_id_stale() {
+        let mut registry = EntityRegistry::new(EntityScope::Running);
+
+        let ids: Vec<_> = (0..3).map(|idx| registry.insert(idx)).collect();
+        registry.clear();
+        let new_ids: Vec<_> = (3..6).map(|idx| registry.insert(idx)).collect();
+
+        for id in ids {
+            assert_matches!(
+                registry.get(id),
+                Err(EntityLookupError::StaleEntityError(_))
+            );
+        }
+        for (id, value) in new_ids.into_iter().zip(3..6) {
+            assert_eq!(registry.get(id), Ok(&value));
+        }
+    }
+
+    #[test]
+    fn rejects_ids_from_another_scope() {
+        let mut global = EntityRegistry::new(EntityScope::Global);
+        let mut running = EntityRegistry::new(EntityScope::Running);
+
+        let global_id = global.insert(());
+        running.insert(());
+
+        assert_matches!(
+            running.get(global_id),
+            Err(EntityLookupError::WrongScopeError(_, EntityScope::Running))
+        );
+    }
+
+    #[test]
+    fn rejects_ids_it_never_issued() {
+        let mut registry = EntityRegistry::new(EntityScope::Running);
+        let mut larger_registry = EntityRegistry::new(EntityScope::Running);
+
+        registry.insert(());
+        larger_registry.insert(());
+        let unknown = larger_registry.insert(());
+
+        assert_matches!(
+            registry.get(unknown),
+            Err(EntityLookupError::UnknownEntityError(_))
+        );
+    }
+}
@@ -1,8 +1,12 @@
+use std::cmp::Reverse;
+use std::collections::BTreeSet;
+use std::collections::BinaryHeap;
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::collections::VecDeque;
 
 use petgraph::graphmap::DiGraphMap;
-use petgraph::visit::Bfs;
+use thiserror::Error;
 
 use crate::game::entities::player::PlayerName;
 use crate::util::flags::CardinalDirection;
@@ -10,7 +14,7 @@ use crate::util::flags::CardinalDirectionFlags;
 use crate::util::flags::DIRECTIONS;
 
 /// Board coordinates of the 4 arm-end tiles of the starting cross, and the
-/// player whose caravan starts there.
+/// player whose caravan starts there under the standard rules.
 pub const STARTING_POSITIONS: [((u8, u8), PlayerName); 4] = [
     ((5, 10), PlayerName::White),
     ((10, 5), PlayerName::Orange),
@@ -18,72 +22,584 @@ pub const STARTING_POSITIONS: [((u8, u8), PlayerName); 4] = [
     ((0, 5), PlayerName::Blue),
 ];
 
-// (x, y, crossed_white): the bool marks that an oasis line was crossed. A
-// move may cross only one, so those nodes are dead ends.
-type MoveNode = (u8, u8, bool);
+/// Tiles per side of the square board.
+pub const BOARD_SIZE: u8 = 11;
+
+/// Oasis lines a single move may cross under the standard rules.
+pub const DEFAULT_OASIS_CROSSINGS: u8 = 1;
+
+/// Water each caravan sets out with, spent on tolls under the `water_toll`
+/// rule.
+pub const STARTING_WATER: u32 = 3;
+
+// (x, y, crossings): oasis lines crossed so far. Crossing the last one a move
+// may cross ends the move, so those nodes are dead ends.
+type MoveNode = (u8, u8, u8);
+
+/// A caravan's way through the move graph.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Route {
+    /// Tiles entered after leaving the start, ending on the destination
+    pub tiles: Vec<(u8, u8)>,
+    /// Desert (brown) lines crossed on the way
+    pub desert_steps: usize,
+    /// Whether the last step crosses an oasis (white) line. Under the standard
+    /// rules a move may cross only one, so no step can follow it.
+    pub crosses_oasis: bool,
+}
+
+impl Route {
+    /// Steps taken, each crossing one line between two tiles.
+    pub fn cost(&self) -> usize {
+        self.tiles.len()
+    }
+    pub fn destination(&self) -> Option<(u8, u8)> {
+        self.tiles.last().copied()
+    }
+}
+
+/// How a moving caravan deals with tiles other caravans stand on.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum InteractionRule {
+    /// Occupied tiles can be passed through, but not stopped on
+    #[default]
+    PassThrough,
+    /// Occupied tiles can be neither passed through nor stopped on
+    Blocking,
+    /// Passing through an occupied tile costs this much water, paid to the
+    /// caravan standing there. Occupied tiles can't be stopped on.
+    WaterToll(u32),
+    /// Stopping on an occupied tile pushes its caravan onto a free adjacent
+    /// tile. Occupied tiles can be passed through.
+    Displacing,
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum InteractionRuleParseError {
+    #[error("Interaction rule expected one of pass_through, blocking, water_toll, displacing, received {0}")]
+    UnknownRule(String),
+}
+
+impl InteractionRule {
+    pub const NAMES: [&'static str; 4] = ["pass_through", "blocking", "water_toll", "displacing"];
+
+    /// Parses the rule's name, e.g. `water_toll`. `toll` is only used by
+    /// [`InteractionRule::WaterToll`].
+    pub fn from_name(name: &str, toll: u32) -> Result<Self, InteractionRuleParseError> {
+        match name {
+            "pass_through" => Ok(Self::PassThrough),
+            "blocking" => Ok(Self::Blocking),
+            "water_toll" => Ok(Self::WaterToll(toll)),
+            "displacing" => Ok(Self::Displacing),
+            _ => Err(InteractionRuleParseError::UnknownRule(name.to_owned())),
+        }
+    }
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::PassThrough => "pass_through",
+            Self::Blocking => "blocking",
+            Self::WaterToll(_) => "water_toll",
+            Self::Displacing => "displacing",
+        }
+    }
+}
+
+/// A legal move for a caravan, and what it does to other caravans.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CaravanMove {
+    pub route: Route,
+    /// Occupied tiles passed through, each owing the toll to its occupant
+    pub tolls: Vec<(u8, u8)>,
+    /// Where the destination's occupant is pushed to
+    pub displaced_to: Option<(u8, u8)>,
+}
+
+impl CaravanMove {
+    /// Whether the move affects another caravan, and so gets highlighted
+    /// differently.
+    pub fn interacts(&self) -> bool {
+        !self.tolls.is_empty() || self.displaced_to.is_some()
+    }
+}
+
+/// An oasis (white) line between two neighboring tiles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct OasisEdge {
+    pub from: (u8, u8),
+    pub to: (u8, u8),
+}
+
+/// Cheapest-first search of the move graph, keeping each node's predecessor so
+/// routes can be read back.
+struct MoveSearch {
+    start: MoveNode,
+    // Nodes in the order they were settled, so by tolls then step count
+    visited: Vec<MoveNode>,
+    predecessors: HashMap<MoveNode, MoveNode>,
+}
+
+impl MoveSearch {
+    fn route_to(&self, node: MoveNode) -> Route {
+        let mut tiles = vec![(node.0, node.1)];
+        let mut current = node;
+
+        while let Some(previous) = self.predecessors.get(&current) {
+            if *previous == self.start {
+                break;
+            }
+
+            tiles.push((previous.0, previous.1));
+            current = *previous;
+        }
+
+        tiles.reverse();
+
+        let previous_crossings = self
+            .predecessors
+            .get(&node)
+            .map_or(0, |previous| previous.2);
 
-#[derive(Debug, Default, Clone)]
+        Route {
+            desert_steps: tiles.len() - node.2 as usize,
+            tiles,
+            crosses_oasis: node.2 > previous_crossings,
+        }
+    }
+    /// Oasis lines crossed on the way to `node`, in the order they're crossed.
+    fn oasis_crossings(&self, node: MoveNode) -> Vec<OasisEdge> {
+        let mut edges = vec![];
+        let mut current = node;
+
+        while let Some(previous) = self.predecessors.get(&current) {
+            if current.2 > previous.2 {
+                edges.push(OasisEdge {
+                    from: (previous.0, previous.1),
+                    to: (current.0, current.1),
+                });
+            }
+
+            current = *previous;
+        }
+
+        edges.reverse();
+        edges
+    }
+    /// The first node settled on each tile other than the start, which is on
+    /// the cheapest route there.
+    fn destinations(&self) -> impl Iterator<Item = MoveNode> + '_ {
+        let mut seen: HashSet<(u8, u8)> = HashSet::from([(self.start.0, self.start.1)]);
+
+        self.visited
+            .iter()
+            .copied()
+            .filter(move |node| seen.insert((node.0, node.1)))
+    }
+}
+
+/// What the board model knows about a placed tile.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TileState {
+    pub oasis_directions: CardinalDirectionFlags,
+    /// Sides with a treasure that hasn't been collected yet
+    pub treasure_directions: CardinalDirectionFlags,
+}
+
+/// The placed tiles and the move graph between them.
+///
+/// Kept on the board model for the whole game and updated as tiles are
+/// placed, so entering the move phase doesn't rebuild anything. Placing a
+/// tile only touches the edges to its up to 4 neighbors.
+#[derive(Debug, Clone)]
 pub struct BoardGraph {
-    tiles: HashMap<(u8, u8), CardinalDirectionFlags>,
+    tiles: HashMap<(u8, u8), TileState>,
+    moves: DiGraphMap<MoveNode, ()>,
+    max_oasis_crossings: u8,
+}
+
+impl Default for BoardGraph {
+    fn default() -> Self {
+        Self::new(DEFAULT_OASIS_CROSSINGS)
+    }
 }
 
 impl BoardGraph {
-    pub fn insert_tile(&mut self, coordinates: (u8, u8), oasis_directions: CardinalDirectionFlags) {
-        self.tiles.insert(coordinates, oasis_directions);
+    /// An empty board, on which a single move may cross up to
+    /// `max_oasis_crossings` oasis lines.
+    pub fn new(max_oasis_crossings: u8) -> Self {
+        Self {
+            tiles: HashMap::new(),
+            moves: DiGraphMap::new(),
+            max_oasis_crossings,
+        }
+    }
+    pub fn max_oasis_crossings(&self) -> u8 {
+        self.max_oasis_crossings
+    }
+    /// Places a tile with its treasures, or replaces the one already placed
+    /// at `coordinates`.
+    pub fn insert_tile(
+        &mut self,
+        coordinates: (u8, u8),
+        oasis_directions: CardinalDirectionFlags,
+        treasure_directions: CardinalDirectionFlags,
+    ) {
+        if self
+            .tiles
+            .get(&coordinates)
+            .is_some_and(|previous| previous.oasis_directions != oasis_directions)
+        {
+            self.remove_move_edges(coordinates);
+        }
+
+        self.tiles.insert(
+            coordinates,
+            TileState {
+                oasis_directions,
+                treasure_directions,
+            },
+        );
+
+        self.add_move_edges(coordinates);
+    }
+    pub fn tile(&self, coordinates: (u8, u8)) -> Option<&TileState> {
+        self.tiles.get(&coordinates)
+    }
+    pub fn tile_count(&self) -> usize {
+        self.tiles.len()
     }
 
     /// Tiles the caravan can reach: any distance over desert (brown) lines,
-    /// plus one final step over an oasis (white) line. Occupied tiles can be
-    /// passed through but are not valid destinations.
+    /// crossing up to [`Self::max_oasis_crossings`] oasis (white) lines, the
+    /// last of which ends the move. Occupied tiles can be passed through but
+    /// are not valid destinations.
     pub fn reachable_tiles(&self, from: (u8, u8), occupied: &HashSet<(u8, u8)>) -> Vec<(u8, u8)> {
-        let graph = self.build_move_graph();
-        let start: MoveNode = (from.0, from.1, false);
+        self.reachable_routes(from, occupied).into_keys().collect()
+    }
 
-        if !graph.contains_node(start) {
-            return vec![];
+    /// The shortest route to every tile in [`Self::reachable_tiles`].
+    pub fn reachable_routes(
+        &self,
+        from: (u8, u8),
+        occupied: &HashSet<(u8, u8)>,
+    ) -> HashMap<(u8, u8), Route> {
+        let Some(search) = self.search(from, &HashSet::new(), &HashSet::new()) else {
+            return HashMap::new();
+        };
+
+        search
+            .destinations()
+            .filter(|node| !occupied.contains(&(node.0, node.1)))
+            .map(|node| ((node.0, node.1), search.route_to(node)))
+            .collect()
+    }
+
+    /// Every move the caravan on `from` may make under `rule`, keyed by
+    /// destination. `occupied` holds the tiles of other caravans, and `water`
+    /// is what the moving caravan can pay in tolls. Under a toll, each move
+    /// takes the route passing the fewest caravans, then the shortest one.
+    pub fn caravan_moves(
+        &self,
+        from: (u8, u8),
+        occupied: &HashSet<(u8, u8)>,
+        rule: InteractionRule,
+        water: u32,
+    ) -> HashMap<(u8, u8), CaravanMove> {
+        let Some(search) = self.caravan_search(from, occupied, rule) else {
+            return HashMap::new();
+        };
+
+        search
+            .destinations()
+            .filter_map(|node| {
+                let caravan_move = self.caravan_move(&search, node, occupied, rule, water)?;
+
+                Some(((node.0, node.1), caravan_move))
+            })
+            .collect()
+    }
+
+    /// Searches the routes a caravan on `from` may take under `rule`.
+    fn caravan_search(
+        &self,
+        from: (u8, u8),
+        occupied: &HashSet<(u8, u8)>,
+        rule: InteractionRule,
+    ) -> Option<MoveSearch> {
+        let (blocked, tolled) = match rule {
+            InteractionRule::Blocking => (occupied.clone(), HashSet::new()),
+            InteractionRule::WaterToll(_) => (HashSet::new(), occupied.clone()),
+            _ => (HashSet::new(), HashSet::new()),
+        };
+
+        self.search(from, &blocked, &tolled)
+    }
+
+    /// The move ending on `node` of a [`Self::caravan_search`], unless `rule`
+    /// forbids stopping there or the tolls on the way cost more than `water`.
+    fn caravan_move(
+        &self,
+        search: &MoveSearch,
+        node: MoveNode,
+        occupied: &HashSet<(u8, u8)>,
+        rule: InteractionRule,
+        water: u32,
+    ) -> Option<CaravanMove> {
+        let coordinates = (node.0, node.1);
+        let from = (search.start.0, search.start.1);
+        let route = search.route_to(node);
+
+        let displaced_to = match (rule, occupied.contains(&coordinates)) {
+            (_, false) => None,
+            (InteractionRule::Displacing, true) => {
+                Some(self.displacement_target(coordinates, from, occupied)?)
+            }
+            _ => return None,
+        };
+
+        let tolls: Vec<(u8, u8)> = match rule {
+            InteractionRule::WaterToll(_) => route.tiles[..route.tiles.len() - 1]
+                .iter()
+                .filter(|tile| occupied.contains(tile))
+                .copied()
+                .collect(),
+            _ => vec![],
+        };
+
+        if let InteractionRule::WaterToll(toll) = rule {
+            if tolls.len() as u32 * toll > water {
+                return None;
+            }
+        }
+
+        Some(CaravanMove {
+            route,
+            tolls,
+            displaced_to,
+        })
+    }
+
+    /// The first free tile next to `coordinates`, in N, E, S, W order, its
+    /// occupant can be pushed to. The mover's own tile counts as free, as the
+    /// mover is leaving it.
+    fn displacement_target(
+        &self,
+        coordinates: (u8, u8),
+        mover: (u8, u8),
+        occupied: &HashSet<(u8, u8)>,
+    ) -> Option<(u8, u8)> {
+        DIRECTIONS
+            .iter()
+            .filter_map(|direction| Self::neighbor_coordinates(coordinates, direction))
+            .find(|neighbor| {
+                self.tiles.contains_key(neighbor)
+                    && (*neighbor == mover || !occupied.contains(neighbor))
+            })
+    }
+
+    /// The route with the fewest steps from `from` to `to` in a single move,
+    /// ignoring other caravans.
+    pub fn shortest_route(&self, from: (u8, u8), to: (u8, u8)) -> Option<Route> {
+        let search = self.search(from, &HashSet::new(), &HashSet::new())?;
+        let destination = search.destinations().find(|node| (node.0, node.1) == to)?;
+
+        Some(search.route_to(destination))
+    }
+
+    /// Tiles reachable from `from` over at most `max_steps` desert lines,
+    /// with the number of steps to each. `from` itself is included at 0.
+    pub fn tiles_within_desert_steps(
+        &self,
+        from: (u8, u8),
+        max_steps: usize,
+    ) -> HashMap<(u8, u8), usize> {
+        let mut steps: HashMap<(u8, u8), usize> = HashMap::new();
+
+        if !self.tiles.contains_key(&from) {
+            return steps;
         }
 
-        let mut visited: HashSet<(u8, u8)> = HashSet::new();
-        let mut bfs = Bfs::new(&graph, start);
+        steps.insert(from, 0);
 
-        while let Some(node) = bfs.next(&graph) {
-            if (node.0, node.1) == from {
+        let mut queue = VecDeque::from([from]);
+
+        while let Some(coordinates) = queue.pop_front() {
+            let distance = steps[&coordinates];
+
+            if distance == max_steps {
                 continue;
             }
 
-            visited.insert((node.0, node.1));
+            for next in self.moves.neighbors((coordinates.0, coordinates.1, 0)) {
+                // Crossing an oasis line is never a desert step
+                if next.2 > 0 || steps.contains_key(&(next.0, next.1)) {
+                    continue;
+                }
+
+                steps.insert((next.0, next.1), distance + 1);
+                queue.push_back((next.0, next.1));
+            }
         }
 
-        visited
-            .into_iter()
-            .filter(|coordinates| !occupied.contains(coordinates))
-            .collect()
+        steps
     }
 
-    fn build_move_graph(&self) -> DiGraphMap<MoveNode, ()> {
-        let mut graph = DiGraphMap::new();
+    /// Oasis lines a caravan on `from` can cross this turn: those on the way
+    /// of any move [`Self::caravan_moves`] allows, on any layer of the search,
+    /// so lines past earlier crossings count too. Sorted and without
+    /// duplicates, so the result is stable.
+    pub fn crossable_oasis_edges(
+        &self,
+        from: (u8, u8),
+        occupied: &HashSet<(u8, u8)>,
+        rule: InteractionRule,
+        water: u32,
+    ) -> Vec<OasisEdge> {
+        let Some(search) = self.caravan_search(from, occupied, rule) else {
+            return vec![];
+        };
+
+        let edges: BTreeSet<OasisEdge> = search
+            .visited
+            .iter()
+            .filter(|node| {
+                self.caravan_move(&search, **node, occupied, rule, water)
+                    .is_some()
+            })
+            .flat_map(|node| search.oasis_crossings(*node))
+            .collect();
+
+        edges.into_iter().collect()
+    }
+
+    /// Whether a caravan on `coordinates` is stuck: every tile it could move
+    /// to is `occupied`, and every side is either placed already or off the
+    /// board, so it can't explore either.
+    pub fn is_cut_off(&self, coordinates: (u8, u8), occupied: &HashSet<(u8, u8)>) -> bool {
+        if !self.reachable_tiles(coordinates, occupied).is_empty() {
+            return false;
+        }
 
-        for (coordinates, oasis_directions) in &self.tiles {
-            for direction in DIRECTIONS {
-                let Some(neighbor) = Self::neighbor_coordinates(*coordinates, &direction) else {
+        DIRECTIONS.iter().all(|direction| {
+            match Self::neighbor_coordinates(coordinates, direction) {
+                Some(neighbor) if neighbor.0 < BOARD_SIZE && neighbor.1 < BOARD_SIZE => {
+                    self.tiles.contains_key(&neighbor)
+                }
+                _ => true,
+            }
+        })
+    }
+
+    /// Searches every route from `from` that avoids `blocked`, cheapest
+    /// first: entering a `tolled` tile costs a toll, and routes with as many
+    /// tolls are ranked by steps. Routes of equal cost are taken in the order
+    /// they're found, so without tolls this is a breadth-first search.
+    fn search(
+        &self,
+        from: (u8, u8),
+        blocked: &HashSet<(u8, u8)>,
+        tolled: &HashSet<(u8, u8)>,
+    ) -> Option<MoveSearch> {
+        let start: MoveNode = (from.0, from.1, 0);
+
+        if !self.moves.contains_node(start) {
+            return None;
+        }
+
+        let mut search = MoveSearch {
+            start,
+            visited: vec![],
+            predecessors: HashMap::new(),
+        };
+        // Cheapest (tolls, steps) found so far to each node
+        let mut costs: HashMap<MoveNode, (usize, usize)> = HashMap::from([(start, (0, 0))]);
+        // The running count keeps nodes of equal cost in the order they were found
+        let mut queue = BinaryHeap::from([Reverse(((0, 0), 0, start))]);
+        let mut found = 0;
+
+        while let Some(Reverse((cost, _, node))) = queue.pop() {
+            // Left over from before a cheaper route to the node was found
+            if costs[&node] < cost {
+                continue;
+            }
+
+            if node != start {
+                search.visited.push(node);
+            }
+
+            for next in self.moves.neighbors(node) {
+                if next == start || blocked.contains(&(next.0, next.1)) {
                     continue;
-                };
+                }
 
-                if !self.tiles.contains_key(&neighbor) {
+                let toll = tolled.contains(&(next.0, next.1)) as usize;
+                let next_cost = (cost.0 + toll, cost.1 + 1);
+
+                if costs.get(&next).is_some_and(|best| *best <= next_cost) {
                     continue;
                 }
 
-                let is_oasis_edge =
-                    oasis_directions.contains(CardinalDirectionFlags::from(&direction));
+                found += 1;
+                costs.insert(next, next_cost);
+                search.predecessors.insert(next, node);
+                queue.push(Reverse((next_cost, found, next)));
+            }
+        }
+
+        Some(search)
+    }
+
+    /// Whether stepping from `coordinates` towards `direction` crosses the
+    /// oasis line on that side of the tile.
+    fn is_oasis_edge(&self, coordinates: (u8, u8), direction: &CardinalDirection) -> bool {
+        self.tiles.get(&coordinates).is_some_and(|tile| {
+            tile.oasis_directions
+                .contains(CardinalDirectionFlags::from(direction))
+        })
+    }
+
+    /// Layers of the move graph a step can leave from. Crossing the last
+    /// allowed oasis line ends the move, so that layer has no way out.
+    fn open_layers(&self) -> std::ops::Range<u8> {
+        0..self.max_oasis_crossings.max(1)
+    }
+
+    /// Adds the step from `from` on `layer` to the neighbor towards
+    /// `direction`, unless it would cross one oasis line too many.
+    fn add_step(&mut self, from: (u8, u8), layer: u8, direction: &CardinalDirection, to: (u8, u8)) {
+        let crossings = layer + self.is_oasis_edge(from, direction) as u8;
+
+        if crossings > self.max_oasis_crossings {
+            return;
+        }
 
-                let source: MoveNode = (coordinates.0, coordinates.1, false);
-                let target: MoveNode = (neighbor.0, neighbor.1, is_oasis_edge);
+        self.moves
+            .add_edge((from.0, from.1, layer), (to.0, to.1, crossings), ());
+    }
+
+    fn add_move_edges(&mut self, coordinates: (u8, u8)) {
+        self.moves.add_node((coordinates.0, coordinates.1, 0));
+
+        for direction in DIRECTIONS {
+            let Some(neighbor) = Self::neighbor_coordinates(coordinates, &direction) else {
+                continue;
+            };
+
+            if !self.tiles.contains_key(&neighbor) {
+                continue;
+            }
 
-                graph.add_edge(source, target, ());
+            for layer in self.open_layers() {
+                self.add_step(coordinates, layer, &direction, neighbor);
+                self.add_step(neighbor, layer, &direction.invert(), coordinates);
             }
         }
+    }
 
-        graph
+    fn remove_move_edges(&mut self, coordinates: (u8, u8)) {
+        for layer in 0..=self.max_oasis_crossings {
+            self.moves
+                .remove_node((coordinates.0, coordinates.1, layer));
+        }
     }
 
     fn neighbor_coordinates(
@@ -111,7 +627,15 @@ mod tests {
     /// Builds a graph from a west-to-east strip. The listed tiles get an oasis
     /// ("white") edge on their east side. All other edges are desert.
     fn strip(tile_count: u8, white_east_sides: &[u8]) -> BoardGraph {
-        let mut graph = BoardGraph::default();
+        strip_with_crossings(DEFAULT_OASIS_CROSSINGS, tile_count, white_east_sides)
+    }
+
+    fn strip_with_crossings(
+        max_oasis_crossings: u8,
+        tile_count: u8,
+        white_east_sides: &[u8],
+    ) -> BoardGraph {
+        let mut graph = BoardGraph::new(max_oasis_crossings);
 
         for x in 0..tile_count {
             let oasis = if white_east_sides.contains(&x) {
@@ -120,7 +644,7 @@ mod tests {
                 CardinalDirectionFlags::empty()
             };
 
-            graph.insert_tile((x, 0), oasis);
+            graph.insert_tile((x, 0), oasis, CardinalDirectionFlags::empty());
         }
 
         graph
@@ -150,4 +674,318 @@ mod tests {
         reachable.sort();
         reachable
     }
+
+    #[test_case(0, &[1] => vec![(1, 0)] ; "no oasis line may be crossed")]
+    #[test_case(2, &[0, 2] => vec![(1, 0), (2, 0), (3, 0)] ; "desert steps continue between crossings")]
+    #[test_case(2, &[0, 1, 2] => vec![(1, 0), (2, 0)] ; "the last allowed crossing ends the move")]
+    fn oasis_crossing_limit(max_oasis_crossings: u8, white_east_sides: &[u8]) -> Vec<(u8, u8)> {
+        let graph = strip_with_crossings(max_oasis_crossings, 5, white_east_sides);
+
+        let mut reachable = graph.reachable_tiles((0, 0), &HashSet::new());
+        reachable.sort();
+        reachable
+    }
+
+    #[test]
+    fn only_the_step_over_the_line_crosses_the_oasis() {
+        let graph = strip_with_crossings(2, 5, &[0, 2]);
+
+        let routes = graph.reachable_routes((0, 0), &HashSet::new());
+
+        assert!(routes[&(1, 0)].crosses_oasis);
+        assert!(!routes[&(2, 0)].crosses_oasis);
+        assert_eq!(routes[&(2, 0)].desert_steps, 1);
+        assert!(routes[&(3, 0)].crosses_oasis);
+    }
+
+    #[test]
+    fn nothing_is_crossable_when_no_crossing_is_allowed() {
+        let graph = strip_with_crossings(0, 3, &[1]);
+
+        assert!(graph
+            .crossable_oasis_edges((0, 0), &HashSet::new(), InteractionRule::PassThrough, 0)
+            .is_empty());
+    }
+
+    #[test_case(4, &[], &[], (3, 0) => Some(vec![(1, 0), (2, 0), (3, 0)]) ; "the route lists every tile entered")]
+    #[test_case(4, &[1], &[], (2, 0) => Some(vec![(1, 0), (2, 0)]) ; "the route ends with the oasis crossing")]
+    #[test_case(3, &[], &[(1, 0)], (2, 0) => Some(vec![(1, 0), (2, 0)]) ; "the route passes through occupied tiles")]
+    #[test_case(3, &[], &[(1, 0)], (1, 0) => None ; "occupied tiles have no route")]
+    #[test_case(4, &[0], &[], (2, 0) => None ; "unreachable tiles have no route")]
+    fn routes(
+        tile_count: u8,
+        white_east_sides: &[u8],
+        occupied: &[(u8, u8)],
+        destination: (u8, u8),
+    ) -> Option<Vec<(u8, u8)>> {
+        let graph = strip(tile_count, white_east_sides);
+        let occupied: HashSet<(u8, u8)> = occupied.iter().copied().collect();
+
+        graph
+            .reachable_routes((0, 0), &occupied)
+            .remove(&destination)
+            .map(|route| route.tiles)
+    }
+
+    #[test]
+    fn routes_take_the_shortest_way_around() {
+        let mut graph = BoardGraph::default();
+
+        // A 3x2 ring: (0, 0) reaches (0, 1) directly rather than around
+        for x in 0..3 {
+            for y in 0..2 {
+                graph.insert_tile(
+                    (x, y),
+                    CardinalDirectionFlags::empty(),
+                    CardinalDirectionFlags::empty(),
+                );
+            }
+        }
+
+        let routes = graph.reachable_routes((0, 0), &HashSet::new());
+
+        assert_eq!(routes[&(0, 1)].tiles, vec![(0, 1)]);
+        assert_eq!(routes[&(2, 1)].cost(), 3);
+    }
+
+    #[test_case(4, &[], (3, 0) => Some((3, 3, false)) ; "a desert route costs one step per line")]
+    #[test_case(4, &[1], (2, 0) => Some((2, 1, true)) ; "an oasis crossing is the last step")]
+    #[test_case(4, &[1], (3, 0) => None ; "nothing follows an oasis crossing")]
+    #[test_case(3, &[], (0, 0) => None ; "there is no route to the start")]
+    #[test_case(3, &[], (7, 0) => None ; "there is no route to an empty spot")]
+    fn shortest_route(
+        tile_count: u8,
+        white_east_sides: &[u8],
+        to: (u8, u8),
+    ) -> Option<(usize, usize, bool)> {
+        let route = strip(tile_count, white_east_sides).shortest_route((0, 0), to)?;
+
+        assert_eq!(route.destination(), Some(to));
+
+        Some((route.cost(), route.desert_steps, route.crosses_oasis))
+    }
+
+    #[test_case(5, &[], 2 => vec![((0, 0), 0), ((1, 0), 1), ((2, 0), 2)] ; "stops after k steps")]
+    #[test_case(5, &[1], 9 => vec![((0, 0), 0), ((1, 0), 1)] ; "oasis lines are not desert steps")]
+    #[test_case(2, &[], 0 => vec![((0, 0), 0)] ; "zero steps only reaches the start")]
+    fn tiles_within_desert_steps(
+        tile_count: u8,
+        white_east_sides: &[u8],
+        max_steps: usize,
+    ) -> Vec<((u8, u8), usize)> {
+        let mut tiles: Vec<((u8, u8), usize)> = strip(tile_count, white_east_sides)
+            .tiles_within_desert_steps((0, 0), max_steps)
+            .into_iter()
+            .collect();
+        tiles.sort();
+        tiles
+    }
+
+    #[test_case(1, 4, &[2], &[], InteractionRule::PassThrough, 0 => vec![((2, 0), (3, 0))] ; "an oasis line past desert lines")]
+    #[test_case(1, 4, &[0, 2], &[], InteractionRule::PassThrough, 0 => vec![((0, 0), (1, 0))] ; "only the first oasis line is in range")]
+    #[test_case(2, 4, &[0, 2], &[], InteractionRule::PassThrough, 0 => vec![((0, 0), (1, 0)), ((2, 0), (3, 0))] ; "oasis_crossings = 2 reaches the second line")]
+    #[test_case(1, 3, &[], &[], InteractionRule::PassThrough, 0 => Vec::<((u8, u8), (u8, u8))>::new() ; "no oasis lines")]
+    #[test_case(1, 3, &[2], &[], InteractionRule::PassThrough, 0 => Vec::<((u8, u8), (u8, u8))>::new() ; "an oasis line facing an empty spot")]
+    #[test_case(1, 4, &[2], &[(1, 0)], InteractionRule::Blocking, 0 => Vec::<((u8, u8), (u8, u8))>::new() ; "a blocking caravan is in the way")]
+    #[test_case(1, 4, &[2], &[(1, 0)], InteractionRule::WaterToll(2), 1 => Vec::<((u8, u8), (u8, u8))>::new() ; "a toll that can't be paid is in the way")]
+    #[test_case(1, 4, &[2], &[(1, 0)], InteractionRule::WaterToll(2), 2 => vec![((2, 0), (3, 0))] ; "a toll that can be paid")]
+    fn crossable_oasis_edges(
+        max_oasis_crossings: u8,
+        tile_count: u8,
+        white_east_sides: &[u8],
+        occupied: &[(u8, u8)],
+        rule: InteractionRule,
+        water: u32,
+    ) -> Vec<((u8, u8), (u8, u8))> {
+        let occupied: HashSet<(u8, u8)> = occupied.iter().copied().collect();
+
+        strip_with_crossings(max_oasis_crossings, tile_count, white_east_sides)
+            .crossable_oasis_edges((0, 0), &occupied, rule, water)
+            .into_iter()
+            .map(|edge| (edge.from, edge.to))
+            .collect()
+    }
+
+    #[test]
+    fn a_caravan_is_cut_off_once_every_tile_around_is_taken() {
+        let mut graph = BoardGraph::default();
+        let mut occupied: HashSet<(u8, u8)> = HashSet::new();
+
+        for x in 0..BOARD_SIZE {
+            for y in 0..BOARD_SIZE {
+                graph.insert_tile(
+                    (x, y),
+                    CardinalDirectionFlags::empty(),
+                    CardinalDirectionFlags::empty(),
+                );
+                occupied.insert((x, y));
+            }
+        }
+
+        occupied.remove(&(0, 0));
+        assert!(graph.is_cut_off((0, 0), &occupied));
+
+        occupied.remove(&(10, 10));
+        assert!(!graph.is_cut_off((0, 0), &occupied));
+    }
+
+    #[test]
+    fn a_caravan_next_to_an_empty_spot_is_not_cut_off() {
+        let graph = strip(1, &[]);
+
+        assert!(!graph.is_cut_off((0, 0), &HashSet::new()));
+    }
+
+    type MoveSummary = ((u8, u8), Vec<(u8, u8)>, Option<(u8, u8)>);
+
+    #[test_case(InteractionRule::PassThrough, 0 => vec![((2, 0), vec![], None)] ; "pass through skips occupied destinations")]
+    #[test_case(InteractionRule::Blocking, 0 => Vec::<MoveSummary>::new() ; "blocking stops at occupied tiles")]
+    #[test_case(InteractionRule::WaterToll(2), 2 => vec![((2, 0), vec![(1, 0)], None)] ; "a toll is owed for every occupied tile passed")]
+    #[test_case(InteractionRule::WaterToll(2), 1 => Vec::<MoveSummary>::new() ; "tolls that can't be paid block the way")]
+    #[test_case(InteractionRule::Displacing, 0 => vec![((1, 0), vec![], Some((2, 0))), ((2, 0), vec![], None)] ; "displacing pushes the occupant aside")]
+    fn caravan_moves(rule: InteractionRule, water: u32) -> Vec<MoveSummary> {
+        let graph = strip(3, &[]);
+        let occupied = HashSet::from([(1, 0)]);
+
+        let mut moves: Vec<MoveSummary> = graph
+            .caravan_moves((0, 0), &occupied, rule, water)
+            .into_iter()
+            .map(|(destination, m)| (destination, m.tolls, m.displaced_to))
+            .collect();
+        moves.sort();
+        moves
+    }
+
+    #[test]
+    fn tolls_are_avoided_by_taking_the_long_way_around() {
+        let mut graph = BoardGraph::default();
+
+        // A 3x2 ring: (0, 0) reaches (2, 0) in 2 steps past the caravan on
+        // (1, 0), or in 4 steps around it
+        for x in 0..3 {
+            for y in 0..2 {
+                graph.insert_tile(
+                    (x, y),
+                    CardinalDirectionFlags::empty(),
+                    CardinalDirectionFlags::empty(),
+                );
+            }
+        }
+
+        let occupied = HashSet::from([(1, 0)]);
+
+        let moves = graph.caravan_moves((0, 0), &occupied, InteractionRule::WaterToll(2), 0);
+
+        assert_eq!(
+            moves[&(2, 0)].route.tiles,
+            vec![(0, 1), (1, 1), (2, 1), (2, 0)]
+        );
+        assert!(moves[&(2, 0)].tolls.is_empty());
+
+        let moves = graph.caravan_moves((0, 0), &occupied, InteractionRule::PassThrough, 0);
+
+        assert_eq!(moves[&(2, 0)].route.tiles, vec![(1, 0), (2, 0)]);
+    }
+
+    #[test]
+    fn a_caravan_with_nowhere_to_go_cannot_be_displaced() {
+        let graph = strip(3, &[]);
+        let occupied = HashSet::from([(1, 0), (2, 0)]);
+
+        let moves = graph.caravan_moves((0, 0), &occupied, InteractionRule::Displacing, 0);
+
+        // (2, 0) has no free neighbor. The tile the mover leaves counts as free.
+        assert!(!moves.contains_key(&(2, 0)));
+        assert_eq!(moves[&(1, 0)].displaced_to, Some((0, 0)));
+        assert!(moves[&(1, 0)].interacts());
+    }
+
+    #[test_case("pass_through", 0 => Ok(InteractionRule::PassThrough))]
+    #[test_case("water_toll", 3 => Ok(InteractionRule::WaterToll(3)))]
+    #[test_case("swap", 0 => Err(InteractionRuleParseError::UnknownRule("swap".to_owned())))]
+    fn parses_interaction_rules(
+        name: &str,
+        toll: u32,
+    ) -> Result<InteractionRule, InteractionRuleParseError> {
+        InteractionRule::from_name(name, toll)
+    }
+
+    #[test]
+    fn placement_order_does_not_change_the_move_graph() {
+        let mut graph = BoardGraph::default();
+
+        for x in [3, 0, 2, 1] {
+            let oasis = if x == 1 {
+                CardinalDirectionFlags::E
+            } else {
+                CardinalDirectionFlags::empty()
+            };
+
+            graph.insert_tile((x, 0), oasis, CardinalDirectionFlags::empty());
+        }
+
+        let mut reachable = graph.reachable_tiles((0, 0), &HashSet::new());
+        reachable.sort();
+
+        assert_eq!(reachable, vec![(1, 0), (2, 0)]);
+    }
+
+    #[test]
+    fn replacing_a_tile_updates_its_edges() {
+        let mut graph = strip(3, &[0]);
+
+        graph.insert_tile(
+            (0, 0),
+            CardinalDirectionFlags::empty(),
+            CardinalDirectionFlags::empty(),
+        );
+
+        let mut reachable = graph.reachable_tiles((0, 0), &HashSet::new());
+        reachable.sort();
+
+        assert_eq!(reachable, vec![(1, 0), (2, 0)]);
+        assert_eq!(graph.tile_count(), 3);
+    }
+
+    #[test]
+    fn keeps_the_treasures_of_placed_tiles() {
+        let mut graph = strip(1, &[]);
+
+        graph.insert_tile(
+            (1, 0),
+            CardinalDirectionFlags::empty(),
+            CardinalDirectionFlags::N,
+        );
+
+        assert_eq!(
+            graph.tile((1, 0)),
+            Some(&TileState {
+                oasis_directions: CardinalDirectionFlags::empty(),
+                treasure_directions: CardinalDirectionFlags::N,
+            })
+        );
+        assert_eq!(graph.tile((5, 5)), None);
+    }
+
+    #[test]
+    fn replacing_a_tile_replaces_its_treasures() {
+        let mut graph = strip(1, &[]);
+
+        graph.insert_tile(
+            (1, 0),
+            CardinalDirectionFlags::empty(),
+            CardinalDirectionFlags::N,
+        );
+        graph.insert_tile((1, 0), CardinalDirectionFlags::W, CardinalDirectionFlags::S);
+
+        assert_eq!(
+            graph.tile((1, 0)),
+            Some(&TileState {
+                oasis_directions: CardinalDirectionFlags::W,
+                treasure_directions: CardinalDirectionFlags::S,
+            })
+        );
+        assert_eq!(graph.reachable_tiles((0, 0), &HashSet::new()), vec![(1, 0)]);
+        assert!(graph.shortest_route((1, 0), (0, 0)).unwrap().crosses_oasis);
+    }
 }
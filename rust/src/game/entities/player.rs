@@ -1,4 +1,6 @@
 use godot::builtin::Color;
+use godot::builtin::PackedVector2Array;
+use godot::builtin::Vector2;
 use godot::classes::ColorRect;
 use godot::classes::Control;
 use godot::classes::IControl;
@@ -13,6 +15,10 @@ use godot::prelude::GodotClass;
 
 use crate::game::entities::player_token::PlayerToken;
 use crate::game::entities::BoardComponent;
+use crate::util::loader::SettingsManager;
+use crate::util::palette::MarkerShape;
+use crate::util::palette::PlayerPalette;
+use crate::util::palette::Rgb;
 
 #[derive(Default, Clone, Copy, Debug, Eq, PartialEq, Hash)]
 pub enum PlayerName {
@@ -32,13 +38,10 @@ impl PlayerName {
             Self::Blue => Self::White,
         }
     }
-    pub fn color(&self) -> Color {
-        match self {
-            PlayerName::White => Color::from_rgba8(255, 255, 255, 255),
-            PlayerName::Orange => Color::from_rgba8(255, 128, 0, 255),
-            PlayerName::Red => Color::from_rgba8(255, 0, 0, 255),
-            PlayerName::Blue => Color::from_rgba8(0, 0, 255, 255),
-        }
+    pub fn color(&self, palette: &PlayerPalette) -> Color {
+        let Rgb(r, g, b) = palette.color(*self);
+
+        Color::from_rgba8(r, g, b, 255)
     }
 }
 
@@ -127,17 +130,27 @@ impl PlayerBuilding {
         let base = self.base();
         base.get_node_as("./ColorRect")
     }
+    fn get_marker(&self) -> Gd<PlayerMarker> {
+        self.base().get_node_as("./Marker")
+    }
     fn set_height(&mut self, height: BuildingType, player: PlayerName) {
         let mut sprite = self.get_sprite();
         let mut initial_size = sprite.get_size();
 
         self.height = height;
+        self.player = player;
 
         initial_size.y = self.height.get_height_in_px(&player);
 
         sprite.set_size(initial_size);
 
-        sprite.set_color(player.color());
+        self.get_marker().bind_mut().set_player(player);
+        self.recolor();
+    }
+    fn recolor(&mut self) {
+        let palette = SettingsManager::get(&self.to_gd()).bind().player_palette();
+
+        self.get_sprite().set_color(self.player.color(&palette));
     }
 }
 
@@ -145,6 +158,70 @@ impl PlayerBuilding {
 impl INode2D for PlayerBuilding {
     fn ready(&mut self) {
         self.set_height(self.height, self.player);
+
+        SettingsManager::get(&self.to_gd())
+            .signals()
+            .settings_changed()
+            .connect_other(&*self, |this| this.recolor());
+    }
+}
+
+const MARKER_RADIUS: f32 = 6.;
+const MARKER_OUTLINE_WIDTH: f32 = 1.5;
+
+/// Draws the player's `MarkerShape`, so tokens and buildings can be told
+/// apart without relying on color. Only visible with `show_markers` enabled.
+#[derive(GodotClass, Debug)]
+#[class(init, base=Node2D)]
+pub struct PlayerMarker {
+    base: Base<Node2D>,
+
+    player: PlayerName,
+}
+
+impl PlayerMarker {
+    pub fn set_player(&mut self, player: PlayerName) {
+        self.player = player;
+        self.base_mut().queue_redraw();
+    }
+    fn update_visibility(&mut self) {
+        let show_markers = SettingsManager::get(&self.to_gd())
+            .bind()
+            .settings()
+            .show_markers;
+
+        self.base_mut().set_visible(show_markers);
+    }
+}
+
+#[godot_api]
+impl INode2D for PlayerMarker {
+    fn ready(&mut self) {
+        self.update_visibility();
+
+        SettingsManager::get(&self.to_gd())
+            .signals()
+            .settings_changed()
+            .connect_other(&*self, |this| this.update_visibility());
+    }
+    fn draw(&mut self) {
+        let mut points: PackedVector2Array = MarkerShape::from(self.player)
+            .polygon(MARKER_RADIUS)
+            .into_iter()
+            .map(|(x, y)| Vector2::new(x, y))
+            .collect();
+
+        self.base_mut().draw_colored_polygon(&points, Color::BLACK);
+
+        // Close the outline so the last edge is drawn as well
+        if let Some(first) = points.get(0) {
+            points.push(first);
+        }
+
+        self.base_mut()
+            .draw_polyline_ex(&points, Color::WHITE)
+            .width(MARKER_OUTLINE_WIDTH)
+            .done();
     }
 }
 
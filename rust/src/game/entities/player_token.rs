@@ -2,13 +2,19 @@ use godot::builtin::Color;
 use godot::classes::INode2D;
 use godot::classes::Node2D;
 use godot::classes::Sprite2D;
+use godot::classes::Tween;
 use godot::obj::Base;
 use godot::obj::Gd;
 use godot::obj::WithBaseField;
 use godot::prelude::godot_api;
 use godot::prelude::GodotClass;
 
+use crate::game::entities::player::PlayerMarker;
 use crate::game::entities::player::PlayerName;
+use crate::util::loader::SettingsManager;
+
+// Brightest channel of the modulate applied to the camel icon
+const TINT_GAIN: f32 = 2.0;
 
 #[derive(GodotClass, Debug)]
 #[class(init, base=Node2D)]
@@ -16,28 +22,58 @@ pub struct PlayerToken {
     base: Base<Node2D>,
 
     pub player: PlayerName,
+    // Walks the token along its current route, if it's still moving
+    route_tween: Option<Gd<Tween>>,
 }
 
 impl PlayerToken {
     fn get_sprite(&self) -> Gd<Sprite2D> {
         self.base().get_node_as("./Sprite2D")
     }
+    fn get_marker(&self) -> Gd<PlayerMarker> {
+        self.base().get_node_as("./Marker")
+    }
     fn tint(&self) {
-        let color = self.player.color();
+        let palette = SettingsManager::get(&self.to_gd()).bind().player_palette();
+        let color = self.player.color(&palette);
 
         // The camel icon is light brown, so a plain multiply mutes the tint.
-        // Overshoot the modulate so the primary saturates over that base.
+        // Overshoot the modulate so the color saturates over that base, and
+        // scale every channel by the same gain so the hue survives. Custom
+        // colors aren't pure primaries, and multiplying each channel by a
+        // constant clipped e.g. orange into red.
+        let brightest = color.r.max(color.g).max(color.b).max(f32::EPSILON);
+        let gain = TINT_GAIN / brightest;
+
         let vibrant = Color {
-            r: color.r * 3.0,
-            g: color.g * 3.0,
-            b: color.b * 3.0,
+            r: color.r * gain,
+            g: color.g * gain,
+            b: color.b * gain,
             a: 1.0,
         };
 
         self.get_sprite().set_modulate(vibrant);
     }
+    /// Stops walking the current route, leaving the token where it is.
+    pub fn stop_moving(&mut self) {
+        if let Some(mut tween) = self.route_tween.take() {
+            tween.kill();
+        }
+    }
+    /// A tween for walking a new route. The previous route's tween is
+    /// stopped, so two tweens never fight over the token's position.
+    pub fn start_route(&mut self) -> Gd<Tween> {
+        self.stop_moving();
+
+        let tween = self.base_mut().create_tween();
+
+        self.route_tween = Some(tween.clone());
+
+        tween
+    }
     pub fn assign_token_to_player(&mut self, player: PlayerName) {
         self.player = player;
+        self.get_marker().bind_mut().set_player(player);
         self.tint();
     }
 }
@@ -45,6 +81,12 @@ impl PlayerToken {
 #[godot_api]
 impl INode2D for PlayerToken {
     fn ready(&mut self) {
+        self.get_marker().bind_mut().set_player(self.player);
         self.tint();
+
+        SettingsManager::get(&self.to_gd())
+            .signals()
+            .settings_changed()
+            .connect_other(&*self, |this| this.tint());
     }
 }
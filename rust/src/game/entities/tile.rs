@@ -1,22 +1,29 @@
 use crate::game::components::hover_outline::CollisionOutline;
+use crate::game::components::hover_outline::HoverKind;
+use crate::game::components::hover_outline::HoverManager;
 use crate::game::components::tile_component::TileComponent;
 use crate::game::components::tile_component::TileData;
+use crate::game::entities::entity_id::EntityId;
+use crate::game::entities::entity_id::EntityScope;
 use crate::game::entities::treasure::Treasure;
-use crate::game::entities::treasure::TreasureKind;
 use crate::game::entities::BoardComponent;
 use crate::game::entities::Entity;
 use crate::game::entities::EntityManager;
-use crate::game::entities::EntityScope;
+use crate::game::entities::TileGetError;
+use crate::util::errors::GameError;
 use crate::util::flags::CardinalDirection;
 use crate::util::flags::CardinalDirectionFlags;
 use crate::util::flags::DIRECTIONS;
 use crate::util::flags::OASIS_CONNECTION_LABELS;
 use crate::util::input::InputActions;
 use crate::util::loader::GameConfig;
-use crate::util::loader::TileConfig;
-use crate::util::loader::TilesetConfig;
 use crate::util::loader::TomlLoader;
 use crate::util::loader::CROSS_IDS;
+use crate::util::tileset::cross_coordinates;
+use crate::util::tileset::TileConfig;
+use crate::util::treasures::is_no_treasure;
+use crate::util::treasures::TreasureCatalog;
+use crate::util::ErrorReporter;
 use crate::util::Logger;
 use godot::builtin::Array;
 use godot::builtin::Color;
@@ -35,7 +42,6 @@ use godot::classes::Sprite2D;
 use godot::global::godot_error;
 use godot::obj::Base;
 use godot::obj::Gd;
-use godot::obj::InstanceId;
 use godot::obj::Singleton;
 use godot::obj::WithBaseField;
 use godot::obj::WithUserSignals;
@@ -88,8 +94,8 @@ impl TileCollisionAreas {
 pub struct Tile {
     base: Base<Node2D>,
 
-    #[init(val = 0)]
-    id: u64,
+    #[init(val = None)]
+    id: Option<EntityId>,
 
     #[init(val = false)]
     is_active: bool,
@@ -103,9 +109,6 @@ pub struct Tile {
     #[init(val = 0)]
     outside_connections: u8,
 
-    #[init(val = vec![])]
-    // TODO: Replace this with a global manager implemented in code
-    active_collisions: Vec<InstanceId>,
     #[init(val = 0.)]
     throttle_wheel: f64,
 
@@ -113,9 +116,14 @@ pub struct Tile {
     is_move_destination: bool,
     #[init(val = false)]
     move_hovered: bool,
+    #[init(val = false)]
+    is_interaction_move: bool,
 }
 
 impl Tile {
+    pub fn entity_id(&self) -> Result<EntityId, TileGetError> {
+        self.id.ok_or(TileGetError::TileNotRegisteredError)
+    }
     fn hide_all_connections(&self) {
         for connection in OASIS_CONNECTION_LABELS {
             let mut connection = self.get_connection_at(connection);
@@ -207,6 +215,12 @@ impl Tile {
         self.get_move_highlight().set_visible(enabled);
         self.refresh_move_highlight();
     }
+    /// Marks moving here as affecting another caravan, e.g. paying it a toll
+    /// or pushing it aside, so it's highlighted in a distinct color.
+    pub fn set_move_interaction(&mut self, interacts: bool) {
+        self.is_interaction_move = interacts;
+        self.refresh_move_highlight();
+    }
     fn get_placement_highlight(&self, direction: &CardinalDirection) -> Gd<ColorRect> {
         let direction: &str = direction.into();
 
@@ -223,7 +237,23 @@ impl Tile {
     /// The tile under the cursor is paler than the rest so it reads as the
     /// current destination.
     fn refresh_move_highlight(&self) {
-        let color = if self.move_hovered {
+        let color = if self.is_interaction_move {
+            if self.move_hovered {
+                Color {
+                    r: 1.0,
+                    g: 0.9,
+                    b: 0.7,
+                    a: 1.0,
+                }
+            } else {
+                Color {
+                    r: 1.0,
+                    g: 0.55,
+                    b: 0.1,
+                    a: 1.0,
+                }
+            }
+        } else if self.move_hovered {
             Color {
                 r: 0.8,
                 g: 0.97,
@@ -254,6 +284,33 @@ impl Tile {
 
         CardinalDirectionFlags::from(tile_component.oasis_layout.clone())
     }
+    /// Treasure id on the N, E, S and W side, `none` or empty without one.
+    pub fn treasure_ids(&self) -> [String; 4] {
+        let gd_tile_component = self.get_tile_component();
+        let tile_component = gd_tile_component.bind();
+        let mut ids: [String; 4] = Default::default();
+
+        for (id, treasure) in ids
+            .iter_mut()
+            .zip(tile_component.treasure_layout.iter_shared())
+        {
+            *id = treasure.to_string();
+        }
+
+        ids
+    }
+    pub fn treasure_directions(&self) -> CardinalDirectionFlags {
+        let gd_tile_component = self.get_tile_component();
+        let tile_component = gd_tile_component.bind();
+
+        DIRECTIONS
+            .iter()
+            .zip(tile_component.treasure_layout.iter_shared())
+            .filter(|(_, treasure)| !is_no_treasure(&treasure.to_string()))
+            .fold(CardinalDirectionFlags::empty(), |flags, (direction, _)| {
+                flags | CardinalDirectionFlags::from(direction)
+            })
+    }
     /// Confines exploration to `open`: a drawn tile may only be dropped on
     /// these sides of this tile. The open sides also glow cyan to show where
     /// placement is possible.
@@ -274,9 +331,11 @@ impl Tile {
     /// Queues this tile as the active caravan's destination and defers the
     /// move to [`BoardComponent::apply_pending_move`].
     fn try_move_here(&mut self) {
-        let coordinates = BoardComponent::get(&self.base())
-            .bind()
-            .get_tile_coordinates(self.id);
+        let coordinates = self.entity_id().and_then(|id| {
+            BoardComponent::get(&self.base())
+                .bind()
+                .get_tile_coordinates(id)
+        });
 
         match coordinates {
             Ok(coordinates) => {
@@ -288,6 +347,26 @@ impl Tile {
             Err(error) => Logger::error(&format!("{error:?}")),
         }
     }
+    /// Shows or hides the active caravan's route to this tile. The board
+    /// applies it later through `call_deferred`, as drawing the route binds
+    /// this tile again.
+    fn preview_route_here(&self, hovered: bool) {
+        let mut gd_board_component = BoardComponent::get(&self.base());
+
+        let coordinates = self
+            .entity_id()
+            .and_then(|id| gd_board_component.bind().get_tile_coordinates(id));
+
+        match coordinates {
+            Ok(coordinates) => {
+                gd_board_component
+                    .bind_mut()
+                    .queue_route_preview(coordinates, hovered);
+                gd_board_component.call_deferred("refresh_route_preview", &[]);
+            }
+            Err(error) => Logger::error(&format!("{error:?}")),
+        }
+    }
     pub fn place_at(
         &mut self,
         direction: CardinalDirection,
@@ -303,11 +382,25 @@ impl Tile {
         // exploring player an immediate extra move + explore.
         let is_desert_tile = self.oasis_directions().is_empty();
 
+        let oasis_directions = self.oasis_directions();
+        let treasure_directions = self.treasure_directions();
+
         let mut gd_board_component = BoardComponent::get(&self.base());
         let mut board_component = gd_board_component.bind_mut();
 
-        if let Err(error) = board_component.add_tile_at(self.id, coordinates.0, coordinates.1) {
-            godot_error!("{error:?}");
+        match self.entity_id() {
+            Ok(id) => {
+                if let Err(error) = board_component.add_tile_at(
+                    id,
+                    coordinates.0,
+                    coordinates.1,
+                    oasis_directions,
+                    treasure_directions,
+                ) {
+                    godot_error!("{error:?}");
+                }
+            }
+            Err(error) => godot_error!("{error:?}"),
         };
 
         board_component.queue_placement(coordinates, is_desert_tile);
@@ -349,6 +442,12 @@ impl Tile {
             .bind()
             .get_collision_area_at_direction(direction)
     }
+    /// Shows edited treasure definitions without restarting the game.
+    fn on_config_reloaded(&mut self, path: GString) {
+        if path.to_string() == GameConfig::Treasures.path() {
+            self.refresh_display_state();
+        }
+    }
     pub fn refresh_display_state(&mut self) {
         let gd_tile_components = self.get_tile_component();
         let tile_components = gd_tile_components.bind();
@@ -357,6 +456,11 @@ impl Tile {
 
         let mut connections: Vec<&str> = vec![];
 
+        let treasures = TomlLoader::treasures(&self.base()).unwrap_or_else(|error| {
+            Logger::error(&error.to_string());
+            TreasureCatalog::default()
+        });
+
         for oasis_idx in 0..4 {
             let flag = (tile_components.oasis_layout.bits() >> (oasis_idx * 4)) as u8;
             let direction_flags = CardinalDirectionFlags::from_bits_truncate(flag);
@@ -376,25 +480,33 @@ impl Tile {
 
                 let mut gd_treasure = self.get_treasure_at_direction(direction);
 
-                let treasure_at_idx = tile_components
+                let treasure_id = tile_components
                     .treasure_layout
                     .get(direction_idx)
-                    .unwrap_or(GString::from(""));
-
-                let treasure_kind: TreasureKind = treasure_at_idx
-                    .try_into()
-                    .expect("Couldn't parse treasure_layout into");
+                    .unwrap_or(GString::from(""))
+                    .to_string();
+
+                // The tileset is validated against the treasures on load, so
+                // an unknown id here means the tile was built some other way
+                let definition = match treasures.get(&treasure_id) {
+                    _ if is_no_treasure(&treasure_id) => None,
+                    Some(definition) => Some(definition.clone()),
+                    None => {
+                        Logger::error(&format!("Undefined treasure {treasure_id} on tile"));
+                        None
+                    }
+                };
 
                 let mut treasure = gd_treasure.bind_mut();
 
-                if treasure_kind != TreasureKind::None {
+                if definition.is_some() {
                     let sprite = &mut treasure.get_sprites()[0];
                     sprite.set_visible(true);
 
                     self.show_desert_icon_if_not_cross(false);
                 }
 
-                treasure.kind = treasure_kind;
+                treasure.definition = definition;
             }
         }
 
@@ -448,25 +560,7 @@ impl Entity for Tile {
             .bind_mut()
             .register(instance_id, EntityScope::Running);
 
-        self.id = id;
-    }
-}
-
-#[godot_api]
-impl Tile {
-    // TODO: Replace this with a global manager implemented in code
-    #[func]
-    fn insert_active_collision(&mut self, id: InstanceId) {
-        self.active_collisions.push(id);
-    }
-    // TODO: Replace this with a global manager implemented in code
-    #[func]
-    fn remove_active_collision(&mut self, id: InstanceId) {
-        let index = self.active_collisions.iter().position(|inner| *inner == id);
-
-        if let Some(index) = index {
-            self.active_collisions.swap_remove(index);
-        }
+        self.id = Some(id);
     }
 }
 
@@ -486,20 +580,30 @@ impl INode2D for Tile {
             let mut tile_config: Option<TileConfig> = None;
 
             if is_cross_tile {
-                let tileset = TomlLoader::get(&self.base(), GameConfig::Tileset)
-                    .expect("Couldn't load tileset. Check if config/tileset.toml exists");
-
-                let parsed_config = TilesetConfig::try_from(&tileset)
-                    .expect("Couldn't parse tileset. Check syntax of config/tileset.toml");
+                let parsed_config = match TomlLoader::tileset(&self.base()) {
+                    Ok(parsed_config) => parsed_config,
+                    Err(error) => {
+                        return ErrorReporter::report(&self.base(), GameError::from(error));
+                    }
+                };
 
                 if &cross_id == "cross_c" {
                     tile_config = Some(parsed_config.cross.get_center());
                     self.outside_connections = CardinalDirectionFlags::all().bits();
                 } else {
-                    tile_config = Some(
-                        parsed_config.cross.get_side(&cross_id).unwrap()[self.cross_index as usize]
-                            .clone(),
-                    );
+                    let side_config = parsed_config
+                        .cross
+                        .get_side(&cross_id)
+                        .ok()
+                        .and_then(|side| side.get(self.cross_index as usize).cloned());
+
+                    let Some(side_config) = side_config else {
+                        let error = GameError::MissingCrossTile(cross_id, self.cross_index + 1);
+
+                        return ErrorReporter::report(&self.base(), error);
+                    };
+
+                    tile_config = Some(side_config);
 
                     match cross_id.as_str() {
                         "cross_n" => {
@@ -556,6 +660,11 @@ impl INode2D for Tile {
 
         self.refresh_display_state();
 
+        TomlLoader::global(&self.base())
+            .signals()
+            .config_reloaded()
+            .connect_other(self, Self::on_config_reloaded);
+
         let collision_areas = self.get_collision_areas();
 
         collision_areas
@@ -574,40 +683,57 @@ impl INode2D for Tile {
             .signals()
             .mouse_entered()
             .connect_other(self, |tile| {
-                tile.move_hovered = true;
-                tile.refresh_move_highlight();
+                HoverManager::enter(&tile.get_move_area(), HoverKind::MoveDestination)
             });
         move_area
             .signals()
             .mouse_exited()
-            .connect_other(self, |tile| {
-                tile.move_hovered = false;
-                tile.refresh_move_highlight();
-            });
+            .connect_other(self, |tile| HoverManager::exit(&tile.get_move_area()));
 
         if is_cross_tile {
             let mut board_component = BoardComponent::get(&self.base());
 
-            let (x, y) = match self.cross_id.to_string().as_str() {
-                "cross_c" => (5, 5),
-                "cross_n" => (5, self.cross_index + 6),
-                "cross_e" => (self.cross_index + 6, 5),
-                "cross_s" => (5, 4 - self.cross_index),
-                "cross_w" => (4 - self.cross_index, 5),
-                _ => {
-                    godot_error!("Expected `cross_id` to be one of cross_[c, n, e, s, w]");
-                    return;
-                }
+            let coordinates = self
+                .cross_id
+                .to_string()
+                .strip_prefix("cross_")
+                .and_then(|arm| arm.chars().next())
+                .and_then(|arm| cross_coordinates(arm, self.cross_index));
+
+            let Some((x, y)) = coordinates else {
+                godot_error!("Expected `cross_id` to be one of cross_[c, n, e, s, w]");
+                return;
             };
 
+            let oasis_directions = self.oasis_directions();
+            let treasure_directions = self.treasure_directions();
+
             let mut board_component = board_component.bind_mut();
 
-            if let Err(error) = board_component.add_tile_at(self.id, x, y) {
-                godot_error!("{error:?}");
+            match self.entity_id() {
+                Ok(id) => {
+                    if let Err(error) =
+                        board_component.add_tile_at(id, x, y, oasis_directions, treasure_directions)
+                    {
+                        godot_error!("{error:?}");
+                    }
+                }
+                Err(error) => godot_error!("{error:?}"),
             }
         }
     }
     fn process(&mut self, dt: f64) {
+        if self.is_move_destination {
+            let move_hovered =
+                HoverManager::is_top(&self.get_move_area(), HoverKind::MoveDestination);
+
+            if move_hovered != self.move_hovered {
+                self.move_hovered = move_hovered;
+                self.refresh_move_highlight();
+                self.preview_route_here(move_hovered);
+            }
+        }
+
         if self.is_move_destination && self.move_hovered {
             let input = Input::singleton();
 
@@ -650,13 +776,11 @@ impl INode2D for Tile {
             self.throttle_wheel += dt * 1000.;
         }
 
-        let collision_id = self.active_collisions.first();
-
-        if let Some(collision_id) = collision_id {
-            // TODO: This isn't safe, but we can potentially make it safe(r) _by implementing a
-            // manager_
-            let mut collision: Gd<CollisionOutline> = Gd::from_instance_id(*collision_id);
+        let collision = HoverManager::get(&self.base())
+            .bind_mut()
+            .top::<CollisionOutline>(HoverKind::PlacementOutline);
 
+        if let Some(mut collision) = collision {
             let collision_side: Vec<CardinalDirection> =
                 CardinalDirectionFlags::from_bits_truncate(collision.bind().side).into();
 
@@ -674,7 +798,10 @@ impl INode2D for Tile {
                 let gd_board_component = BoardComponent::get(&self.base());
                 let board_component = gd_board_component.bind();
 
-                match board_component.get_tile_coordinates(collided_tile.id) {
+                match collided_tile
+                    .entity_id()
+                    .and_then(|id| board_component.get_tile_coordinates(id))
+                {
                     Ok((x, y)) => {
                         let x = x as i32;
                         let y = y as i32;
@@ -800,25 +927,21 @@ impl INode2D for Tile {
                 let placement_coordinates =
                     (placement_coordinates.0 as u8, placement_coordinates.1 as u8);
 
-                if self.active_collisions.len() != 1 {
-                    godot_error!("Must have exactly 1 collision to place tile");
-                } else {
-                    self.is_active = false;
+                self.is_active = false;
 
-                    let position = collision.get_global_position();
+                let position = collision.get_global_position();
 
-                    collision.signals().submitted_at().emit();
+                collision.signals().submitted_at().emit();
 
-                    target_position = Some(self.place_at(
-                        collision_side[0].clone(),
-                        position,
-                        placement_coordinates,
-                        adjacent_tiles,
-                    ));
+                target_position = Some(self.place_at(
+                    collision_side[0].clone(),
+                    position,
+                    placement_coordinates,
+                    adjacent_tiles,
+                ));
 
-                    let mut base = self.base_mut();
-                    base.set_scale(Vector2::from_tuple((0.2 * 0.9, 0.2 * 0.9)))
-                }
+                self.base_mut()
+                    .set_scale(Vector2::from_tuple((0.2 * 0.9, 0.2 * 0.9)));
 
                 self.refresh_display_state();
             }
@@ -1,216 +1,47 @@
 use godot::classes::CompressedTexture2D;
 use godot::classes::Control;
+use godot::classes::Image;
+use godot::classes::ImageTexture;
 use godot::classes::Label;
 use godot::classes::Sprite2D;
-use godot::meta::error::IoError;
+use godot::classes::Texture2D;
 use godot::prelude::*;
 
-use thiserror::Error;
-
 use crate::game::components::hover_outline::ActionCollisionSquare;
-
-const MAX_TREASURE_SUBSTRINGS: u8 = 2;
-
-#[derive(Error, Debug)]
-pub enum TreasureKindParseError {
-    #[error("TreasureKind expected {0} arguments, received {1}")]
-    ArgumentCount(String, usize),
-    #[error("TreasureKind expected one of water, double_water, goods:[gems, myrrh, salt, incense], camels, rumors, received {0}")]
-    ParseTreasure(String),
-    #[error("TreasureKinds expected goods to be one of goods:[gems, myrrh, salt, incense], received {0}")]
-    ParseGoods(String),
-}
-
-#[derive(Clone, Debug, PartialEq)]
-pub enum Good {
-    Incense,
-    Myrrh,
-    Salt,
-    Gems,
-}
-
-impl TryFrom<&str> for Good {
-    type Error = TreasureKindParseError;
-
-    fn try_from(value: &str) -> Result<Self, TreasureKindParseError> {
-        match value {
-            "incense" => Ok(Good::Incense),
-            "myrrh" => Ok(Good::Myrrh),
-            "salt" => Ok(Good::Salt),
-            "gems" => Ok(Good::Gems),
-            _ => Err(TreasureKindParseError::ParseGoods(String::from(value))),
-        }
-    }
-}
-
-#[derive(Clone, Debug, PartialEq)]
-pub enum TreasureKind {
-    Water,
-    DoubleWater,
-    Goods(Good),
-    Camels,
-    Rumors,
-    None,
-}
-
-impl TreasureKind {
-    pub fn get_label_text(&self) -> &'static str {
-        match self {
-            TreasureKind::Water => "Water",
-            TreasureKind::DoubleWater => "Water x2",
-            TreasureKind::Camels => "Camels",
-            TreasureKind::Rumors => "Rumors",
-            TreasureKind::Goods(good) => match good {
-                Good::Incense => "Incense",
-                Good::Myrrh => "Myrrh",
-                Good::Salt => "Salt",
-                Good::Gems => "Gems",
-            },
-            TreasureKind::None => "No treasure",
-        }
-    }
-}
-
-impl From<&TreasureKind> for &str {
-    fn from(val: &TreasureKind) -> Self {
-        match val {
-            TreasureKind::Water => "water",
-            TreasureKind::DoubleWater => "double_water",
-            TreasureKind::Camels => "camels",
-            TreasureKind::Rumors => "rumors",
-            TreasureKind::Goods(good) => match good {
-                Good::Incense => "goods/incense",
-                Good::Myrrh => "goods/myrrh",
-                Good::Salt => "goods/salt",
-                Good::Gems => "goods/gems",
-            },
-            TreasureKind::None => "none",
-        }
-    }
-}
-
-impl From<&TreasureKind> for GString {
-    fn from(value: &TreasureKind) -> GString {
-        let kind_string: &str = value.into();
-
-        GString::from(kind_string)
-    }
-}
-
-impl TryFrom<&str> for TreasureKind {
-    type Error = TreasureKindParseError;
-
-    fn try_from(value: &str) -> Result<Self, TreasureKindParseError> {
-        let substrings: Vec<String> = value
-            .split(':')
-            .map(|substring| substring.to_lowercase())
-            .collect();
-
-        if substrings.is_empty() || substrings.len() > MAX_TREASURE_SUBSTRINGS as usize {
-            return Err(TreasureKindParseError::ArgumentCount(
-                "1 to 2".to_owned(),
-                substrings.len(),
-            ));
-        }
-
-        match substrings[0].as_str() {
-            "double_water" => Ok(TreasureKind::DoubleWater),
-            "water" => Ok(TreasureKind::Water),
-            "goods" => {
-                if substrings.len() != MAX_TREASURE_SUBSTRINGS as usize {
-                    Err(TreasureKindParseError::ArgumentCount(
-                        "2".to_owned(),
-                        substrings.len(),
-                    ))
-                } else {
-                    Ok(TreasureKind::Goods(Good::try_from(substrings[1].as_str())?))
-                }
-            }
-            "camels" => Ok(TreasureKind::Camels),
-            "rumors" => Ok(TreasureKind::Rumors),
-            "none" => Ok(TreasureKind::None),
-            "" => Ok(TreasureKind::None),
-            _ => Err(TreasureKindParseError::ParseTreasure(
-                substrings
-                    .into_iter()
-                    .next()
-                    .expect("Substrings should have length greater than 0."),
-            )),
-        }
-    }
-}
-
-impl TryFrom<&String> for TreasureKind {
-    type Error = TreasureKindParseError;
-
-    fn try_from(value: &String) -> Result<Self, TreasureKindParseError> {
-        TreasureKind::try_from(value.as_str())
-    }
-}
-
-impl TryFrom<String> for TreasureKind {
-    type Error = TreasureKindParseError;
-
-    fn try_from(value: String) -> Result<Self, TreasureKindParseError> {
-        TreasureKind::try_from(&value)
-    }
-}
-
-impl TryFrom<GString> for TreasureKind {
-    type Error = TreasureKindParseError;
-
-    fn try_from(value: GString) -> Result<Self, TreasureKindParseError> {
-        TreasureKind::try_from(value.to_string())
-    }
-}
-
-impl TryFrom<&GString> for TreasureKind {
-    type Error = TreasureKindParseError;
-
-    fn try_from(value: &GString) -> Result<Self, TreasureKindParseError> {
-        TreasureKind::try_from(value.to_string())
-    }
-}
-
-impl TryFrom<&TreasureKind> for Option<Gd<CompressedTexture2D>> {
-    type Error = IoError;
-
-    fn try_from(kind: &TreasureKind) -> Result<Option<Gd<CompressedTexture2D>>, IoError> {
-        if *kind == TreasureKind::None {
-            return Ok(None);
-        }
-
-        let icon_name: &str = kind.into();
-
-        let path = format!("res://assets/icons/treasure/{icon_name}.png");
-
-        try_load::<CompressedTexture2D>(&path).map(Some)
-    }
-}
-
-impl TryFrom<TreasureKind> for Option<Gd<CompressedTexture2D>> {
-    type Error = IoError;
-
-    fn try_from(kind: TreasureKind) -> Result<Option<Gd<CompressedTexture2D>>, IoError> {
-        let image_texture: Option<Gd<CompressedTexture2D>> = (&kind).try_into()?;
-
-        Ok(image_texture)
-    }
-}
+use crate::game::components::hover_outline::HoverKind;
+use crate::game::components::hover_outline::HoverManager;
+use crate::util::mods::MODS_ROOT;
+use crate::util::treasures::TreasureDefinition;
+use crate::util::Logger;
 
 #[derive(GodotClass, Debug)]
 #[class(init, base=Node2D)]
 pub struct Treasure {
     base: Base<Node2D>,
 
-    #[init(val = TreasureKind::None)]
-    pub kind: TreasureKind,
-    #[init(val = TreasureKind::None)]
-    sprite_kind: TreasureKind,
+    /// None for sides without a treasure
+    pub definition: Option<TreasureDefinition>,
+    // The definition the sprites and label currently show
+    shown_definition: Option<TreasureDefinition>,
 
     tooltip_visible: bool,
 }
 
+/// Loads a treasure icon. Content packs ship plain images rather than
+/// imported resources, so their icons are read straight from the file.
+fn load_icon(path: &str) -> Result<Gd<Texture2D>, String> {
+    if path.starts_with(MODS_ROOT) {
+        return Image::load_from_file(path)
+            .and_then(|image| ImageTexture::create_from_image(&image))
+            .map(Gd::upcast)
+            .ok_or(String::from("not a readable image"));
+    }
+
+    try_load::<CompressedTexture2D>(path)
+        .map(Gd::upcast)
+        .map_err(|error| error.to_string())
+}
+
 impl Treasure {
     pub fn get_hover_collision(&self) -> Gd<ActionCollisionSquare> {
         self.base().get_node_as("./ActionCollision")
@@ -238,7 +69,7 @@ impl Treasure {
         self.base().get_node_as("./Tooltip")
     }
     fn change_tooltip_visibility(&mut self, visible: bool) {
-        if self.kind == TreasureKind::None {
+        if self.definition.is_none() {
             return;
         }
 
@@ -251,25 +82,11 @@ impl Treasure {
 impl INode2D for Treasure {
     fn ready(&mut self) {
         self.hide_icon();
-        let gd_self = self.to_gd();
-
-        let hover_collision = self.get_hover_collision();
-
-        hover_collision
-            .bind()
-            .base()
-            .signals()
-            .mouse_entered()
-            .connect_other(&gd_self, |this| this.tooltip_visible = true);
-
-        hover_collision
-            .bind()
-            .base()
-            .signals()
-            .mouse_exited()
-            .connect_other(&gd_self, |this| this.tooltip_visible = false);
     }
     fn process(&mut self, _dt: f64) {
+        // Neighbouring treasures' collisions overlap, only one tooltip shows
+        self.tooltip_visible = HoverManager::is_top(&self.get_hover_collision(), HoverKind::Action);
+
         let is_tooltip_visible = self.get_tooltip().is_visible();
 
         if self.tooltip_visible != is_tooltip_visible {
@@ -287,33 +104,32 @@ impl INode2D for Treasure {
             tooltip.set_global_position(mouse_position);
         }
 
-        if self.sprite_kind == self.kind {
+        if self.shown_definition == self.definition {
             return;
         }
 
-        if self.kind == TreasureKind::None {
-            self.sprite_kind = self.kind.clone();
+        self.shown_definition = self.definition.clone();
 
+        let Some(definition) = self.definition.clone() else {
             return self.hide_icon();
-        }
+        };
 
         let mut sprites = self.get_sprites();
 
-        let texture: Option<Gd<CompressedTexture2D>> = (&self.kind)
-            .try_into()
-            .expect("Provided invalid TreasureKind to Treasure.ready()");
-
-        if let Some(texture) = texture {
-            sprites[0].set_texture(&texture);
-            sprites[1].set_texture(&texture);
+        match load_icon(&definition.icon) {
+            Ok(texture) => {
+                sprites[0].set_texture(&texture);
+                sprites[1].set_texture(&texture);
+            }
+            Err(error) => Logger::error(&format!(
+                "Couldn't load icon {} of treasure {}: {error}",
+                definition.icon, definition.id
+            )),
         }
 
-        let label = self.kind.get_label_text();
         let mut gd_label = self.get_label();
 
-        gd_label.set_text(label);
-
-        self.sprite_kind = self.kind.clone();
+        gd_label.set_text(&definition.label);
 
         if !sprites[0].is_visible() {
             self.show_icon();
@@ -1,3 +1,5 @@
+use thiserror::Error;
+
 use crate::game::entities::player::PlayerName;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -6,22 +8,79 @@ pub enum TurnPhase {
     Explore,
 }
 
+/// What a player gets for exploring a pure-desert tile.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DesertReward {
+    /// Another full turn: a move, then an explore
+    #[default]
+    ExtraMove,
+    /// Another explore only, from where the caravan now stands
+    ExtraExplore,
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum DesertRewardParseError {
+    #[error("Desert reward expected one of move, explore, received {0}")]
+    UnknownReward(String),
+}
+
+impl DesertReward {
+    pub const ALL: [DesertReward; 2] = [DesertReward::ExtraMove, DesertReward::ExtraExplore];
+
+    pub fn from_name(name: &str) -> Result<Self, DesertRewardParseError> {
+        match name {
+            "move" => Ok(Self::ExtraMove),
+            "explore" => Ok(Self::ExtraExplore),
+            _ => Err(DesertRewardParseError::UnknownReward(name.to_owned())),
+        }
+    }
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::ExtraMove => "move",
+            Self::ExtraExplore => "explore",
+        }
+    }
+}
+
+/// House rules for chaining desert tiles within a turn.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ExtraMoveRules {
+    /// Desert tiles in a row that grant a reward, or `None` for no limit.
+    /// The next one ends the turn like any other tile.
+    pub max_chain: Option<u8>,
+    pub reward: DesertReward,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct TurnState {
     active_player: PlayerName,
     phase: TurnPhase,
+    rules: ExtraMoveRules,
+    desert_chain: u8,
 }
 
 impl Default for TurnState {
     fn default() -> Self {
+        Self::new(ExtraMoveRules::default())
+    }
+}
+
+impl TurnState {
+    pub fn new(rules: ExtraMoveRules) -> Self {
         Self {
             active_player: PlayerName::default(),
             phase: TurnPhase::Move,
+            rules,
+            desert_chain: 0,
         }
     }
-}
-
-impl TurnState {
+    pub fn rules(&self) -> ExtraMoveRules {
+        self.rules
+    }
+    /// Desert tiles the active player has chained this turn.
+    pub fn desert_chain(&self) -> u8 {
+        self.desert_chain
+    }
     pub fn active_player(&self) -> PlayerName {
         self.active_player
     }
@@ -40,27 +99,64 @@ impl TurnState {
         self.phase = TurnPhase::Explore;
     }
     /// Explore -> Move: hand the turn to the next player. A desert tile keeps
-    /// the same player for another move. Only legal from the Explore phase.
-    pub fn advance_turn(&mut self, was_desert_tile: bool) {
+    /// the same player for the [`DesertReward`], until the chain reaches
+    /// [`ExtraMoveRules::max_chain`]. Returns whether the reward was granted.
+    /// Only legal from the Explore phase.
+    pub fn advance_turn(&mut self, was_desert_tile: bool) -> bool {
         debug_assert!(
             self.phase == TurnPhase::Explore,
             "advance_turn is only legal from the Explore phase, was {:?}",
             self.phase
         );
 
-        if !was_desert_tile {
-            self.active_player = self.active_player.cycle();
+        let chain_allowed = self
+            .rules
+            .max_chain
+            .is_none_or(|max_chain| self.desert_chain < max_chain);
+
+        if was_desert_tile && chain_allowed {
+            self.desert_chain += 1;
+            self.phase = match self.rules.reward {
+                DesertReward::ExtraMove => TurnPhase::Move,
+                DesertReward::ExtraExplore => TurnPhase::Explore,
+            };
+
+            return true;
         }
 
+        self.active_player = self.active_player.cycle();
+        self.desert_chain = 0;
         self.phase = TurnPhase::Move;
+
+        false
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use test_case::test_case;
+
+    use super::DesertReward;
+    use super::DesertRewardParseError;
+    use super::ExtraMoveRules;
     use super::TurnState;
     use crate::game::entities::player::PlayerName;
 
+    /// Explores `tiles` (true for desert) one after another, moving first
+    /// whenever the turn allows it.
+    fn explore(turn: &mut TurnState, tiles: &[bool]) -> Vec<bool> {
+        tiles
+            .iter()
+            .map(|was_desert_tile| {
+                if turn.can_move() {
+                    turn.advance_to_explore();
+                }
+
+                turn.advance_turn(*was_desert_tile)
+            })
+            .collect()
+    }
+
     #[test]
     fn starts_in_move_phase_for_the_first_player() {
         let turn = TurnState::default();
@@ -101,6 +197,75 @@ mod tests {
         assert_eq!(turn.active_player(), player);
     }
 
+    #[test]
+    fn desert_chains_are_unlimited_by_default() {
+        let mut turn = TurnState::default();
+
+        let granted = explore(&mut turn, &[true; 20]);
+
+        assert!(granted.iter().all(|granted| *granted));
+        assert_eq!(turn.desert_chain(), 20);
+        assert_eq!(turn.active_player(), PlayerName::White);
+    }
+
+    #[test_case(0, &[true], &[false] ; "no chain at all")]
+    #[test_case(2, &[true, true, true], &[true, true, false] ; "third desert ends the turn")]
+    #[test_case(2, &[true, false, true], &[true, false, true] ; "chain resets for the next player")]
+    fn the_desert_chain_is_capped(max_chain: u8, tiles: &[bool], expected: &[bool]) {
+        let mut turn = TurnState::new(ExtraMoveRules {
+            max_chain: Some(max_chain),
+            ..Default::default()
+        });
+
+        assert_eq!(explore(&mut turn, tiles), expected);
+    }
+
+    #[test]
+    fn the_chain_counter_resets_when_the_turn_passes() {
+        let mut turn = TurnState::new(ExtraMoveRules {
+            max_chain: Some(1),
+            ..Default::default()
+        });
+
+        explore(&mut turn, &[true]);
+        assert_eq!(turn.desert_chain(), 1);
+
+        explore(&mut turn, &[true]);
+        assert_eq!(turn.desert_chain(), 0);
+        assert_eq!(turn.active_player(), PlayerName::White.cycle());
+    }
+
+    #[test]
+    fn an_extra_explore_skips_the_move() {
+        let mut turn = TurnState::new(ExtraMoveRules {
+            reward: DesertReward::ExtraExplore,
+            ..Default::default()
+        });
+        let player = turn.active_player();
+
+        turn.advance_to_explore();
+        turn.advance_turn(true);
+
+        assert!(!turn.can_move());
+        assert_eq!(turn.active_player(), player);
+
+        turn.advance_turn(false);
+
+        assert!(turn.can_move());
+        assert_eq!(turn.active_player(), player.cycle());
+    }
+
+    #[test_case("move", Ok(DesertReward::ExtraMove))]
+    #[test_case("explore", Ok(DesertReward::ExtraExplore))]
+    #[test_case("turn", Err(DesertRewardParseError::UnknownReward("turn".to_owned())))]
+    fn parses_desert_rewards(name: &str, expected: Result<DesertReward, DesertRewardParseError>) {
+        assert_eq!(DesertReward::from_name(name), expected);
+
+        if let Ok(reward) = expected {
+            assert_eq!(reward.name(), name);
+        }
+    }
+
     #[test]
     #[should_panic]
     fn advancing_the_turn_from_the_move_phase_is_rejected() {
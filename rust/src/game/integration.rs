@@ -0,0 +1,442 @@
+use std::collections::VecDeque;
+
+use godot::builtin::Vector2;
+use godot::classes::INode;
+use godot::classes::Input;
+use godot::classes::InputEvent;
+use godot::classes::InputEventMouseButton;
+use godot::classes::InputEventMouseMotion;
+use godot::classes::Node;
+use godot::classes::Os;
+use godot::classes::PackedScene;
+use godot::classes::Time;
+use godot::global::MouseButton;
+use godot::global::MouseButtonMask;
+use godot::obj::Base;
+use godot::obj::Gd;
+use godot::obj::NewGd;
+use godot::obj::Singleton;
+use godot::obj::WithBaseField;
+use godot::obj::WithUserSignals;
+use godot::prelude::godot_api;
+use godot::prelude::load;
+use godot::prelude::GodotClass;
+
+use crate::game::components::tile_component::TileDeckComponent;
+use crate::game::entities::deck::TileDeck;
+use crate::game::entities::player::PlayerName;
+use crate::game::entities::BoardComponent;
+use crate::scenes::GameScene;
+use crate::util::debug_tools_enabled;
+use crate::util::test_report::TestOutcome;
+use crate::util::test_report::TestReport;
+use crate::util::test_report::TestResult;
+use crate::util::Logger;
+use crate::util::RootWindow;
+
+// Frames for a fresh running scene to place its cross and caravans
+const STARTUP_FRAMES: u32 = 5;
+// Frames for hovers, clicks and deferred calls to land
+const SETTLE_FRAMES: u32 = 3;
+// Tiles a deck can hold at most, so drawing it empty can't loop forever
+const MAX_DECK_SIZE: u8 = 64;
+// Center of the starting cross, whose arms span the board
+const CROSS_CENTER: (u8, u8) = (5, 5);
+
+/// Runs a test step against the running game's root, failing with a reason.
+type Action = fn(&Node) -> Result<(), String>;
+
+/// Something a test does on a frame.
+enum Step {
+    /// Lets this many frames pass
+    Wait(u32),
+    /// Moves the mouse over the center of the board square at these
+    /// coordinates, placed or not
+    Hover((u8, u8)),
+    /// Moves the mouse over the deck tiles are drawn from
+    HoverActiveDeck,
+    /// Presses the primary mouse button where the mouse is, and releases it
+    /// on the next frame
+    Click,
+    Release,
+    /// Calls into the game, or checks its state
+    Run(Action),
+}
+
+struct IntegrationTest {
+    name: &'static str,
+    steps: Vec<Step>,
+}
+
+fn expect_eq<T: PartialEq + std::fmt::Debug>(
+    what: &str,
+    actual: T,
+    expected: T,
+) -> Result<(), String> {
+    if actual == expected {
+        return Ok(());
+    }
+
+    Err(format!(
+        "Expected {what} to be {expected:?}, was {actual:?}"
+    ))
+}
+
+fn tests() -> Vec<IntegrationTest> {
+    vec![
+        IntegrationTest {
+            name: "cross_tiles_are_wired_into_the_board",
+            steps: vec![Step::Run(|node| {
+                let gd_board = BoardComponent::get(node);
+                let board = gd_board.bind();
+
+                expect_eq("the tile count", board.graph().tile_count(), 21)?;
+
+                for (coordinates, player) in board.rules().starting_positions {
+                    expect_eq(
+                        &format!("{player:?}'s position"),
+                        board.get_player_position(player),
+                        Some(coordinates),
+                    )?;
+                }
+
+                expect_eq(
+                    "the active player",
+                    board.active_player(),
+                    PlayerName::White,
+                )?;
+                expect_eq("the move phase", board.can_move(), true)
+            })],
+        },
+        IntegrationTest {
+            name: "clicking_a_destination_moves_the_caravan",
+            steps: vec![
+                Step::Hover((5, 8)),
+                Step::Wait(SETTLE_FRAMES),
+                Step::Click,
+                Step::Wait(SETTLE_FRAMES),
+                Step::Run(|node| {
+                    let gd_board = BoardComponent::get(node);
+                    let board = gd_board.bind();
+
+                    expect_eq(
+                        "White's position",
+                        board.get_player_position(PlayerName::White),
+                        Some((5, 8)),
+                    )?;
+                    expect_eq("the move phase", board.can_move(), false)
+                }),
+            ],
+        },
+        IntegrationTest {
+            name: "a_placed_desert_tile_grants_another_move",
+            steps: vec![
+                // Puts a tile without oases, a desert tile, on top of the deck
+                Step::Run(|node| {
+                    let deck = BoardComponent::get(node)
+                        .bind()
+                        .active_deck()
+                        .ok_or("Expected a deck to draw from")?;
+                    let gd_deck =
+                        TileDeck::find(node, deck).ok_or(format!("Couldn't find deck {deck}"))?;
+                    let mut gd_deck_component =
+                        gd_deck.get_node_as::<TileDeckComponent>("./TileDeckComponent");
+                    let mut deck_component = gd_deck_component.bind_mut();
+                    let nth = deck_component.tiles[deck_component.index as usize..]
+                        .iter()
+                        .position(|tile| tile.oasis_layout.is_empty())
+                        .ok_or("Expected a desert tile in the deck")?;
+
+                    deck_component.move_to_top(nth as u8);
+
+                    Ok(())
+                }),
+                Step::HoverActiveDeck,
+                Step::Wait(SETTLE_FRAMES),
+                Step::Click,
+                Step::Wait(SETTLE_FRAMES),
+                // West of White's starting tile, the top of the north arm
+                Step::Hover((4, 10)),
+                Step::Wait(SETTLE_FRAMES),
+                Step::Click,
+                Step::Wait(SETTLE_FRAMES),
+                Step::Run(|node| {
+                    let gd_board = BoardComponent::get(node);
+                    let board = gd_board.bind();
+
+                    expect_eq(
+                        "White's position",
+                        board.get_player_position(PlayerName::White),
+                        Some((4, 10)),
+                    )?;
+                    expect_eq(
+                        "the active player",
+                        board.active_player(),
+                        PlayerName::White,
+                    )?;
+                    expect_eq("the move phase", board.can_move(), true)
+                }),
+            ],
+        },
+        IntegrationTest {
+            name: "drawing_a_deck_empty_moves_on_to_the_next",
+            steps: vec![
+                Step::Run(|node| {
+                    let first = BoardComponent::get(node)
+                        .bind()
+                        .active_deck()
+                        .ok_or("Expected a deck to draw from")?;
+                    let mut gd_deck =
+                        TileDeck::find(node, first).ok_or(format!("Couldn't find deck {first}"))?;
+
+                    for _ in 0..MAX_DECK_SIZE {
+                        if gd_deck.bind_mut().draw_tile(0).is_err() {
+                            break;
+                        }
+                    }
+
+                    Ok(())
+                }),
+                Step::Wait(SETTLE_FRAMES),
+                Step::Run(|node| {
+                    let gd_board = BoardComponent::get(node);
+                    let board = gd_board.bind();
+                    let Some(&first) = board.rules().draw_order(board.deck_count()).first() else {
+                        return Err(String::from("Expected the tileset to have a deck"));
+                    };
+
+                    if board.active_deck() == Some(first) {
+                        return Err(format!("Expected deck {} to be exhausted", first + 1));
+                    }
+
+                    Ok(())
+                }),
+            ],
+        },
+    ]
+}
+
+/// Plays scripted tests against fresh copies of the running game, in
+/// headless Godot: `godot --headless res://tests/integration.tscn`. Prints
+/// libtest-style JSON events, or writes them to the path after `--report`,
+/// and quits with a failing exit code when a test fails.
+#[derive(GodotClass)]
+#[class(init, base=Node)]
+pub struct IntegrationTestRunner {
+    base: Base<Node>,
+    tests: VecDeque<IntegrationTest>,
+    steps: VecDeque<Step>,
+    current: Option<&'static str>,
+    started_msec: u64,
+    wait_frames: u32,
+    mouse_position: Vector2,
+    report: TestReport,
+}
+
+impl IntegrationTestRunner {
+    /// Replaces the running game with a fresh one and queues the next test.
+    /// Returns false once every test has run.
+    fn start_next_test(&mut self) -> bool {
+        let Some(test) = self.tests.pop_front() else {
+            return false;
+        };
+
+        let mut root = self.base().get_tree_root();
+
+        if let Some(mut running) = root.get_node_or_null("./Running") {
+            // Free it right away, so the new scene gets the `Running` name
+            root.remove_child(&running);
+            running.queue_free();
+        }
+
+        let running = load::<PackedScene>(GameScene::Running.to_path()).instantiate();
+
+        if let Some(running) = running {
+            root.add_child(&running);
+        }
+
+        self.current = Some(test.name);
+        self.steps = test.steps.into();
+        self.wait_frames = STARTUP_FRAMES;
+        self.started_msec = Time::singleton().get_ticks_msec();
+
+        true
+    }
+    fn finish_test(&mut self, outcome: TestOutcome) {
+        let Some(name) = self.current.take() else {
+            return;
+        };
+
+        let elapsed_msec = Time::singleton().get_ticks_msec() - self.started_msec;
+
+        if let TestOutcome::Failed(reason) = &outcome {
+            Logger::error(&format!("{name} failed: {reason}"));
+        }
+
+        self.steps.clear();
+        self.report.push(TestResult {
+            name: name.to_owned(),
+            outcome,
+            seconds: elapsed_msec as f64 / 1000.,
+        });
+    }
+    /// Where the center of the board square at `coordinates` is on screen.
+    /// Squares without a tile are found by stepping along the starting cross.
+    fn screen_position(&self, coordinates: (u8, u8)) -> Result<Vector2, String> {
+        let gd_board = BoardComponent::get(&self.base());
+        let board = gd_board.bind();
+        let tile_center = |coordinates: (u8, u8)| {
+            board
+                .get_tile_center(coordinates)
+                .ok_or(format!("No tile at {coordinates:?}"))
+        };
+
+        let center = match board.get_tile_center(coordinates) {
+            Some(center) => center,
+            None => {
+                let (x, y) = CROSS_CENTER;
+                let origin = tile_center(CROSS_CENTER)?;
+                let step_x = tile_center((x + 1, y))? - origin;
+                let step_y = tile_center((x, y + 1))? - origin;
+
+                origin
+                    + step_x * (coordinates.0 as f32 - x as f32)
+                    + step_y * (coordinates.1 as f32 - y as f32)
+            }
+        };
+        let viewport = self.base().get_viewport().ok_or("No viewport")?;
+
+        Ok(viewport.get_screen_transform() * (viewport.get_canvas_transform() * center))
+    }
+    /// Where the center of the deck tiles are drawn from is on screen.
+    fn active_deck_position(&self) -> Result<Vector2, String> {
+        let deck = BoardComponent::get(&self.base())
+            .bind()
+            .active_deck()
+            .ok_or("No deck to draw from")?;
+        let gd_deck = TileDeck::find(&self.base(), deck).ok_or(format!("No deck {deck}"))?;
+        let viewport = self.base().get_viewport().ok_or("No viewport")?;
+
+        Ok(viewport.get_screen_transform() * gd_deck.get_global_transform_with_canvas().origin)
+    }
+    fn send(&self, event: &Gd<InputEvent>) {
+        Input::singleton().parse_input_event(event);
+    }
+    fn move_mouse(&mut self, position: Vector2) {
+        let mut motion = InputEventMouseMotion::new_gd();
+
+        self.mouse_position = position;
+        motion.set_position(position);
+        motion.set_global_position(position);
+        self.send(&motion.upcast());
+    }
+    fn send_button(&self, pressed: bool) {
+        let mut button = InputEventMouseButton::new_gd();
+
+        button.set_button_index(MouseButton::LEFT);
+        button.set_pressed(pressed);
+        button.set_position(self.mouse_position);
+        button.set_global_position(self.mouse_position);
+
+        if pressed {
+            button.set_button_mask(MouseButtonMask::LEFT);
+        }
+
+        self.send(&button.upcast());
+    }
+    fn run_step(&mut self, step: Step) -> Result<(), String> {
+        match step {
+            Step::Wait(frames) => self.wait_frames = frames,
+            Step::Hover(coordinates) => {
+                let position = self.screen_position(coordinates)?;
+
+                self.move_mouse(position);
+            }
+            Step::HoverActiveDeck => {
+                let position = self.active_deck_position()?;
+
+                self.move_mouse(position);
+            }
+            Step::Click => {
+                self.send_button(true);
+
+                // Tiles look for the press in their `process`, so the
+                // release has to wait a frame
+                self.steps.push_front(Step::Release);
+                self.wait_frames = 1;
+            }
+            Step::Release => self.send_button(false),
+            Step::Run(action) => action(&self.base())?,
+        }
+
+        Ok(())
+    }
+    fn write_report(&self) {
+        let json = self.report.to_json_lines();
+        let args: Vec<String> = Os::singleton()
+            .get_cmdline_user_args()
+            .as_slice()
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect();
+        let path = args
+            .iter()
+            .position(|arg| arg == "--report")
+            .and_then(|idx| args.get(idx + 1));
+
+        match path {
+            Some(path) => {
+                if let Err(error) = std::fs::write(path, format!("{json}\n")) {
+                    Logger::error(&format!("Couldn't write {path}: {error}"));
+                }
+            }
+            None => println!("{json}"),
+        }
+
+        println!("{}", self.report.summary());
+    }
+}
+
+#[godot_api]
+impl INode for IntegrationTestRunner {
+    fn ready(&mut self) {
+        // A development tool like the debug console, so release builds leave
+        // it out
+        if !debug_tools_enabled() {
+            Logger::error("Integration tests only run in debug builds, or with DEBUG=true");
+
+            self.base().get_tree().quit_ex().exit_code(1).done();
+            return self.base_mut().set_process(false);
+        }
+
+        self.tests = tests().into();
+    }
+    fn process(&mut self, _dt: f64) {
+        if self.wait_frames > 0 {
+            self.wait_frames -= 1;
+            return;
+        }
+
+        if self.current.is_none() && !self.start_next_test() {
+            self.write_report();
+
+            let exit_code = if self.report.failed() == 0 { 0 } else { 1 };
+
+            self.base().get_tree().quit_ex().exit_code(exit_code).done();
+            self.base_mut().set_process(false);
+            return;
+        }
+
+        while self.wait_frames == 0 {
+            let Some(step) = self.steps.pop_front() else {
+                self.finish_test(TestOutcome::Passed);
+                return;
+            };
+
+            if let Err(reason) = self.run_step(step) {
+                self.finish_test(TestOutcome::Failed(reason));
+                return;
+            }
+        }
+    }
+}
@@ -7,13 +7,15 @@ const MENU_SCREENS: &str = "res://menus/";
 #[derive(Clone, Copy, Debug)]
 pub enum GameScene {
     MainMenu,
+    Lobby,
     Running,
     Settings,
+    Error,
 }
 
 #[derive(Error, Debug)]
 pub enum GameSceneParseError<'a> {
-    #[error("GameScene expected one of running, settings, main_menu, received {0}")]
+    #[error("GameScene expected one of lobby, running, settings, main_menu, error, received {0}")]
     ParseGameScene(&'a str),
 }
 
@@ -22,9 +24,11 @@ impl<'a> TryFrom<&'a str> for GameScene {
 
     fn try_from(value: &'a str) -> Result<Self, GameSceneParseError<'a>> {
         match value {
+            "lobby" => Ok(GameScene::Lobby),
             "running" => Ok(GameScene::Running),
             "settings" => Ok(GameScene::Settings),
             "main_menu" => Ok(GameScene::MainMenu),
+            "error" => Ok(GameScene::Error),
             _ => Err(GameSceneParseError::ParseGameScene(value)),
         }
     }
@@ -33,9 +37,11 @@ impl<'a> TryFrom<&'a str> for GameScene {
 impl GameScene {
     pub fn to_path(&self) -> &'static str {
         match &self {
+            GameScene::Lobby => concatcp!(MENU_SCREENS, "lobby.tscn"),
             GameScene::Running => concatcp!(GAME_SCREENS, "running/running.tscn"),
             GameScene::Settings => concatcp!(MENU_SCREENS, "settings.tscn"),
             GameScene::MainMenu => concatcp!(MENU_SCREENS, "main_menu.tscn"),
+            GameScene::Error => concatcp!(MENU_SCREENS, "error.tscn"),
         }
     }
 }
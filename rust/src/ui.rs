@@ -1,13 +1,46 @@
 use godot::classes::Area2D;
 use godot::classes::Button;
+use godot::classes::CheckBox;
 use godot::classes::CollisionShape2D;
+use godot::classes::ColorPickerButton;
+use godot::classes::Control;
+use godot::classes::HSlider;
 use godot::classes::IArea2D;
 use godot::classes::IButton;
+use godot::classes::IControl;
 use godot::classes::Label;
+use godot::classes::LineEdit;
 use godot::classes::MarginContainer;
+use godot::classes::OptionButton;
+use godot::classes::SpinBox;
+use godot::global::randi_range;
 use godot::prelude::*;
 
+use crate::game::entities::movement::InteractionRule;
+use crate::game::entities::player::PlayerName;
+use crate::game::entities::turn::DesertReward;
 use crate::scenes::GameScene;
+use crate::util::errors::GameError;
+use crate::util::loader::SettingsManager;
+use crate::util::loader::TomlLoader;
+use crate::util::mods::ModPack;
+use crate::util::palette::Rgb;
+use crate::util::rules::RulesConfig;
+use crate::util::rules::MAX_CHAIN_RANGE;
+use crate::util::rules::OASIS_CROSSINGS_RANGE;
+use crate::util::rules::WATER_TOLL_RANGE;
+use crate::util::settings::ColorblindPalette;
+use crate::util::settings::SeedBehavior;
+use crate::util::settings::Settings;
+use crate::util::settings::WindowMode;
+use crate::util::settings::MAX_SEED;
+use crate::util::tileset::generate::generate;
+use crate::util::tileset::generate::keep_deck_metadata;
+use crate::util::tileset::generate::GeneratorTargets;
+use crate::util::tileset::stats::TilesetStats;
+use crate::util::ErrorReporter;
+use crate::util::LogLevel;
+use crate::util::Logger;
 
 #[derive(GodotClass)]
 #[class(init, base=Button)]
@@ -25,9 +58,14 @@ impl IButton for SceneChangeButton {
 
         let on_click_string = self.scene_on_click.to_string();
 
-        let target_scene: GameScene = on_click_string.as_str().try_into().expect(
-            "Provided invalid scene name as `scene_on_click` to `SceneChangeButton.pressed()`",
-        );
+        let target_scene = match GameScene::try_from(on_click_string.as_str()) {
+            Ok(target_scene) => target_scene,
+            Err(error) => {
+                let error = GameError::InvalidScene(error.to_string());
+
+                return ErrorReporter::report(&self.base(), error);
+            }
+        };
 
         let target_scene_path = target_scene.to_path();
 
@@ -35,6 +73,29 @@ impl IButton for SceneChangeButton {
     }
 }
 
+/// Lists the problems reported through [`ErrorReporter`] that ended the
+/// game, above a button back to the main menu.
+#[derive(GodotClass)]
+#[class(init, base=Control)]
+pub struct ErrorScreen {
+    base: Base<Control>,
+}
+
+#[godot_api]
+impl IControl for ErrorScreen {
+    fn ready(&mut self) {
+        let mut messages = ErrorReporter::take();
+
+        if messages.is_empty() {
+            messages.push(String::from("No details were reported, see the log"));
+        }
+
+        self.base()
+            .get_node_as::<Label>("./MarginContainer/VBoxContainer/Errors")
+            .set_text(&messages.join("\n"));
+    }
+}
+
 #[derive(GodotClass)]
 #[class(init, base=Button)]
 pub struct OnClickButton {
@@ -94,6 +155,14 @@ impl LabelTooltip {
         self.base()
             .get_node_as("./Panel/Outline/MarginContainer/VBoxContainer/Highlight")
     }
+    /// Replaces the highlighted line below the label, hiding it when empty.
+    pub fn set_value(&mut self, value: &str) {
+        self.value = GString::from(value);
+
+        let mut label = self.get_tooltip_value();
+        label.set_visible(!value.is_empty());
+        label.set_text(value);
+    }
 }
 
 #[godot_api]
@@ -130,5 +199,542 @@ impl IArea2D for LabelTooltip {
     }
 }
 
+/// Lists `labels` in `option_button`, with the one at `selected` picked.
+fn fill_option_button(
+    mut option_button: Gd<OptionButton>,
+    labels: &[&str],
+    selected: usize,
+) -> Gd<OptionButton> {
+    option_button.clear();
+
+    for label in labels {
+        option_button.add_item(*label);
+    }
+
+    option_button.select(selected as i32);
+
+    option_button
+}
+
+const SETTINGS_GRID: &str = "./MarginContainer/VBoxContainer/Grid";
+
+// Children of the `CustomColors` row, in `PlayerName` order
+const CUSTOM_COLOR_PICKERS: [&str; 4] = ["White", "Orange", "Red", "Blue"];
+
+type SliderSetting = (&'static str, f64, fn(&mut Settings, f64));
+
+#[derive(GodotClass)]
+#[class(init, base=Control)]
+pub struct SettingsMenu {
+    base: Base<Control>,
+}
+
+impl SettingsMenu {
+    fn get_slider(&self, name: &str) -> Gd<HSlider> {
+        self.base().get_node_as(&format!("{SETTINGS_GRID}/{name}"))
+    }
+    fn get_option_button(&self, name: &str) -> Gd<OptionButton> {
+        self.base().get_node_as(&format!("{SETTINGS_GRID}/{name}"))
+    }
+    fn get_color_picker(&self, name: &str) -> Gd<ColorPickerButton> {
+        self.base()
+            .get_node_as(&format!("{SETTINGS_GRID}/CustomColors/{name}"))
+    }
+    fn get_show_markers(&self) -> Gd<CheckBox> {
+        self.base()
+            .get_node_as(&format!("{SETTINGS_GRID}/ShowMarkers"))
+    }
+    fn get_seed_input(&self) -> Gd<LineEdit> {
+        self.base().get_node_as(&format!("{SETTINGS_GRID}/Seed"))
+    }
+    fn update_settings(&self, change: impl FnOnce(&mut Settings)) {
+        SettingsManager::get(&self.base()).bind_mut().update(change);
+    }
+    fn preview_settings(&self, change: impl FnOnce(&mut Settings)) {
+        SettingsManager::get(&self.base())
+            .bind_mut()
+            .preview(change);
+    }
+    fn save_settings(&self) {
+        SettingsManager::get(&self.base()).bind_mut().save_changes();
+    }
+    fn submit_seed(&mut self, text: GString) {
+        let text = text.to_string();
+
+        let seed = match text.trim() {
+            "" | "random" => SeedBehavior::Random,
+            seed => match seed.parse() {
+                Ok(seed) if seed <= MAX_SEED => SeedBehavior::Fixed(seed),
+                _ => {
+                    Logger::warn(&format!(
+                        "Expected seed to be a number up to {MAX_SEED} or random, got {text}"
+                    ));
+                    return;
+                }
+            },
+        };
+
+        self.update_settings(|s| s.seed = seed);
+    }
+}
+
+#[godot_api]
+impl IControl for SettingsMenu {
+    fn ready(&mut self) {
+        let settings = SettingsManager::get(&self.base()).bind().settings().clone();
+        let gd_self = self.to_gd();
+
+        let sliders: [SliderSetting; 4] = [
+            ("MasterVolume", settings.audio.master_volume, |s, v| {
+                s.audio.master_volume = v
+            }),
+            ("MusicVolume", settings.audio.music_volume, |s, v| {
+                s.audio.music_volume = v
+            }),
+            ("EffectsVolume", settings.audio.effects_volume, |s, v| {
+                s.audio.effects_volume = v
+            }),
+            ("UiScale", settings.ui_scale, |s, v| s.ui_scale = v),
+        ];
+
+        for (name, value, setter) in sliders {
+            let mut slider = self.get_slider(name);
+
+            slider.set_value_no_signal(value);
+            slider
+                .signals()
+                .value_changed()
+                .connect_other(&gd_self, move |this: &mut Self, value: f64| {
+                    this.preview_settings(|s| setter(s, value))
+                });
+            // Saving on every step of a drag would write the file dozens of
+            // times a second
+            slider
+                .signals()
+                .drag_ended()
+                .connect_other(&gd_self, |this: &mut Self, _changed: bool| {
+                    this.save_settings()
+                });
+        }
+
+        let window_modes = WindowMode::ALL.map(<&str>::from);
+        let selected = WindowMode::ALL
+            .iter()
+            .position(|m| *m == settings.window_mode)
+            .unwrap_or(0);
+
+        fill_option_button(
+            self.get_option_button("WindowMode"),
+            &window_modes,
+            selected,
+        )
+        .signals()
+        .item_selected()
+        .connect_other(&gd_self, |this: &mut Self, index: i64| {
+            this.update_settings(|s| s.window_mode = WindowMode::ALL[index as usize])
+        });
+
+        let palettes = ColorblindPalette::ALL.map(<&str>::from);
+        let selected = ColorblindPalette::ALL
+            .iter()
+            .position(|p| *p == settings.colorblind_palette)
+            .unwrap_or(0);
+
+        fill_option_button(
+            self.get_option_button("ColorblindPalette"),
+            &palettes,
+            selected,
+        )
+        .signals()
+        .item_selected()
+        .connect_other(&gd_self, |this: &mut Self, index: i64| {
+            this.update_settings(|s| s.colorblind_palette = ColorblindPalette::ALL[index as usize])
+        });
+
+        for (idx, name) in CUSTOM_COLOR_PICKERS.iter().enumerate() {
+            let Rgb(r, g, b) = settings.custom_colors[idx];
+            let mut picker = self.get_color_picker(name);
+
+            picker.set_pick_color(Color::from_rgba8(r, g, b, 255));
+            picker.signals().color_changed().connect_other(
+                &gd_self,
+                move |this: &mut Self, color: Color| {
+                    let player = PlayerName::from(idx as u8);
+                    let rgb = Rgb(color.r8(), color.g8(), color.b8());
+
+                    this.update_settings(|s| s.custom_colors[player as usize] = rgb)
+                },
+            );
+        }
+
+        let mut show_markers = self.get_show_markers();
+
+        show_markers.set_pressed_no_signal(settings.show_markers);
+        show_markers
+            .signals()
+            .toggled()
+            .connect_other(&gd_self, |this: &mut Self, pressed: bool| {
+                this.update_settings(|s| s.show_markers = pressed)
+            });
+
+        // The first entry leaves the threshold to the build and `DEBUG` flag
+        let mut log_levels = vec!["default"];
+        log_levels.extend(LogLevel::LEVELS.iter().map(<&str>::from));
+        let selected = settings
+            .log_level
+            .and_then(|level| LogLevel::LEVELS.iter().position(|l| *l == level))
+            .map_or(0, |idx| idx + 1);
+
+        fill_option_button(self.get_option_button("LogLevel"), &log_levels, selected)
+            .signals()
+            .item_selected()
+            .connect_other(&gd_self, |this: &mut Self, index: i64| {
+                let level = (index as usize)
+                    .checked_sub(1)
+                    .map(|idx| LogLevel::LEVELS[idx]);
+
+                this.update_settings(|s| s.log_level = level)
+            });
+
+        let mut seed_input = self.get_seed_input();
+
+        if let SeedBehavior::Fixed(seed) = settings.seed {
+            seed_input.set_text(&seed.to_string());
+        }
+
+        seed_input
+            .signals()
+            .text_submitted()
+            .connect_other(&gd_self, |this: &mut Self, text: GString| {
+                this.submit_seed(text)
+            });
+    }
+    fn exit_tree(&mut self) {
+        // Sliders moved with the keyboard never end a drag
+        self.save_settings();
+    }
+}
+
+const LOBBY_GRID: &str = "./MarginContainer/VBoxContainer/Grid";
+const TILESET_OPTIONS: [&str; 2] = ["Standard", "Random"];
+const TILESET_SEED_RANGE: (i64, i64) = (0, 999_999);
+const NO_CONTENT_PACK: &str = "None";
+
+/// Rule overrides for the next game. Starts from `config/rules.toml`, and
+/// every change is handed to the `TomlLoader` right away, so the game started
+/// from here is played with what's shown.
+#[derive(GodotClass)]
+#[class(init, base=Control)]
+pub struct LobbyMenu {
+    base: Base<Control>,
+    rules: RulesConfig,
+    // Kept apart from the interaction rule, so switching away from
+    // `water_toll` and back doesn't forget it
+    #[init(val = 1)]
+    water_toll: u32,
+    random_tileset: bool,
+    tileset_seed: u64,
+    // The playable packs in `user://mods/`, listed after "None"
+    packs: Vec<ModPack>,
+    pack: Option<usize>,
+}
+
+impl LobbyMenu {
+    fn get_spin_box(&self, name: &str) -> Gd<SpinBox> {
+        self.base().get_node_as(&format!("{LOBBY_GRID}/{name}"))
+    }
+    fn get_option_button(&self, name: &str) -> Gd<OptionButton> {
+        self.base().get_node_as(&format!("{LOBBY_GRID}/{name}"))
+    }
+    fn get_pack_error(&self) -> Gd<Label> {
+        self.base()
+            .get_node_as("./MarginContainer/VBoxContainer/ContentPackError")
+    }
+    fn selected_pack(&self) -> Option<&ModPack> {
+        self.pack.and_then(|pack| self.packs.get(pack))
+    }
+    fn interaction_index(rules: &RulesConfig) -> usize {
+        InteractionRule::NAMES
+            .iter()
+            .position(|name| *name == rules.interaction.name())
+            .unwrap_or(0)
+    }
+    fn reward_index(rules: &RulesConfig) -> usize {
+        DesertReward::ALL
+            .iter()
+            .position(|reward| *reward == rules.extra_moves.reward)
+            .unwrap_or(0)
+    }
+    /// Shows `self.rules` without announcing the changes.
+    fn show_rules(&self) {
+        let rules = &self.rules;
+
+        self.get_spin_box("OasisCrossings")
+            .set_value_no_signal(rules.oasis_crossings as f64);
+        self.get_option_button("Interaction")
+            .select(Self::interaction_index(rules) as i32);
+        self.get_spin_box("WaterToll")
+            .set_value_no_signal(self.water_toll as f64);
+        self.get_spin_box("MaxDesertChain")
+            .set_value_no_signal(rules.extra_moves.max_chain.unwrap_or(0) as f64);
+        self.get_option_button("DesertReward")
+            .select(Self::reward_index(rules) as i32);
+    }
+    /// Plays the selected content pack's tileset, rules and treasures, or
+    /// goes back to the game's own configs. Rules the pack leaves out start
+    /// from `config/rules.toml`.
+    fn select_pack(&mut self, pack: Option<usize>) {
+        self.pack = pack;
+
+        let pack = self.selected_pack().cloned();
+
+        if let Some(pack) = &pack {
+            Logger::info(&format!("Playing content pack {}", pack.directory));
+        }
+
+        self.rules = pack
+            .as_ref()
+            .and_then(|pack| pack.rules.clone())
+            .unwrap_or_else(|| TomlLoader::file_rules(&self.base()));
+
+        if let InteractionRule::WaterToll(toll) = self.rules.interaction {
+            self.water_toll = toll;
+        }
+
+        self.show_rules();
+        self.update_rules(|_| {});
+
+        TomlLoader::set_treasures_override(&self.base(), pack.and_then(|pack| pack.treasures));
+
+        self.update_tileset(|_| {});
+    }
+    /// Lists the packs in `user://mods/`. Invalid packs are listed but can't
+    /// be picked, and what's wrong with them is shown below the options.
+    fn fill_packs(&mut self) -> Gd<OptionButton> {
+        let mut errors = vec![];
+
+        self.packs.clear();
+
+        for result in TomlLoader::mods(&self.base()) {
+            match result {
+                Ok(pack) => self.packs.push(pack),
+                Err(error) => {
+                    Logger::warn(&format!("Invalid content pack {error}"));
+                    errors.push(error);
+                }
+            }
+        }
+
+        let mut labels = vec![String::from(NO_CONTENT_PACK)];
+
+        labels.extend(self.packs.iter().map(|pack| pack.manifest.label()));
+        labels.extend(
+            errors
+                .iter()
+                .map(|error| format!("{} (invalid)", error.directory)),
+        );
+
+        let labels: Vec<&str> = labels.iter().map(String::as_str).collect();
+        let mut option_button =
+            fill_option_button(self.get_option_button("ContentPack"), &labels, 0);
+
+        for (idx, pack) in self.packs.iter().enumerate() {
+            let manifest = &pack.manifest;
+            let tooltip = [
+                manifest.description.clone(),
+                manifest.author.clone().map(|author| format!("by {author}")),
+            ]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<String>>()
+            .join("\n");
+
+            option_button.set_item_tooltip(idx as i32 + 1, &tooltip);
+        }
+
+        for idx in 0..errors.len() {
+            option_button.set_item_disabled((1 + self.packs.len() + idx) as i32, true);
+        }
+
+        let messages: Vec<String> = errors.iter().map(ToString::to_string).collect();
+        let mut gd_error = self.get_pack_error();
+
+        gd_error.set_text(&messages.join("\n"));
+        gd_error.set_visible(!messages.is_empty());
+
+        option_button
+    }
+    fn update_rules(&mut self, change: impl FnOnce(&mut Self)) {
+        change(self);
+
+        if let InteractionRule::WaterToll(_) = self.rules.interaction {
+            self.rules.interaction = InteractionRule::WaterToll(self.water_toll);
+        }
+
+        Logger::debug(&format!("Lobby rules: {:?}", self.rules));
+
+        TomlLoader::set_rules_override(&self.base(), Some(self.rules.clone()));
+    }
+    /// Generates the random tileset from its seed, balanced like the content
+    /// pack's or `config/tileset.toml` and with its deck names, or goes back to
+    /// the pack's or the file's own tileset.
+    fn update_tileset(&mut self, change: impl FnOnce(&mut Self)) {
+        change(self);
+
+        let pack_tileset = self.selected_pack().and_then(|pack| pack.tileset.clone());
+
+        if !self.random_tileset {
+            return TomlLoader::set_tileset_override(&self.base(), pack_tileset, None);
+        }
+
+        let template = match &pack_tileset {
+            Some(tileset) => Ok(tileset.clone()),
+            None => TomlLoader::file_tileset(&self.base()),
+        };
+
+        let tileset = template
+            .map_err(|error| error.to_string())
+            .and_then(|template| {
+                let targets = GeneratorTargets::from(&TilesetStats::from(&template));
+                let mut tileset =
+                    generate(self.tileset_seed, &targets).map_err(|error| error.to_string())?;
+
+                keep_deck_metadata(&mut tileset, &template);
+
+                Ok(tileset)
+            });
+
+        match tileset {
+            Ok(tileset) => {
+                Logger::info(&format!("Random tileset from seed {}", self.tileset_seed));
+                TomlLoader::set_tileset_override(
+                    &self.base(),
+                    Some(tileset),
+                    Some(self.tileset_seed),
+                );
+            }
+            Err(error) => {
+                Logger::error(&format!("Couldn't generate a random tileset: {error}"));
+                TomlLoader::set_tileset_override(&self.base(), pack_tileset, None);
+            }
+        }
+    }
+    fn fill_spin_box(&self, name: &str, range: (i64, i64), value: f64) -> Gd<SpinBox> {
+        let mut spin_box = self.get_spin_box(name);
+
+        spin_box.set_min(range.0 as f64);
+        spin_box.set_max(range.1 as f64);
+        spin_box.set_value_no_signal(value);
+
+        spin_box
+    }
+}
+
+#[godot_api]
+impl IControl for LobbyMenu {
+    fn ready(&mut self) {
+        self.rules = TomlLoader::file_rules(&self.base());
+        if let InteractionRule::WaterToll(toll) = self.rules.interaction {
+            self.water_toll = toll;
+        }
+        self.update_rules(|_| {});
+
+        let rules = self.rules.clone();
+        let gd_self = self.to_gd();
+
+        self.fill_spin_box(
+            "OasisCrossings",
+            OASIS_CROSSINGS_RANGE,
+            rules.oasis_crossings as f64,
+        )
+        .signals()
+        .value_changed()
+        .connect_other(&gd_self, |this: &mut Self, value: f64| {
+            this.update_rules(|this| this.rules.oasis_crossings = value as u8)
+        });
+
+        fill_option_button(
+            self.get_option_button("Interaction"),
+            &InteractionRule::NAMES,
+            Self::interaction_index(&rules),
+        )
+        .signals()
+        .item_selected()
+        .connect_other(&gd_self, |this: &mut Self, index: i64| {
+            this.update_rules(|this| {
+                let name = InteractionRule::NAMES[index as usize];
+
+                this.rules.interaction =
+                    InteractionRule::from_name(name, this.water_toll).unwrap_or_default();
+            })
+        });
+
+        self.fill_spin_box("WaterToll", WATER_TOLL_RANGE, self.water_toll as f64)
+            .signals()
+            .value_changed()
+            .connect_other(&gd_self, |this: &mut Self, value: f64| {
+                this.update_rules(|this| this.water_toll = value as u32)
+            });
+
+        self.fill_spin_box(
+            "MaxDesertChain",
+            MAX_CHAIN_RANGE,
+            rules.extra_moves.max_chain.unwrap_or(0) as f64,
+        )
+        .signals()
+        .value_changed()
+        .connect_other(&gd_self, |this: &mut Self, value: f64| {
+            this.update_rules(|this| {
+                this.rules.extra_moves.max_chain = (value >= 1.).then_some(value as u8)
+            })
+        });
+
+        let rewards = DesertReward::ALL.map(|reward| reward.name());
+        fill_option_button(
+            self.get_option_button("DesertReward"),
+            &rewards,
+            Self::reward_index(&rules),
+        )
+        .signals()
+        .item_selected()
+        .connect_other(&gd_self, |this: &mut Self, index: i64| {
+            this.update_rules(|this| {
+                this.rules.extra_moves.reward = DesertReward::ALL[index as usize]
+            })
+        });
+
+        // A fixed game seed also fixes the random tileset
+        let seed = match SettingsManager::get(&self.base()).bind().settings().seed {
+            SeedBehavior::Fixed(seed) => seed % (TILESET_SEED_RANGE.1 as u64 + 1),
+            SeedBehavior::Random => randi_range(TILESET_SEED_RANGE.0, TILESET_SEED_RANGE.1) as u64,
+        };
+        self.update_tileset(|this| {
+            this.random_tileset = false;
+            this.tileset_seed = seed;
+        });
+
+        fill_option_button(self.get_option_button("Tileset"), &TILESET_OPTIONS, 0)
+            .signals()
+            .item_selected()
+            .connect_other(&gd_self, |this: &mut Self, index: i64| {
+                this.update_tileset(|this| this.random_tileset = index == 1)
+            });
+
+        self.fill_spin_box("TilesetSeed", TILESET_SEED_RANGE, seed as f64)
+            .signals()
+            .value_changed()
+            .connect_other(&gd_self, |this: &mut Self, value: f64| {
+                this.update_tileset(|this| this.tileset_seed = value as u64)
+            });
+
+        self.fill_packs()
+            .signals()
+            .item_selected()
+            .connect_other(&gd_self, |this: &mut Self, index: i64| {
+                this.select_pack(usize::try_from(index - 1).ok())
+            });
+    }
+}
+
 #[cfg(test)]
 mod tests {}
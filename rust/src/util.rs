@@ -1,3 +1,7 @@
+use std::sync::atomic::AtomicU8;
+use std::sync::atomic::Ordering;
+use std::sync::Mutex;
+
 use godot::classes::INode;
 use godot::classes::Node;
 use godot::classes::Os;
@@ -5,16 +9,32 @@ use godot::classes::Window;
 use godot::global::godot_error;
 use godot::global::godot_print;
 use godot::global::godot_warn;
+use godot::meta::ToGodot;
 use godot::obj::Base;
 use godot::obj::Gd;
 use godot::obj::Singleton;
 use godot::prelude::godot_api;
 use godot::prelude::GodotClass;
 
+use crate::scenes::GameScene;
+use crate::util::errors::ErrorLog;
+use crate::util::errors::GameError;
+
+pub mod config;
+pub mod console;
 pub mod debug;
+pub mod errors;
 pub mod flags;
 pub mod input;
 pub mod loader;
+pub mod mods;
+pub mod palette;
+pub mod rules;
+pub mod scenario;
+pub mod settings;
+pub mod test_report;
+pub mod tileset;
+pub mod treasures;
 
 pub trait RootWindow
 where
@@ -36,33 +56,87 @@ impl RootWindow for Node {
     }
 }
 
-#[derive(PartialEq, PartialOrd)]
-enum LogLevel {
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub enum LogLevel {
     Debug,
     Info,
     Warn,
     Error,
 }
 
+impl LogLevel {
+    pub const LEVELS: [LogLevel; 4] = [
+        LogLevel::Debug,
+        LogLevel::Info,
+        LogLevel::Warn,
+        LogLevel::Error,
+    ];
+}
+
+impl TryFrom<&str> for LogLevel {
+    type Error = &'static str;
+
+    fn try_from(value: &str) -> Result<Self, &'static str> {
+        match value {
+            "debug" => Ok(LogLevel::Debug),
+            "info" => Ok(LogLevel::Info),
+            "warn" => Ok(LogLevel::Warn),
+            "error" => Ok(LogLevel::Error),
+            _ => Err("Expected log level to be one of debug, info, warn, error"),
+        }
+    }
+}
+
+impl From<&LogLevel> for &'static str {
+    fn from(value: &LogLevel) -> Self {
+        match value {
+            LogLevel::Debug => "debug",
+            LogLevel::Info => "info",
+            LogLevel::Warn => "warn",
+            LogLevel::Error => "error",
+        }
+    }
+}
+
+// Set from the `log_level` setting. 0 means "no override", any other value is
+// an index into `LogLevel::LEVELS` shifted by one.
+static THRESHOLD_OVERRIDE: AtomicU8 = AtomicU8::new(0);
+
 #[derive(GodotClass, Debug)]
 #[class(base=Node)]
 struct Env {
     base: Base<Node>,
 }
 
+/// Whether the `DEBUG` environment flag is set.
+fn debug_flag() -> bool {
+    Os::singleton()
+        .get_environment("DEBUG")
+        .to_string()
+        .parse()
+        .unwrap_or(false)
+}
+
+/// Whether development tools like the debug console are available: in debug
+/// builds, or when the `DEBUG` environment flag is set.
+pub fn debug_tools_enabled() -> bool {
+    cfg!(debug_assertions) || debug_flag()
+}
+
 pub struct Logger;
 
 impl Logger {
-    /// Lowest severity that prints. `Error` in release, `Info` in debug
-    /// builds, or `Debug` when the `DEBUG` environment flag is set.
+    /// Lowest severity that prints. The `log_level` setting wins when it is
+    /// set. Otherwise `Error` in release, `Info` in debug builds, or `Debug`
+    /// when the `DEBUG` environment flag is set.
     fn threshold() -> LogLevel {
-        let debug_flag = Os::singleton()
-            .get_environment("DEBUG")
-            .to_string()
-            .parse()
-            .unwrap_or(false);
+        let level_override = THRESHOLD_OVERRIDE.load(Ordering::Relaxed) as usize;
+
+        if level_override > 0 {
+            return LogLevel::LEVELS[level_override - 1];
+        }
 
-        if debug_flag {
+        if debug_flag() {
             LogLevel::Debug
         } else if cfg!(debug_assertions) {
             LogLevel::Info
@@ -70,9 +144,22 @@ impl Logger {
             LogLevel::Error
         }
     }
+    /// Replaces the build- and environment-derived threshold. `None` restores
+    /// the default behavior.
+    pub fn set_threshold_override(level: Option<LogLevel>) {
+        let level_override = level
+            .and_then(|level| LogLevel::LEVELS.iter().position(|l| *l == level))
+            .map_or(0, |idx| idx as u8 + 1);
+
+        THRESHOLD_OVERRIDE.store(level_override, Ordering::Relaxed);
+    }
+    /// Whether messages of `level` print, to skip building costly ones.
+    pub fn is_enabled(level: LogLevel) -> bool {
+        level >= Self::threshold()
+    }
     /// Use `format!()` macro to construct a formatted `output` parameter
     fn print(level: LogLevel, output: &str) {
-        if level < Self::threshold() {
+        if !Self::is_enabled(level) {
             return;
         }
 
@@ -97,6 +184,41 @@ impl Logger {
     }
 }
 
+// Problems waiting for the error screen
+static REPORTED: Mutex<ErrorLog> = Mutex::new(ErrorLog::new());
+
+pub struct ErrorReporter;
+
+impl ErrorReporter {
+    /// Logs `error` and replaces the current scene with the error screen,
+    /// which lists every problem reported until it opens. Callers give up
+    /// on what they were setting up rather than panic.
+    pub fn report(node: &Node, error: GameError) {
+        let message = error.to_string();
+
+        Logger::error(&format!("{message} ({error:?})"));
+
+        let is_first = REPORTED
+            .lock()
+            .map(|mut reported| reported.push(message))
+            .unwrap_or(false);
+
+        if is_first {
+            node.get_tree().call_deferred(
+                "change_scene_to_file",
+                &[GameScene::Error.to_path().to_variant()],
+            );
+        }
+    }
+    /// The messages reported since the last call, for the error screen.
+    pub fn take() -> Vec<String> {
+        REPORTED
+            .lock()
+            .map(|mut reported| reported.take())
+            .unwrap_or_default()
+    }
+}
+
 #[godot_api]
 impl INode for Env {
     fn init(base: Base<Node>) -> Self {
@@ -0,0 +1,79 @@
+use toml::Table;
+use toml::Value;
+
+pub struct ConfigBool(bool);
+
+impl ConfigBool {
+    fn from_key_in_table(key: &str, table: &Table) -> Option<Self> {
+        table.get(key).and_then(|v| ConfigBool::try_from(v).ok())
+    }
+    pub(crate) fn from_key_in_table_as_bool(key: &str, table: &Table) -> Option<bool> {
+        ConfigBool::from_key_in_table(key, table).map(|b| b.into())
+    }
+}
+
+impl From<ConfigBool> for bool {
+    fn from(value: ConfigBool) -> Self {
+        value.0
+    }
+}
+
+impl TryFrom<&Value> for ConfigBool {
+    type Error = &'static str;
+
+    fn try_from(value: &Value) -> Result<Self, &'static str> {
+        match value {
+            Value::Boolean(value) => Ok(ConfigBool(*value)),
+            _ => Err("Couldn't parse value as boolean"),
+        }
+    }
+}
+
+pub struct ConfigString(String);
+
+impl ConfigString {
+    fn from_key_in_table(key: &str, table: &Table) -> Option<Self> {
+        table.get(key).and_then(|v| ConfigString::try_from(v).ok())
+    }
+    pub(crate) fn from_key_in_table_as_string(key: &str, table: &Table) -> Option<String> {
+        ConfigString::from_key_in_table(key, table).map(|s| s.into())
+    }
+}
+
+impl From<ConfigString> for String {
+    fn from(value: ConfigString) -> Self {
+        value.0
+    }
+}
+
+impl TryFrom<&Value> for ConfigString {
+    type Error = &'static str;
+
+    fn try_from(value: &Value) -> Result<Self, &'static str> {
+        match value {
+            Value::String(value) => Ok(ConfigString(value.clone())),
+            _ => Err("Couldn't parse value as string"),
+        }
+    }
+}
+
+pub struct ConfigFloat(f64);
+
+impl From<ConfigFloat> for f64 {
+    fn from(value: ConfigFloat) -> Self {
+        value.0
+    }
+}
+
+impl TryFrom<&Value> for ConfigFloat {
+    type Error = &'static str;
+
+    fn try_from(value: &Value) -> Result<Self, &'static str> {
+        match value {
+            Value::Float(value) => Ok(ConfigFloat(*value)),
+            // Hand-edited files often write `1` where `1.0` is meant
+            Value::Integer(value) => Ok(ConfigFloat(*value as f64)),
+            _ => Err("Couldn't parse value as float"),
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use thiserror::Error;
+
+use crate::game::entities::board_dump::Charset;
+use crate::game::entities::movement::BOARD_SIZE;
+
+// Name, usage and description of each command, in the order `help` lists them
+const COMMANDS: [(&str, &str, &str); 7] = [
+    (
+        "move",
+        "move <x> <y>",
+        "move the active caravan to the tile at x, y",
+    ),
+    (
+        "draw",
+        "draw <deck> <tile>",
+        "draw the deck's nth remaining tile, both from 1",
+    ),
+    (
+        "skip-move",
+        "skip-move",
+        "give up the active caravan's move to explore",
+    ),
+    (
+        "dump",
+        "dump board [ascii]",
+        "print the board, in plain ASCII if asked",
+    ),
+    ("seed", "seed", "print the game's seed"),
+    (
+        "reload-config",
+        "reload-config",
+        "re-read the configs from disk",
+    ),
+    ("help", "help", "list the commands"),
+];
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ConsoleError {
+    #[error("Unknown command {0}, try help")]
+    UnknownCommand(String),
+    #[error("Usage: {0}")]
+    Usage(&'static str),
+    #[error("{0} must be between {1} and {2}")]
+    OutOfRange(&'static str, u32, u32),
+}
+
+/// A debug console command. Decks and tiles are counted from 0, as in the
+/// game, but typed from 1, as they're labelled.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConsoleCommand {
+    Move((u8, u8)),
+    Draw { deck: u8, tile: u8 },
+    SkipMove,
+    DumpBoard(Charset),
+    Seed,
+    ReloadConfig,
+    Help,
+}
+
+impl ConsoleCommand {
+    /// Every command with its usage, one per line.
+    pub fn help() -> String {
+        COMMANDS
+            .iter()
+            .map(|(_, usage, description)| format!("{usage:<34}{description}"))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+fn usage(name: &str) -> ConsoleError {
+    let usage = COMMANDS
+        .iter()
+        .find(|(command, _, _)| *command == name)
+        .map_or("help", |(_, usage, _)| usage);
+
+    ConsoleError::Usage(usage)
+}
+
+fn number(value: &str, name: &'static str, range: (u32, u32)) -> Result<u32, ConsoleError> {
+    value
+        .parse::<u32>()
+        .ok()
+        .filter(|value| (range.0..=range.1).contains(value))
+        .ok_or(ConsoleError::OutOfRange(name, range.0, range.1))
+}
+
+impl TryFrom<&str> for ConsoleCommand {
+    type Error = ConsoleError;
+
+    fn try_from(line: &str) -> Result<Self, ConsoleError> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let Some((&name, args)) = words.split_first() else {
+            return Err(usage("help"));
+        };
+        let coordinate_range = (0, BOARD_SIZE as u32 - 1);
+
+        let command = match (name, args) {
+            ("move", [x, y]) => Self::Move((
+                number(x, "x", coordinate_range)? as u8,
+                number(y, "y", coordinate_range)? as u8,
+            )),
+            // The deck checks the tile against the tiles it has left
+            ("draw", [deck, tile]) => Self::Draw {
+                deck: number(deck, "deck", (1, u8::MAX as u32))? as u8 - 1,
+                tile: number(tile, "tile", (1, u8::MAX as u32))? as u8 - 1,
+            },
+            ("skip-move", []) => Self::SkipMove,
+            ("dump", ["board"]) => Self::DumpBoard(Charset::Unicode),
+            ("dump", ["board", "ascii"]) => Self::DumpBoard(Charset::Ascii),
+            ("seed", []) => Self::Seed,
+            ("reload-config", []) => Self::ReloadConfig,
+            ("help", []) => Self::Help,
+            (name, _) if COMMANDS.iter().any(|(command, _, _)| *command == name) => {
+                return Err(usage(name))
+            }
+            (name, _) => return Err(ConsoleError::UnknownCommand(name.to_owned())),
+        };
+
+        Ok(command)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::*;
+
+    #[test_case("move 5 3", ConsoleCommand::Move((5, 3)))]
+    #[test_case("  move   0 10 ", ConsoleCommand::Move((0, 10)))]
+    #[test_case("draw 1 17", ConsoleCommand::Draw { deck: 0, tile: 16 })]
+    #[test_case("draw 2 40", ConsoleCommand::Draw { deck: 1, tile: 39 })]
+    #[test_case("skip-move", ConsoleCommand::SkipMove)]
+    #[test_case("dump board", ConsoleCommand::DumpBoard(Charset::Unicode))]
+    #[test_case("dump board ascii", ConsoleCommand::DumpBoard(Charset::Ascii))]
+    #[test_case("seed", ConsoleCommand::Seed)]
+    #[test_case("reload-config", ConsoleCommand::ReloadConfig)]
+    fn parses_commands(line: &str, expected: ConsoleCommand) {
+        assert_eq!(ConsoleCommand::try_from(line), Ok(expected));
+    }
+
+    #[test_case("" => ConsoleError::Usage("help") ; "empty line")]
+    #[test_case("fly white" => ConsoleError::UnknownCommand(String::from("fly")) ; "unknown command")]
+    #[test_case("move 5" => ConsoleError::Usage("move <x> <y>") ; "missing argument")]
+    #[test_case("move 5 11" => ConsoleError::OutOfRange("y", 0, 10) ; "off the board")]
+    #[test_case("draw 0 1" => ConsoleError::OutOfRange("deck", 1, 255) ; "deck 0")]
+    #[test_case("draw 1 0" => ConsoleError::OutOfRange("tile", 1, 255) ; "tiles count from one")]
+    #[test_case("skip-move now" => ConsoleError::Usage("skip-move") ; "extra argument")]
+    #[test_case("dump board utf8" => ConsoleError::Usage("dump board [ascii]") ; "unknown charset")]
+    fn explains_rejected_commands(line: &str) -> ConsoleError {
+        ConsoleCommand::try_from(line).unwrap_err()
+    }
+
+    #[test]
+    fn lists_every_command() {
+        let help = ConsoleCommand::help();
+
+        assert_eq!(help.lines().count(), COMMANDS.len());
+        assert!(help.starts_with("move <x> <y>"));
+    }
+}
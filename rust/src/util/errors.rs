@@ -0,0 +1,75 @@
+use thiserror::Error;
+
+use crate::util::tileset::TilesetError;
+
+/// Problems bad content or scenes cause at runtime. They end the game on the
+/// error screen instead of crashing it, see `ErrorReporter`.
+#[derive(Error, Debug)]
+pub enum GameError {
+    #[error("{0}")]
+    Tileset(#[from] TilesetError),
+    #[error("The tileset has no deck {0}")]
+    MissingDeck(u8),
+    #[error("The starting cross has no tile {1} on {0}")]
+    MissingCrossTile(String, u8),
+    #[error("Couldn't change scenes: {0}")]
+    InvalidScene(String),
+}
+
+/// Messages of the problems reported since the error screen last showed
+/// them, oldest first.
+#[derive(Debug, Default, PartialEq)]
+pub struct ErrorLog {
+    messages: Vec<String>,
+}
+
+impl ErrorLog {
+    pub const fn new() -> Self {
+        Self {
+            messages: Vec::new(),
+        }
+    }
+    /// Adds `message` unless it's already listed, e.g. by every tile of a
+    /// broken tileset. Returns whether it's the first problem listed, which
+    /// is when the error screen should open.
+    pub fn push(&mut self, message: String) -> bool {
+        if self.messages.contains(&message) {
+            return false;
+        }
+
+        self.messages.push(message);
+        self.messages.len() == 1
+    }
+    pub fn take(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.messages)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_each_problem_once() {
+        let mut log = ErrorLog::new();
+
+        assert!(log.push(String::from("The tileset has no deck 5")));
+        assert!(!log.push(String::from("The tileset has no deck 5")));
+        assert!(!log.push(String::from("Couldn't change scenes")));
+        assert_eq!(
+            log.take(),
+            ["The tileset has no deck 5", "Couldn't change scenes"]
+        );
+        assert_eq!(log, ErrorLog::default());
+    }
+
+    #[test]
+    fn reads_like_the_tileset_error() {
+        let error = GameError::from(TilesetError::ParseError("Deck requires exactly 17 tiles"));
+
+        assert_eq!(
+            error.to_string(),
+            "Couldn't parse config/tileset.toml: Deck requires exactly 17 tiles"
+        );
+    }
+}
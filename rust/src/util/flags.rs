@@ -11,13 +11,13 @@ bitflags! {
 }
 
 impl CardinalDirectionFlags {
-    fn rotate_left(&self, amount: u32) -> CardinalDirectionFlags {
+    pub fn rotate_left(&self, amount: u32) -> CardinalDirectionFlags {
         let rotated_u8 = self.bits().rotate_left(amount);
         let upper_half = rotated_u8 >> 4;
 
         CardinalDirectionFlags::from_bits_truncate(upper_half + rotated_u8)
     }
-    fn rotate_right(&self, amount: u32) -> CardinalDirectionFlags {
+    pub fn rotate_right(&self, amount: u32) -> CardinalDirectionFlags {
         let rotated_u8 = self.bits().rotate_right(amount);
         let upper_half = rotated_u8 >> 4;
 
@@ -66,41 +66,26 @@ impl OasisLayoutFlags {
         let bits = (f.bits() as u16) << (4 * idx);
         OasisLayoutFlags::from_bits_truncate(bits)
     }
-    fn to_chunks(&self) -> Vec<CardinalDirectionFlags> {
-        let mut flags = self.bits();
-        let mut vec_flags = vec![];
-        let mut idx = 0;
+    /// The directions of each of the four oases, the first oasis first.
+    pub fn to_chunks(&self) -> [CardinalDirectionFlags; 4] {
+        let bits = self.bits();
 
-        while flags > 0 {
-            flags >>= 4 * idx;
-
-            vec_flags.push(CardinalDirectionFlags::from_bits_truncate(flags as u8));
-            idx += 1;
-        }
-
-        vec_flags
+        [0, 1, 2, 3]
+            .map(|idx| CardinalDirectionFlags::from_bits_truncate((bits >> (4 * idx)) as u8))
+    }
+    pub fn from_chunks(chunks: &[CardinalDirectionFlags; 4]) -> Self {
+        chunks
+            .iter()
+            .enumerate()
+            .fold(OasisLayoutFlags::empty(), |acc, (idx, f)| {
+                acc | OasisLayoutFlags::from_cardinal_direction_flags(f, idx as u8)
+            })
     }
     pub fn rotate_right(&self, amount: u32) -> Self {
-        let chunks: Vec<CardinalDirectionFlags> = self.to_chunks();
-
-        OasisLayoutFlags::from_bits_truncate(
-            chunks
-                .iter()
-                .enumerate()
-                .map(|(idx, f)| f.rotate_right(amount).bits().wrapping_shl(4 * idx as u32))
-                .fold(0, |acc, f| acc + (f as u16)),
-        )
+        OasisLayoutFlags::from_chunks(&self.to_chunks().map(|f| f.rotate_right(amount)))
     }
     pub fn rotate_left(&self, amount: u32) -> Self {
-        let chunks: Vec<CardinalDirectionFlags> = self.to_chunks();
-
-        OasisLayoutFlags::from_bits_truncate(
-            chunks
-                .iter()
-                .enumerate()
-                .map(|(idx, f)| f.rotate_left(amount).bits().wrapping_shl(4 * idx as u32))
-                .fold(0, |acc, f| acc + (f as u16)),
-        )
+        OasisLayoutFlags::from_chunks(&self.to_chunks().map(|f| f.rotate_left(amount)))
     }
 }
 
@@ -212,7 +197,7 @@ impl From<&CardinalDirection> for CardinalDirectionFlags {
 
 pub const OASIS_CONNECTION_LABELS: [&str; 6] =
     ["N | W", "N | E", "S | W", "S | E", "E | W", "N | S"];
-const OASIS_CONNECTION_FLAGS: [CardinalDirectionFlags; 6] = [
+pub const OASIS_CONNECTION_FLAGS: [CardinalDirectionFlags; 6] = [
     CardinalDirectionFlags::N.union(CardinalDirectionFlags::W),
     CardinalDirectionFlags::N.union(CardinalDirectionFlags::E),
     CardinalDirectionFlags::S.union(CardinalDirectionFlags::W),
@@ -236,3 +221,112 @@ impl From<CardinalDirectionFlags> for Vec<&str> {
             .collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+    use test_case::test_case;
+
+    use super::*;
+
+    fn direction_flags() -> impl Strategy<Value = CardinalDirectionFlags> {
+        (0u8..16).prop_map(CardinalDirectionFlags::from_bits_truncate)
+    }
+
+    fn layout_flags() -> impl Strategy<Value = OasisLayoutFlags> {
+        any::<u16>().prop_map(OasisLayoutFlags::from_bits_truncate)
+    }
+
+    #[test_case(CardinalDirectionFlags::N, 1 => CardinalDirectionFlags::E)]
+    #[test_case(CardinalDirectionFlags::W, 1 => CardinalDirectionFlags::N)]
+    #[test_case(CardinalDirectionFlags::N | CardinalDirectionFlags::E, 2 => CardinalDirectionFlags::S | CardinalDirectionFlags::W)]
+    #[test_case(CardinalDirectionFlags::S, 5 => CardinalDirectionFlags::W)]
+    fn rotates_directions_clockwise(
+        flags: CardinalDirectionFlags,
+        amount: u32,
+    ) -> CardinalDirectionFlags {
+        flags.rotate_left(amount)
+    }
+
+    #[test]
+    fn rotates_every_oasis_of_a_layout() {
+        let layout = OasisLayoutFlags::N1
+            | OasisLayoutFlags::E2
+            | OasisLayoutFlags::S3
+            | OasisLayoutFlags::W4;
+
+        assert_eq!(
+            layout.rotate_left(1),
+            OasisLayoutFlags::E1
+                | OasisLayoutFlags::S2
+                | OasisLayoutFlags::W3
+                | OasisLayoutFlags::N4
+        );
+        assert_eq!(
+            layout.rotate_right(1),
+            OasisLayoutFlags::W1
+                | OasisLayoutFlags::N2
+                | OasisLayoutFlags::E3
+                | OasisLayoutFlags::S4
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn four_direction_rotations_are_the_identity(flags in direction_flags(), amount in 0u32..16) {
+            prop_assert_eq!(flags.rotate_left(4), flags.clone());
+            prop_assert_eq!(flags.rotate_left(amount + 4), flags.rotate_left(amount));
+        }
+
+        #[test]
+        fn direction_rotations_invert_each_other(flags in direction_flags(), amount in 0u32..16) {
+            prop_assert_eq!(flags.rotate_left(amount).rotate_right(amount), flags.clone());
+            prop_assert_eq!(flags.rotate_right(amount).rotate_left(amount), flags);
+        }
+
+        #[test]
+        fn direction_rotations_keep_the_direction_count(flags in direction_flags(), amount in 0u32..16) {
+            prop_assert_eq!(flags.rotate_left(amount).bits().count_ones(), flags.bits().count_ones());
+        }
+
+        #[test]
+        fn four_layout_rotations_are_the_identity(layout in layout_flags()) {
+            let rotated = (0..4).fold(layout.clone(), |acc, _| acc.rotate_left(1));
+
+            prop_assert_eq!(rotated, layout);
+        }
+
+        #[test]
+        fn layout_rotations_invert_each_other(layout in layout_flags(), amount in 0u32..16) {
+            prop_assert_eq!(layout.rotate_left(amount).rotate_right(amount), layout.clone());
+            prop_assert_eq!(layout.rotate_right(amount).rotate_left(amount), layout);
+        }
+
+        #[test]
+        fn chunks_are_preserved(layout in layout_flags()) {
+            prop_assert_eq!(OasisLayoutFlags::from_chunks(&layout.to_chunks()), layout);
+        }
+
+        #[test]
+        fn rotating_a_layout_rotates_each_chunk(layout in layout_flags(), amount in 0u32..16) {
+            let chunks = layout.to_chunks();
+            let rotated = layout.rotate_left(amount).to_chunks();
+
+            for (chunk, rotated) in chunks.iter().zip(rotated) {
+                prop_assert_eq!(chunk.rotate_left(amount), rotated);
+            }
+        }
+
+        #[test]
+        fn union_commutes_with_rotation(layout in layout_flags(), amount in 0u32..16) {
+            prop_assert_eq!(
+                CardinalDirectionFlags::from(layout.rotate_left(amount)),
+                CardinalDirectionFlags::from(layout.clone()).rotate_left(amount)
+            );
+            prop_assert_eq!(
+                CardinalDirectionFlags::from(layout.rotate_right(amount)),
+                CardinalDirectionFlags::from(layout).rotate_right(amount)
+            );
+        }
+    }
+}
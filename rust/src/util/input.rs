@@ -3,6 +3,7 @@ pub enum InputActions {
     _Secondary,
     RotateCw,
     RotateCcw,
+    ToggleConsole,
 }
 
 impl From<InputActions> for String {
@@ -12,6 +13,7 @@ impl From<InputActions> for String {
             InputActions::_Secondary => String::from("Secondary"),
             InputActions::RotateCw => String::from("Rotate CW"),
             InputActions::RotateCcw => String::from("Rotate CCW"),
+            InputActions::ToggleConsole => String::from("Toggle Console"),
         }
     }
 }
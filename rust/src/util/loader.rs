@@ -3,13 +3,25 @@ use thiserror::Error;
 use toml::map::Map;
 
 use const_format::concatcp;
+use godot::builtin::GString;
+use godot::builtin::Variant;
+use godot::builtin::VariantType;
+use godot::classes::display_server::WindowMode as DisplayWindowMode;
 use godot::classes::file_access::ModeFlags;
+use godot::classes::AudioServer;
+use godot::classes::DirAccess;
+use godot::classes::DisplayServer;
 use godot::classes::FileAccess;
 use godot::classes::INode;
 use godot::classes::Node;
 use godot::classes::PackedScene;
+use godot::classes::ResourceLoader;
+use godot::global::linear_to_db;
+use godot::meta::ToGodot;
 use godot::obj::Base;
 use godot::obj::Gd;
+use godot::obj::Singleton;
+use godot::obj::WithBaseField;
 use godot::prelude::godot_api;
 use godot::prelude::load;
 use godot::prelude::GodotClass;
@@ -19,14 +31,30 @@ use toml::Value;
 
 use crate::game::components::tile_component::TileComponent;
 use crate::game::entities::tile::Tile;
-use crate::util::flags::CardinalDirectionFlags;
-use crate::util::flags::OasisLayoutFlags;
+use crate::util::mods::InvalidMod;
+use crate::util::mods::ModFiles;
+use crate::util::mods::ModPack;
+use crate::util::mods::MANIFEST_FILE;
+use crate::util::mods::MODS_ROOT;
+use crate::util::mods::RULES_FILE;
+use crate::util::mods::TILESET_FILE;
+use crate::util::mods::TREASURES_FILE;
+use crate::util::palette::PlayerPalette;
+use crate::util::rules::RulesConfig;
+use crate::util::settings::Settings;
+use crate::util::settings::SettingsParseError;
+use crate::util::settings::WindowMode;
+use crate::util::tileset::TilesetConfig;
+use crate::util::tileset::TilesetError;
+use crate::util::treasures::TreasureCatalog;
+use crate::util::Logger;
 use crate::util::RootWindow;
 
 const GAME_CONFIGS_ROOT: &str = "res://config/";
+// Seconds between checks for edited configs in debug builds
+const CONFIG_POLL_INTERVAL: f64 = 0.5;
 const GAME_OBJECTS_ROOT: &str = "res://game/objects/";
-
-mod test;
+const SETTINGS_PATH: &str = "user://settings.toml";
 
 pub const CROSS_IDS: [&str; 5] = ["cross_c", "cross_n", "cross_e", "cross_s", "cross_w"];
 
@@ -36,454 +64,593 @@ pub enum LoadTomlError {
     FileReadError(String),
     #[error("Error parsing toml")]
     ParseError(TomlError),
+    #[error("Error writing file {0}")]
+    FileWriteError(String),
+    #[error("{0}")]
+    SettingsError(#[from] SettingsParseError),
 }
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub enum GameConfig {
     Tileset,
+    Rules,
+    Treasures,
 }
 
-#[derive(Clone, Debug)]
-pub struct ConfigOasisFlags(CardinalDirectionFlags);
-
-impl From<ConfigOasisFlags> for CardinalDirectionFlags {
-    fn from(value: ConfigOasisFlags) -> Self {
-        value.0
-    }
-}
-
-impl TryFrom<&Value> for ConfigOasisFlags {
-    type Error = &'static str;
-
-    fn try_from(value: &Value) -> Result<Self, &'static str> {
-        match value {
-            Value::String(value) => Ok(ConfigOasisFlags(
-                (bitflags::parser::from_str(value))
-                    .or(Err("Expected oasis flags to be bit flags"))?,
-            )),
-            _ => Err("Couldn't parse value as boolean"),
+impl GameConfig {
+    pub fn path(&self) -> &'static str {
+        match self {
+            GameConfig::Tileset => concatcp!(GAME_CONFIGS_ROOT, "tileset.toml"),
+            GameConfig::Rules => concatcp!(GAME_CONFIGS_ROOT, "rules.toml"),
+            GameConfig::Treasures => concatcp!(GAME_CONFIGS_ROOT, "treasures.toml"),
         }
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct ConfigOasisLayoutFlags(OasisLayoutFlags);
-
-impl ConfigOasisLayoutFlags {
-    fn from_key_in_table(key: &str, table: &Table) -> Option<Self> {
-        let res = table
-            .get(key)
-            .and_then(|v| ConfigOasisLayoutFlags::try_from(v).ok());
+/// The configs read from disk, and the tileset and treasures parsed from
+/// them. The tileset is validated against the treasures, so the two are
+/// parsed and dropped together.
+#[derive(Debug, Default)]
+struct ConfigCache {
+    tables: HashMap<GameConfig, Table>,
+    // Parsed from `tables` on first use
+    tileset: Option<TilesetConfig>,
+    treasures: Option<TreasureCatalog>,
+}
 
-        res
+impl ConfigCache {
+    fn table(&self, config: &GameConfig) -> Option<&Table> {
+        self.tables.get(config)
     }
-    fn from_key_in_table_as_oasis_flags_array(
-        key: &str,
-        table: &Table,
-    ) -> Option<ConfigOasisLayoutFlags> {
-        ConfigOasisLayoutFlags::from_key_in_table(key, table)
+    fn configs(&self) -> Vec<GameConfig> {
+        self.tables.keys().cloned().collect()
     }
-}
-
-impl From<ConfigOasisLayoutFlags> for OasisLayoutFlags {
-    fn from(value: ConfigOasisLayoutFlags) -> Self {
-        value.0
+    fn insert(&mut self, config: GameConfig, table: Table) {
+        self.tables.insert(config, table);
     }
-}
-
-// impl TryFrom<&Vec<Value>> for ConfigOasisFlagsArray {
-//     type Error = &'static str;
-//
-//     fn try_from(value: &Vec<Value>) -> Result<Self, &'static str> {
-//         if value.len() != 5 {
-//             return Err("Could not parse value as array of 4 elements");
-//         }
-//
-//         let oasis_flags: Vec<ConfigOasisFlags> = value
-//             .iter()
-//             .map(ConfigOasisFlags::try_from)
-//             .collect::<Result<Vec<ConfigOasisFlags>, &str>>(
-//         )?;
-//
-//         let array = std::array::from_fn(|idx| oasis_flags[idx].clone());
-//
-//         Ok(ConfigOasisFlagsArray(array))
-//     }
-// }
-
-impl TryFrom<&Value> for ConfigOasisLayoutFlags {
-    type Error = &'static str;
-
-    fn try_from(value: &Value) -> Result<Self, &'static str> {
-        match value {
-            Value::Array(value) => {
-                let mut oasis_flags: Vec<ConfigOasisFlags> = value
-                    .iter()
-                    .map(ConfigOasisFlags::try_from)
-                    .collect::<Result<Vec<ConfigOasisFlags>, &'static str>>()?;
-
-                oasis_flags.resize(4, ConfigOasisFlags(CardinalDirectionFlags::empty()));
-
-                let output_flags = oasis_flags.iter().enumerate().fold(
-                    OasisLayoutFlags::empty(),
-                    |acc, (idx, flags)| {
-                        acc | OasisLayoutFlags::from_cardinal_direction_flags(&flags.0, idx as u8)
-                    },
-                );
-
-                Ok(ConfigOasisLayoutFlags(output_flags))
-            }
-            _ => Err("Couldn't parse value as array"),
+    fn tileset(&mut self) -> Result<TilesetConfig, TilesetError> {
+        if let Some(tileset) = &self.tileset {
+            return Ok(tileset.clone());
         }
-    }
-}
 
-pub struct ConfigBool(bool);
+        let table = self
+            .tables
+            .get(&GameConfig::Tileset)
+            .ok_or(TilesetError::LoadError)?;
+        let tileset = TilesetConfig::try_from(table).map_err(TilesetError::ParseError)?;
 
-impl ConfigBool {
-    fn from_key_in_table(key: &str, table: &Table) -> Option<Self> {
-        table.get(key).and_then(|v| ConfigBool::try_from(v).ok())
-    }
-    fn from_key_in_table_as_bool(key: &str, table: &Table) -> Option<bool> {
-        ConfigBool::from_key_in_table(key, table).map(|b| b.into())
-    }
-}
+        self.treasures()?.validate(tileset.treasure_references())?;
 
-impl From<ConfigBool> for bool {
-    fn from(value: ConfigBool) -> Self {
-        value.0
+        self.tileset = Some(tileset.clone());
+
+        Ok(tileset)
     }
-}
+    fn treasures(&mut self) -> Result<TreasureCatalog, TilesetError> {
+        if let Some(treasures) = &self.treasures {
+            return Ok(treasures.clone());
+        }
 
-impl TryFrom<&Value> for ConfigBool {
-    type Error = &'static str;
+        let table = self
+            .tables
+            .get(&GameConfig::Treasures)
+            .ok_or(TilesetError::TreasuresLoadError)?;
+        let treasures = TreasureCatalog::try_from(table)?;
+
+        self.treasures = Some(treasures.clone());
+
+        Ok(treasures)
+    }
+    /// Swaps in an edited config. One that no longer parses, or that leaves
+    /// the tileset failing validation, is rejected and the previous config
+    /// and everything parsed from it stay.
+    fn replace(&mut self, config: GameConfig, table: Table) -> Result<(), String> {
+        let previous = self.tables.insert(config.clone(), table);
+        let tileset = self.tileset.take();
+        let treasures = self.treasures.take();
+
+        let validated = match config {
+            GameConfig::Rules => RulesConfig::try_from(&self.tables[&config])
+                .map(|_| ())
+                .map_err(|error| error.to_string()),
+            GameConfig::Tileset | GameConfig::Treasures => self
+                .tileset()
+                .map(|_| ())
+                .map_err(|error| error.to_string()),
+        };
 
-    fn try_from(value: &Value) -> Result<Self, &'static str> {
-        match value {
-            Value::Boolean(value) => Ok(ConfigBool(*value)),
-            _ => Err("Couldn't parse value as boolean"),
+        if validated.is_err() {
+            match previous {
+                Some(previous) => self.tables.insert(config, previous),
+                None => self.tables.remove(&config),
+            };
+
+            self.tileset = tileset;
+            self.treasures = treasures;
         }
+
+        validated
     }
 }
 
-pub struct ConfigString(String);
+#[derive(GodotClass, Debug)]
+#[class(init, base=Node)]
+pub struct TomlLoader {
+    base: Base<Node>,
+    configs: ConfigCache,
+    // Set from the lobby to play a single game with different rules
+    rules_override: Option<RulesConfig>,
+    // Set from the lobby to play a single game on a generated tileset
+    tileset_override: Option<TilesetConfig>,
+    // The seed the generated tileset came from, for the debug console
+    tileset_seed: Option<u64>,
+    // Set from the lobby to play a content pack's own treasures
+    treasures_override: Option<TreasureCatalog>,
+    // When each cached config was last modified, to reload edited ones
+    modified_times: HashMap<GameConfig, u64>,
+    since_poll: f64,
+}
 
-impl ConfigString {
-    fn from_key_in_table(key: &str, table: &Table) -> Option<Self> {
-        table.get(key).and_then(|v| ConfigString::try_from(v).ok())
+#[godot_api]
+impl TomlLoader {
+    pub fn global(node: &Node) -> Gd<TomlLoader> {
+        node.get_tree_root()
+            .get_node_as::<TomlLoader>("./GlobalTomlLoader")
     }
-    fn from_key_in_table_as_string(key: &str, table: &Table) -> Option<String> {
-        ConfigString::from_key_in_table(key, table).map(|s| s.into())
+    pub fn get(node: &Node, config: GameConfig) -> Option<Map<String, Value>> {
+        Self::global(node).bind_mut().table(config)
     }
-}
+    /// The tileset for the next game: the lobby's generated one if there is
+    /// one, otherwise `config/tileset.toml`.
+    pub fn tileset(node: &Node) -> Result<TilesetConfig, TilesetError> {
+        if let Some(tileset) = &Self::global(node).bind().tileset_override {
+            return Ok(tileset.clone());
+        }
 
-impl From<ConfigString> for String {
-    fn from(value: ConfigString) -> Self {
-        value.0
+        Self::file_tileset(node)
     }
-}
-
-impl TryFrom<&Value> for ConfigString {
-    type Error = &'static str;
+    /// The parsed `config/tileset.toml`, with every treasure it places
+    /// checked against `config/treasures.toml`.
+    pub fn file_tileset(node: &Node) -> Result<TilesetConfig, TilesetError> {
+        Self::global(node).bind_mut().parse_tileset()
+    }
+    /// The treasures for the next game: the lobby's content pack's if it
+    /// defines its own, otherwise every treasure in `config/treasures.toml`.
+    pub fn treasures(node: &Node) -> Result<TreasureCatalog, TilesetError> {
+        if let Some(treasures) = &Self::global(node).bind().treasures_override {
+            return Ok(treasures.clone());
+        }
 
-    fn try_from(value: &Value) -> Result<Self, &'static str> {
-        match value {
-            Value::String(value) => Ok(ConfigString(value.clone())),
-            _ => Err("Couldn't parse value as string"),
+        Self::global(node).bind_mut().parse_treasures()
+    }
+    /// The rules for the next game: the lobby's override if there is one,
+    /// otherwise `config/rules.toml`. An invalid rules file falls back to the
+    /// standard rules after logging why.
+    pub fn rules(node: &Node) -> RulesConfig {
+        if let Some(rules) = &Self::global(node).bind().rules_override {
+            return rules.clone();
         }
+
+        Self::file_rules(node)
     }
-}
+    /// The rules in `config/rules.toml`, ignoring any override.
+    pub fn file_rules(node: &Node) -> RulesConfig {
+        let Some(table) = Self::get(node, GameConfig::Rules) else {
+            Logger::warn("Couldn't load rules.toml, playing with the standard rules");
+            return RulesConfig::default();
+        };
 
-#[derive(Debug, Clone)]
-pub struct CrossConfigArray([TileConfig; 5]);
+        RulesConfig::try_from(&table).unwrap_or_else(|error| {
+            Logger::warn(&format!(
+                "Invalid rules.toml, playing with the standard rules: {error}"
+            ));
+            RulesConfig::default()
+        })
+    }
+    /// Plays the next games with `rules` instead of `config/rules.toml`, or
+    /// goes back to the file with `None`.
+    pub fn set_rules_override(node: &Node, rules: Option<RulesConfig>) {
+        Self::global(node).bind_mut().rules_override = rules;
+    }
+    /// Plays the next games on `tileset` instead of `config/tileset.toml`, or
+    /// goes back to the file with `None`. `seed` is the one a random tileset
+    /// was generated from.
+    pub fn set_tileset_override(node: &Node, tileset: Option<TilesetConfig>, seed: Option<u64>) {
+        let mut loader = Self::global(node);
+        let mut loader = loader.bind_mut();
+
+        loader.tileset_override = tileset;
+        loader.tileset_seed = seed;
+    }
+    /// The seed the next game's tileset was generated from, if it's a random
+    /// one.
+    pub fn tileset_seed(node: &Node) -> Option<u64> {
+        Self::global(node).bind().tileset_seed
+    }
+    /// Plays the next games with `treasures` instead of
+    /// `config/treasures.toml`, or goes back to the file with `None`.
+    pub fn set_treasures_override(node: &Node, treasures: Option<TreasureCatalog>) {
+        Self::global(node).bind_mut().treasures_override = treasures;
+    }
+    /// Every content pack in `user://mods/`, sorted by directory. Packs are
+    /// validated against the game's own treasures, and the invalid ones say
+    /// why they can't be played.
+    pub fn mods(node: &Node) -> Vec<Result<ModPack, InvalidMod>> {
+        // Create the directory, so players know where packs go
+        if !DirAccess::dir_exists_absolute(MODS_ROOT) {
+            DirAccess::make_dir_recursive_absolute(MODS_ROOT);
+        }
 
-impl CrossConfigArray {
-    fn from_key_in_table(key: &str, table: &Table) -> Option<Self> {
-        let val = table
-            .get(key)
-            .and_then(|v| CrossConfigArray::try_from(v).ok());
+        let base_treasures = Self::global(node)
+            .bind_mut()
+            .parse_treasures()
+            .unwrap_or_else(|error| {
+                Logger::error(&error.to_string());
+                TreasureCatalog::default()
+            });
+
+        let mut directories: Vec<String> = DirAccess::get_directories_at(MODS_ROOT)
+            .as_slice()
+            .iter()
+            .map(GString::to_string)
+            .collect();
+
+        directories.sort();
+
+        directories
+            .iter()
+            .map(|directory| {
+                let read = |file: &str| Self::read_text(&format!("{MODS_ROOT}{directory}/{file}"));
+                let files = ModFiles {
+                    manifest: read(MANIFEST_FILE),
+                    tileset: read(TILESET_FILE),
+                    rules: read(RULES_FILE),
+                    treasures: read(TREASURES_FILE),
+                };
+
+                ModPack::validate(directory, &files, &base_treasures, |icon| {
+                    FileAccess::file_exists(icon) || ResourceLoader::singleton().exists(icon)
+                })
+            })
+            .collect()
+    }
+    fn read_text(path: &str) -> Option<String> {
+        if !FileAccess::file_exists(path) {
+            return None;
+        }
 
-        val
+        FileAccess::open(path, ModeFlags::READ).map(|file| String::from(file.get_as_text()))
     }
-    fn from_key_in_table_as_array(key: &str, table: &Table) -> Option<[TileConfig; 5]> {
-        CrossConfigArray::from_key_in_table(key, table).map(|b| b.into())
+    fn table(&mut self, config: GameConfig) -> Option<Table> {
+        self.load_once(config.clone());
+        self.configs.table(&config).cloned()
     }
-}
+    /// Reads `config` from disk unless it already was.
+    fn load_once(&mut self, config: GameConfig) {
+        if self.configs.table(&config).is_some() {
+            return;
+        }
 
-impl From<CrossConfigArray> for [TileConfig; 5] {
-    fn from(value: CrossConfigArray) -> Self {
-        value.0
+        if let Ok(table) = self.read(config.clone()) {
+            self.configs.insert(config, table);
+        }
     }
-}
+    fn parse_tileset(&mut self) -> Result<TilesetConfig, TilesetError> {
+        self.load_once(GameConfig::Tileset);
+        self.load_once(GameConfig::Treasures);
+        self.configs.tileset()
+    }
+    fn parse_treasures(&mut self) -> Result<TreasureCatalog, TilesetError> {
+        self.load_once(GameConfig::Treasures);
+        self.configs.treasures()
+    }
+    /// Re-reads a config edited on disk. A config that no longer parses or
+    /// validates keeps its previous contents, so saving a half-finished edit
+    /// doesn't break the running game.
+    fn reload(&mut self, config: GameConfig) {
+        let path = config.path();
+
+        // The tileset is validated against the treasures, so both are needed
+        // to check an edit of either
+        self.load_once(GameConfig::Tileset);
+        self.load_once(GameConfig::Treasures);
+
+        // Check the edit once, even if it fails to load
+        self.modified_times
+            .insert(config.clone(), FileAccess::get_modified_time(path));
+
+        let validated = match self.read(config.clone()) {
+            Ok(table) => self.configs.replace(config, table),
+            Err(error) => Err(error.to_string()),
+        };
 
-impl TryFrom<&Vec<Value>> for CrossConfigArray {
-    type Error = &'static str;
+        if let Err(error) = validated {
+            Logger::error(&format!("Keeping the previous {path}: {error}"));
 
-    fn try_from(value: &Vec<Value>) -> Result<Self, &'static str> {
-        if value.len() != 5 {
-            return Err("Could not parse value as array of 5 elements");
+            return;
         }
 
-        let tile_configs: Vec<TileConfig> = value
-            .iter()
-            .map(TileConfig::try_from)
-            .collect::<Result<Vec<TileConfig>, &str>>()?;
+        Logger::info(&format!("Reloaded {path}"));
 
-        let array = std::array::from_fn(|idx| tile_configs[idx].clone());
+        self.base_mut()
+            .call_deferred("emit_config_reloaded", &[path.to_variant()]);
+    }
+    /// Re-reads every config the game has loaded, edited or not, e.g. from
+    /// the debug console. Configs that no longer validate are kept.
+    pub fn reload_configs(node: &Node) {
+        let mut gd_loader = Self::global(node);
+        let configs = gd_loader.bind().configs.configs();
 
-        Ok(CrossConfigArray(array))
+        for config in configs {
+            gd_loader.bind_mut().reload(config);
+        }
     }
-}
+    /// Announces that the config at `path` was edited and reloaded.
+    ///
+    /// Godot invokes this by name (the `"emit_config_reloaded"` string) from
+    /// [`Self::reload`] through `call_deferred`, so listeners can read the
+    /// config once the reload no longer holds the loader mutably.
+    #[func]
+    fn emit_config_reloaded(&self, path: GString) {
+        self.to_gd().signals().config_reloaded().emit(&path);
+    }
+    /// Emitted in debug builds with the path of a config that was edited on
+    /// disk, once it's been reloaded and validated.
+    #[signal]
+    pub fn config_reloaded(path: GString);
+    /// Reads and parses `config` from disk, noting when it was modified.
+    fn read(&mut self, config: GameConfig) -> Result<Table, LoadTomlError> {
+        let table_path = config.path();
 
-impl TryFrom<&Value> for CrossConfigArray {
-    type Error = &'static str;
+        let raw_data = String::from(
+            FileAccess::open(table_path, ModeFlags::READ)
+                .ok_or(LoadTomlError::FileReadError(table_path.to_owned()))?
+                .get_as_text(),
+        );
 
-    fn try_from(value: &Value) -> Result<Self, &'static str> {
-        match value {
-            Value::Array(value) => {
-                let tile_configs: Vec<TileConfig> = value
-                    .iter()
-                    .map(TileConfig::try_from_for_cross)
-                    .collect::<Result<Vec<TileConfig>, &'static str>>()?;
+        let table = toml::from_str::<Table>(&raw_data).map_err(LoadTomlError::ParseError)?;
 
-                let array: [TileConfig; 5] = tile_configs.try_into().or(Err(
-                    "Couldn't coerce TileConfig for center cross into array of 5 tiles",
-                ))?;
+        self.modified_times
+            .insert(config, FileAccess::get_modified_time(table_path));
 
-                Ok(CrossConfigArray(array))
-            }
-            _ => Err("Couldn't parse value as array"),
-        }
+        Ok(table)
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct TileConfig {
-    pub is_cross: Option<bool>,
-    pub is_desert: Option<bool>,
-    pub oasis: Option<OasisLayoutFlags>,
-    pub treasure_n: Option<String>,
-    pub treasure_e: Option<String>,
-    pub treasure_s: Option<String>,
-    pub treasure_w: Option<String>,
-}
-
-impl TileConfig {
-    fn try_from_for_cross(value: &Value) -> Result<Self, &'static str> {
-        let mut base = Self::try_from(value)?;
+#[godot_api]
+impl INode for TomlLoader {
+    fn ready(&mut self) {
+        // Only development builds watch the configs for edits
+        self.base_mut().set_process(cfg!(debug_assertions));
+    }
+    fn process(&mut self, dt: f64) {
+        self.since_poll += dt;
 
-        base.is_cross = Some(true);
+        if self.since_poll < CONFIG_POLL_INTERVAL {
+            return;
+        }
 
-        Ok(base)
-    }
-}
+        self.since_poll = 0.0;
 
-impl TryFrom<&Value> for TileConfig {
-    type Error = &'static str;
+        let edited: Vec<GameConfig> = self
+            .modified_times
+            .iter()
+            .filter(|(config, modified)| FileAccess::get_modified_time(config.path()) != **modified)
+            .map(|(config, _)| config.clone())
+            .collect();
 
-    fn try_from(value: &Value) -> Result<Self, &'static str> {
-        match value {
-            Value::Table(table) => {
-                let is_desert = ConfigBool::from_key_in_table_as_bool("is_desert", table);
-                let oasis =
-                    ConfigOasisLayoutFlags::from_key_in_table_as_oasis_flags_array("oasis", table);
-                let treasure_n = ConfigString::from_key_in_table_as_string("treasure_n", table);
-                let treasure_e = ConfigString::from_key_in_table_as_string("treasure_e", table);
-                let treasure_s = ConfigString::from_key_in_table_as_string("treasure_s", table);
-                let treasure_w = ConfigString::from_key_in_table_as_string("treasure_w", table);
-
-                Ok(Self {
-                    is_cross: Some(false),
-                    is_desert,
-                    oasis: oasis.map(|f| f.0),
-                    treasure_n,
-                    treasure_e,
-                    treasure_s,
-                    treasure_w,
-                })
-            }
-            _ => Err("Could not parse value as tile config table"),
+        for config in edited {
+            self.reload(config);
         }
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct CrossConfig {
-    c: TileConfig,
-    n: [TileConfig; 5],
-    e: [TileConfig; 5],
-    s: [TileConfig; 5],
-    w: [TileConfig; 5],
+#[derive(GodotClass, Debug)]
+#[class(init, base=Node)]
+pub struct SettingsManager {
+    base: Base<Node>,
+    settings: Settings,
+    // Whether settings changed through `preview` still need saving
+    unsaved: bool,
 }
 
-impl CrossConfig {
-    pub fn get_side(&self, side: &str) -> Result<[TileConfig; 5], &'static str> {
-        match side {
-            "cross_n" => Ok(self.n.clone()),
-            "cross_e" => Ok(self.e.clone()),
-            "cross_s" => Ok(self.s.clone()),
-            "cross_w" => Ok(self.w.clone()),
-            _ => Err("Requested invalid side for cross config"),
-        }
+impl SettingsManager {
+    pub fn get(node: &Node) -> Gd<SettingsManager> {
+        let root = node.get_tree_root();
+
+        root.get_node_as::<SettingsManager>("./GlobalSettings")
     }
-    pub fn get_center(&self) -> TileConfig {
-        self.c.clone()
+    pub fn settings(&self) -> &Settings {
+        &self.settings
     }
-}
+    pub fn player_palette(&self) -> PlayerPalette {
+        PlayerPalette::new(
+            self.settings.colorblind_palette,
+            &self.settings.custom_colors,
+        )
+    }
+    /// Changes the settings, applies them to the running game and saves them
+    /// to `user://settings.toml`.
+    pub fn update(&mut self, change: impl FnOnce(&mut Settings)) {
+        self.preview(change);
+        self.save_changes();
+    }
+    /// Like [`Self::update`], but leaves saving to [`Self::save_changes`],
+    /// for settings that change many times a second, e.g. while dragging a
+    /// slider.
+    pub fn preview(&mut self, change: impl FnOnce(&mut Settings)) {
+        change(&mut self.settings);
 
-impl TryFrom<&Option<&Value>> for CrossConfig {
-    type Error = &'static str;
-
-    fn try_from(value: &Option<&Value>) -> Result<Self, &'static str> {
-        if let Some(value) = value {
-            if let Value::Table(table) = value {
-                let center_value = value
-                    .get("c")
-                    .ok_or("Missing center entry in cross config")?;
-                let c = TileConfig::try_from_for_cross(center_value)
-                    .map_err(|_| "Missing center entry in cross config")?;
-                let n = CrossConfigArray::from_key_in_table_as_array("n", table)
-                    .ok_or("Missing north entry in cross config")?;
-                let e = CrossConfigArray::from_key_in_table_as_array("e", table)
-                    .ok_or("Missing east entry in cross config")?;
-                let s = CrossConfigArray::from_key_in_table_as_array("s", table)
-                    .ok_or("Missing south entry in cross config")?;
-                let w = CrossConfigArray::from_key_in_table_as_array("w", table)
-                    .ok_or("Missing west entry in cross config")?;
-
-                Ok(Self { c, n, e, s, w })
-            } else {
-                Err("Cross config was not a table")
-            }
-        } else {
-            Err("Could not find cross config")
-        }
+        self.apply();
+        self.unsaved = true;
+
+        self.to_gd().call_deferred("emit_settings_changed", &[]);
     }
-}
+    /// Saves the settings if they changed since they were last saved.
+    pub fn save_changes(&mut self) {
+        if !self.unsaved {
+            return;
+        }
 
-#[derive(Debug, Clone)]
-pub struct DeckConfig(pub [TileConfig; 17]);
+        self.unsaved = false;
 
-impl TryFrom<&Value> for DeckConfig {
-    type Error = &'static str;
+        if let Err(error) = self.save() {
+            Logger::error(&format!("Couldn't save settings: {error}"));
+        }
+    }
+    /// Reads the saved settings, along with the problems of the settings
+    /// that were dropped for their default.
+    fn read() -> Result<(Settings, Vec<SettingsParseError>), LoadTomlError> {
+        // No file yet simply means the player never changed a setting
+        if !FileAccess::file_exists(SETTINGS_PATH) {
+            return Ok((Settings::default(), vec![]));
+        }
 
-    fn try_from(value: &Value) -> Result<Self, &'static str> {
-        match value {
-            Value::Array(value) => {
-                let value: Vec<Result<TileConfig, &str>> =
-                    value.iter().map(TileConfig::try_from).collect();
+        let raw_data = String::from(
+            FileAccess::open(SETTINGS_PATH, ModeFlags::READ)
+                .ok_or(LoadTomlError::FileReadError(SETTINGS_PATH.to_owned()))?
+                .get_as_text(),
+        );
 
-                if value.len() != 17 {
-                    return Err("Deck requires exactly 17 tiles");
-                }
+        let table = toml::from_str::<Table>(&raw_data).map_err(LoadTomlError::ParseError)?;
 
-                let array: [TileConfig; 17] =
-                    std::array::from_fn(|idx| value[idx].clone().unwrap());
+        Ok(Settings::from_table_lossy(&table))
+    }
+    fn save(&self) -> Result<(), LoadTomlError> {
+        let mut file = FileAccess::open(SETTINGS_PATH, ModeFlags::WRITE)
+            .ok_or(LoadTomlError::FileWriteError(SETTINGS_PATH.to_owned()))?;
 
-                Ok(DeckConfig(array))
-            }
-            _ => Err("Couldn't parse value as array"),
-        }
+        file.store_string(&Table::from(&self.settings).to_string());
+
+        Ok(())
     }
-}
+    fn apply(&self) {
+        Logger::set_threshold_override(self.settings.log_level);
 
-pub struct DeckConfigArray([DeckConfig; 5]);
+        let window_mode = match self.settings.window_mode {
+            WindowMode::Windowed => DisplayWindowMode::WINDOWED,
+            WindowMode::Fullscreen => DisplayWindowMode::FULLSCREEN,
+            WindowMode::ExclusiveFullscreen => DisplayWindowMode::EXCLUSIVE_FULLSCREEN,
+        };
 
-impl TryFrom<&Table> for DeckConfigArray {
-    type Error = &'static str;
+        DisplayServer::singleton().window_set_mode(window_mode);
 
-    fn try_from(table: &Table) -> Result<Self, &'static str> {
-        let value = table
-            .get("decks")
-            .ok_or("Could not find list of decks in config")?;
+        self.base()
+            .get_tree_root()
+            .set_content_scale_factor(self.settings.ui_scale as f32);
 
-        match value {
-            Value::Array(value) => {
-                let vec_of_nested_decks: Result<Vec<&Value>, &'static str> = value
-                    .iter()
-                    .map(|t| {
-                        t.get("deck")
-                            .ok_or("Missing property 'deck' in decks array")
-                    })
-                    .collect();
-
-                let value: Vec<Result<DeckConfig, &str>> = vec_of_nested_decks?
-                    .iter()
-                    .map(|e| DeckConfig::try_from(*e))
-                    .collect();
-
-                if value.len() != 5 {
-                    return Err("Config requires exactly 5 tile decks");
-                }
+        let mut audio_server = AudioServer::singleton();
+        let audio = &self.settings.audio;
 
-                let array: [DeckConfig; 5] = std::array::from_fn(|idx| value[idx].clone().unwrap());
+        for (bus, volume) in [
+            ("Master", audio.master_volume),
+            ("Music", audio.music_volume),
+            ("Effects", audio.effects_volume),
+        ] {
+            let index = audio_server.get_bus_index(bus);
 
-                Ok(DeckConfigArray(array))
+            // Only the Master bus is guaranteed to exist
+            if index < 0 {
+                continue;
             }
-            _ => Err("Couldn't parse value as array"),
+
+            audio_server.set_bus_volume_db(index, linear_to_db(volume) as f32);
         }
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct TilesetConfig {
-    pub cross: CrossConfig,
-    pub deck: [DeckConfig; 5],
-}
-
-impl TryFrom<&Table> for TilesetConfig {
-    type Error = &'static str;
-
-    fn try_from(table: &Table) -> Result<Self, Self::Error> {
-        let cross = CrossConfig::try_from(&table.get("cross"))?;
-        let deck = DeckConfigArray::try_from(table)?.0;
+#[godot_api]
+impl SettingsManager {
+    /// Reads a setting by its `section.key` path in `settings.toml`, e.g.
+    /// `audio.master_volume`. Returns nil for unknown or unset settings.
+    #[func]
+    fn get_setting(&self, path: GString) -> Variant {
+        let table = Table::from(&self.settings);
+        let path = path.to_string();
+
+        let value = path
+            .split_once('.')
+            .and_then(|(section, key)| table.get(section)?.get(key));
 
-        Ok(Self { cross, deck })
+        match value {
+            Some(Value::Float(value)) => value.to_variant(),
+            Some(Value::Integer(value)) => value.to_variant(),
+            Some(Value::Boolean(value)) => value.to_variant(),
+            Some(Value::String(value)) => GString::from(value.as_str()).to_variant(),
+            _ => Variant::nil(),
+        }
     }
-}
+    /// Changes a setting by its `section.key` path in `settings.toml`. The
+    /// value goes through the same validation as the file. Returns whether
+    /// the change was accepted.
+    #[func]
+    fn set_setting(&mut self, path: GString, value: Variant) -> bool {
+        let path = path.to_string();
 
-#[derive(GodotClass, Debug)]
-#[class(init, base=Node)]
-pub struct TomlLoader {
-    base: Base<Node>,
-    configs: HashMap<GameConfig, Table>,
-}
+        let Some((section, key)) = path.split_once('.') else {
+            Logger::warn(&format!("Expected setting path as section.key, got {path}"));
+            return false;
+        };
 
-impl TomlLoader {
-    pub fn get(node: &Node, config: GameConfig) -> Option<Map<String, Value>> {
-        let root = node.get_tree_root();
+        let value = match value.get_type() {
+            VariantType::FLOAT => Value::Float(value.to::<f64>()),
+            VariantType::INT => Value::Integer(value.to::<i64>()),
+            VariantType::BOOL => Value::Boolean(value.to::<bool>()),
+            VariantType::STRING => Value::String(value.to::<GString>().to_string()),
+            _ => return false,
+        };
 
-        {
-            let gd_loader = root.get_node_as::<TomlLoader>("./GlobalTomlLoader");
-            let loader = gd_loader.bind();
+        let mut table = Table::from(&self.settings);
+
+        let Some(Value::Table(section)) = table.get_mut(section) else {
+            Logger::warn(&format!("Unknown settings section in {path}"));
+            return false;
+        };
 
-            let table = loader.configs.get(&config);
+        section.insert(key.to_owned(), value);
 
-            if let Some(table) = table {
-                return Some(table.clone());
+        match Settings::try_from(&table) {
+            Ok(settings) => {
+                self.update(|s| *s = settings);
+                true
+            }
+            Err(error) => {
+                Logger::warn(&format!("Rejected setting {path}: {error}"));
+                false
             }
         }
-
-        let mut gd_loader = root.get_node_as::<TomlLoader>("./GlobalTomlLoader");
-        let mut loader = gd_loader.bind_mut();
-        loader.load(config).ok()
     }
-    fn load(&mut self, config: GameConfig) -> Result<Table, LoadTomlError> {
-        let table_path = match config {
-            GameConfig::Tileset => concatcp!(GAME_CONFIGS_ROOT, "tileset.toml"),
-        };
-
-        let raw_data = String::from(
-            FileAccess::open(table_path, ModeFlags::READ)
-                .ok_or(LoadTomlError::FileReadError(table_path.to_owned()))?
-                .get_as_text(),
-        );
+    /// Announces a settings change.
+    ///
+    /// Godot invokes this by name (the `"emit_settings_changed"` string) from
+    /// [`Self::update`] through `call_deferred`, so listeners can read the
+    /// settings once `update` no longer holds them mutably.
+    #[func]
+    fn emit_settings_changed(&self) {
+        self.to_gd().signals().settings_changed().emit();
+    }
+    #[signal]
+    pub fn settings_changed();
+}
 
-        let table = toml::from_str::<Table>(&raw_data).map_err(LoadTomlError::ParseError)?;
+#[godot_api]
+impl INode for SettingsManager {
+    fn ready(&mut self) {
+        match Self::read() {
+            Ok((settings, errors)) => {
+                for error in errors {
+                    Logger::warn(&format!(
+                        "Dropped an invalid setting from {SETTINGS_PATH}, using its default: {error}"
+                    ));
+                }
 
-        self.configs.insert(GameConfig::Tileset, table.clone());
+                self.settings = settings;
+            }
+            Err(error) => Logger::warn(&format!(
+                "Couldn't load {SETTINGS_PATH}, using default settings: {error}"
+            )),
+        }
 
-        Ok(table)
+        self.apply();
     }
 }
 
@@ -521,3 +688,65 @@ impl INode for SceneLoader {
         self.tile_scene = load(concatcp!(GAME_OBJECTS_ROOT, "tile.tscn"));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+
+    use super::*;
+
+    const SHIPPED_TILESET: &str = include_str!("../../../godot/config/tileset.toml");
+    const SHIPPED_TREASURES: &str = include_str!("../../../godot/config/treasures.toml");
+
+    fn shipped_configs() -> ConfigCache {
+        let mut configs = ConfigCache::default();
+
+        configs.insert(
+            GameConfig::Tileset,
+            toml::from_str(SHIPPED_TILESET).unwrap(),
+        );
+        configs.insert(
+            GameConfig::Treasures,
+            toml::from_str(SHIPPED_TREASURES).unwrap(),
+        );
+
+        configs
+    }
+
+    #[test]
+    fn keeps_the_previous_treasures_when_an_edit_is_rejected() {
+        let mut configs = shipped_configs();
+        let shipped = shipped_configs().treasures().unwrap();
+        // Drops every treasure but water, which the tileset still places
+        let edit = toml::from_str::<Table>(
+            "[[treasure]]\nid = \"water\"\nlabel = \"Water\"\nicon = \"res://water.png\"\ncategory = \"water\"\neffects = { water = 1 }\n",
+        )
+        .unwrap();
+
+        assert_matches!(configs.replace(GameConfig::Treasures, edit), Err(_));
+        assert_eq!(configs.treasures().unwrap(), shipped);
+        assert!(configs.tileset().is_ok());
+    }
+
+    #[test]
+    fn swaps_in_an_edit_that_validates() {
+        let mut configs = shipped_configs();
+        let edit = toml::from_str::<Table>(
+            &SHIPPED_TREASURES.replace("label = \"Water x2\"", "label = \"Double water\""),
+        )
+        .unwrap();
+
+        configs.treasures().unwrap();
+
+        assert_eq!(configs.replace(GameConfig::Treasures, edit), Ok(()));
+        assert_eq!(
+            configs
+                .treasures()
+                .unwrap()
+                .get("double_water")
+                .unwrap()
+                .label,
+            "Double water"
+        );
+    }
+}
@@ -0,0 +1,341 @@
+use thiserror::Error;
+use toml::Table;
+use toml::Value;
+
+use crate::util::rules::RulesConfig;
+use crate::util::rules::RulesParseError;
+use crate::util::tileset::TilesetConfig;
+use crate::util::treasures::TreasureCatalog;
+use crate::util::treasures::TreasureConfigError;
+
+/// Each directory in here is a content pack.
+pub const MODS_ROOT: &str = "user://mods/";
+pub const MANIFEST_FILE: &str = "mod.toml";
+pub const TILESET_FILE: &str = "tileset.toml";
+pub const RULES_FILE: &str = "rules.toml";
+pub const TREASURES_FILE: &str = "treasures.toml";
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ModError {
+    #[error("Missing {MANIFEST_FILE}")]
+    MissingManifest,
+    #[error("Expected a {TILESET_FILE}, a {RULES_FILE} or both")]
+    EmptyPack,
+    #[error("Couldn't parse {0}: {1}")]
+    ParseError(&'static str, String),
+    #[error("Invalid {MANIFEST_FILE}: {0}")]
+    ManifestError(&'static str),
+    #[error("Invalid {TILESET_FILE}: {0}")]
+    TilesetError(String),
+    #[error("Invalid {RULES_FILE}: {0}")]
+    RulesError(#[from] RulesParseError),
+    #[error("Invalid {TREASURES_FILE}: {0}")]
+    TreasuresError(#[from] TreasureConfigError),
+    #[error("{TREASURES_FILE} needs a {TILESET_FILE} placing its treasures")]
+    TreasuresWithoutTileset,
+    #[error("Icon {1} of treasure {0} doesn't exist")]
+    MissingIcon(String, String),
+}
+
+/// A pack that can't be played, and why.
+#[derive(Error, Debug, PartialEq)]
+#[error("{directory}: {error}")]
+pub struct InvalidMod {
+    pub directory: String,
+    pub error: ModError,
+}
+
+/// Describes a pack in its `mod.toml`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModManifest {
+    pub name: String,
+    pub version: Option<String>,
+    pub author: Option<String>,
+    pub description: Option<String>,
+}
+
+impl ModManifest {
+    /// The name shown in the lobby, e.g. `Long Dunes 1.2`.
+    pub fn label(&self) -> String {
+        match &self.version {
+            Some(version) => format!("{} {version}", self.name),
+            None => self.name.clone(),
+        }
+    }
+}
+
+impl TryFrom<&Table> for ModManifest {
+    type Error = ModError;
+
+    fn try_from(table: &Table) -> Result<Self, ModError> {
+        let string = |key: &str, error: &'static str| match table.get(key) {
+            None => Ok(None),
+            Some(Value::String(value)) => Ok(Some(value.clone())),
+            Some(_) => Err(ModError::ManifestError(error)),
+        };
+
+        let name = string("name", "name must be a string")?
+            .filter(|name| !name.trim().is_empty())
+            .ok_or(ModError::ManifestError("Expected a name"))?;
+
+        Ok(Self {
+            name,
+            version: string("version", "version must be a string")?,
+            author: string("author", "author must be a string")?,
+            description: string("description", "description must be a string")?,
+        })
+    }
+}
+
+/// The contents of a pack's files, `None` for the ones it doesn't have.
+#[derive(Debug, Clone, Default)]
+pub struct ModFiles {
+    pub manifest: Option<String>,
+    pub tileset: Option<String>,
+    pub rules: Option<String>,
+    pub treasures: Option<String>,
+}
+
+/// A validated content pack from `user://mods/<directory>/`. Whatever it
+/// leaves out is played from the game's own configs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModPack {
+    pub directory: String,
+    pub manifest: ModManifest,
+    pub tileset: Option<TilesetConfig>,
+    pub rules: Option<RulesConfig>,
+    /// Replaces `config/treasures.toml`, with icon paths under the pack
+    pub treasures: Option<TreasureCatalog>,
+}
+
+fn parse(file: &'static str, contents: &str) -> Result<Table, ModError> {
+    toml::from_str::<Table>(contents)
+        .map_err(|error| ModError::ParseError(file, error.message().to_owned()))
+}
+
+impl ModPack {
+    /// Checks a pack the way the game checks its own configs: the tileset
+    /// parses and only places treasures that are defined, either by the pack
+    /// or by `base_treasures`, and the rules parse. Treasure icons relative to
+    /// the pack must stay inside it and satisfy `icon_exists`.
+    pub fn validate(
+        directory: &str,
+        files: &ModFiles,
+        base_treasures: &TreasureCatalog,
+        icon_exists: impl Fn(&str) -> bool,
+    ) -> Result<Self, InvalidMod> {
+        Self::try_validate(directory, files, base_treasures, icon_exists).map_err(|error| {
+            InvalidMod {
+                directory: directory.to_owned(),
+                error,
+            }
+        })
+    }
+    fn try_validate(
+        directory: &str,
+        files: &ModFiles,
+        base_treasures: &TreasureCatalog,
+        icon_exists: impl Fn(&str) -> bool,
+    ) -> Result<Self, ModError> {
+        let manifest = files.manifest.as_ref().ok_or(ModError::MissingManifest)?;
+        let manifest = ModManifest::try_from(&parse(MANIFEST_FILE, manifest)?)?;
+
+        if files.tileset.is_none() && files.rules.is_none() {
+            return Err(ModError::EmptyPack);
+        }
+
+        if files.treasures.is_some() && files.tileset.is_none() {
+            return Err(ModError::TreasuresWithoutTileset);
+        }
+
+        let treasures = match &files.treasures {
+            Some(treasures) => {
+                let catalog = TreasureCatalog::try_from(&parse(TREASURES_FILE, treasures)?)?
+                    .with_icons_under(&format!("{MODS_ROOT}{directory}/"))?;
+
+                for definition in catalog.definitions() {
+                    if !icon_exists(&definition.icon) {
+                        return Err(ModError::MissingIcon(
+                            definition.id.clone(),
+                            definition.icon.clone(),
+                        ));
+                    }
+                }
+
+                Some(catalog)
+            }
+            None => None,
+        };
+
+        let tileset = match &files.tileset {
+            Some(tileset) => {
+                let tileset = TilesetConfig::try_from(&parse(TILESET_FILE, tileset)?)
+                    .map_err(|error| ModError::TilesetError(error.to_owned()))?;
+
+                treasures
+                    .as_ref()
+                    .unwrap_or(base_treasures)
+                    .validate(tileset.treasure_references())
+                    .map_err(|error| ModError::TilesetError(error.to_string()))?;
+
+                Some(tileset)
+            }
+            None => None,
+        };
+
+        let rules = match &files.rules {
+            Some(rules) => Some(RulesConfig::try_from(&parse(RULES_FILE, rules)?)?),
+            None => None,
+        };
+
+        Ok(Self {
+            directory: directory.to_owned(),
+            manifest,
+            tileset,
+            rules,
+            treasures,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+    use test_case::test_case;
+
+    use super::*;
+
+    const TILESET: &str = include_str!("../../../godot/config/tileset.toml");
+    const TREASURES: &str = include_str!("../../../godot/config/treasures.toml");
+    const RULES: &str = include_str!("../../../godot/config/rules.toml");
+    const MANIFEST: &str = "name = \"Long Dunes\"\nversion = \"1.2\"";
+
+    fn base_treasures() -> TreasureCatalog {
+        TreasureCatalog::try_from(&toml::from_str::<Table>(TREASURES).unwrap()).unwrap()
+    }
+
+    fn files(tileset: Option<&str>, rules: Option<&str>, treasures: Option<&str>) -> ModFiles {
+        ModFiles {
+            manifest: Some(MANIFEST.to_owned()),
+            tileset: tileset.map(str::to_owned),
+            rules: rules.map(str::to_owned),
+            treasures: treasures.map(str::to_owned),
+        }
+    }
+
+    fn validate(files: &ModFiles) -> Result<ModPack, ModError> {
+        ModPack::validate("dunes", files, &base_treasures(), |_| true).map_err(|e| e.error)
+    }
+
+    #[test]
+    fn accepts_a_pack_of_the_game_configs() {
+        let pack = validate(&files(Some(TILESET), Some(RULES), Some(TREASURES))).unwrap();
+
+        assert_eq!(pack.manifest.label(), "Long Dunes 1.2");
+        assert!(pack.tileset.is_some());
+        assert!(pack.rules.is_some());
+    }
+
+    #[test]
+    fn leaves_out_what_the_pack_doesnt_have() {
+        let pack = validate(&files(None, Some("[movement]"), None)).unwrap();
+
+        assert_eq!(pack.tileset, None);
+        assert_eq!(pack.treasures, None);
+    }
+
+    #[test]
+    fn resolves_treasure_icons_inside_the_pack() {
+        let treasures = TREASURES.replacen("icon = \"res://assets/", "icon = \"icons/", 1);
+        let pack = validate(&files(Some(TILESET), None, Some(&treasures))).unwrap();
+        let icons: Vec<&str> = pack
+            .treasures
+            .as_ref()
+            .unwrap()
+            .definitions()
+            .iter()
+            .map(|definition| definition.icon.as_str())
+            .collect();
+
+        assert!(icons[0].starts_with("user://mods/dunes/icons/"));
+        assert!(icons[1..].iter().all(|icon| icon.starts_with("res://")));
+    }
+
+    #[test]
+    fn rejects_icons_outside_the_pack() {
+        let treasures = TREASURES.replacen("icon = \"res://assets/", "icon = \"../other/", 1);
+
+        assert_matches!(
+            validate(&files(Some(TILESET), None, Some(&treasures))),
+            Err(ModError::TreasuresError(TreasureConfigError::IconOutsideDirectory(_, icon)))
+                if icon.starts_with("../other/")
+        );
+    }
+
+    #[test]
+    fn reports_missing_icons() {
+        let treasures = TREASURES.replacen("icon = \"res://assets/", "icon = \"icons/", 1);
+        let result = ModPack::validate(
+            "dunes",
+            &files(Some(TILESET), None, Some(&treasures)),
+            &base_treasures(),
+            |icon| !icon.starts_with(MODS_ROOT),
+        );
+
+        assert_matches!(
+            result,
+            Err(InvalidMod { error: ModError::MissingIcon(_, icon), .. })
+                if icon.starts_with("user://mods/dunes/icons/")
+        );
+    }
+
+    #[test_case(None, Some(TILESET), None, None => matches Err(ModError::MissingManifest) ; "no manifest")]
+    #[test_case(Some("version = \"1\""), Some(TILESET), None, None => matches Err(ModError::ManifestError(_)) ; "no name")]
+    #[test_case(Some("name = 1"), Some(TILESET), None, None => matches Err(ModError::ManifestError(_)) ; "name not a string")]
+    #[test_case(Some(MANIFEST), None, None, None => matches Err(ModError::EmptyPack) ; "empty pack")]
+    #[test_case(Some(MANIFEST), Some("[cross"), None, None => matches Err(ModError::ParseError(TILESET_FILE, _)) ; "broken toml")]
+    #[test_case(Some(MANIFEST), Some("[cross.c]"), None, None => matches Err(ModError::TilesetError(_)) ; "incomplete tileset")]
+    #[test_case(Some(MANIFEST), None, Some("[movement]\noasis_crossings = -1"), None => matches Err(ModError::RulesError(_)) ; "invalid rules")]
+    #[test_case(Some(MANIFEST), None, Some(RULES), Some(TREASURES) => matches Err(ModError::TreasuresWithoutTileset) ; "treasures without tileset")]
+    #[test_case(Some(MANIFEST), Some(TILESET), None, Some("[[treasure]]") => matches Err(ModError::TreasuresError(_)) ; "invalid treasures")]
+    fn rejects_invalid_packs(
+        manifest: Option<&str>,
+        tileset: Option<&str>,
+        rules: Option<&str>,
+        treasures: Option<&str>,
+    ) -> Result<ModPack, ModError> {
+        let files = ModFiles {
+            manifest: manifest.map(str::to_owned),
+            ..files(tileset, rules, treasures)
+        };
+
+        validate(&files)
+    }
+
+    #[test]
+    fn checks_the_tileset_against_the_packs_treasures() {
+        let undefined = TILESET.replacen("\"goods:salt\"", "\"goods:gold\"", 1);
+
+        assert_matches!(
+            validate(&files(Some(&undefined), None, None)),
+            Err(ModError::TilesetError(_))
+        );
+
+        let treasures = format!(
+            "{TREASURES}\n[[treasure]]\nid = \"goods:gold\"\nlabel = \"Gold\"\nicon = \"gold.png\"\ncategory = \"goods\"\n"
+        );
+
+        assert_matches!(
+            validate(&files(Some(&undefined), None, Some(&treasures))),
+            Ok(_)
+        );
+    }
+
+    #[test]
+    fn names_the_pack_in_its_error() {
+        let error = ModPack::validate("dunes", &ModFiles::default(), &base_treasures(), |_| true)
+            .unwrap_err();
+
+        assert_eq!(error.to_string(), "dunes: Missing mod.toml");
+    }
+}
@@ -0,0 +1,276 @@
+use std::f32::consts::PI;
+
+use thiserror::Error;
+
+use crate::game::entities::player::PlayerName;
+use crate::util::settings::ColorblindPalette;
+
+const MARKER_CIRCLE_SEGMENTS: usize = 24;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum RgbParseError {
+    #[error("Expected color as #rrggbb, received {0}")]
+    Format(String),
+}
+
+/// An opaque 8-bit sRGB color. Kept free of Godot types so palettes can be
+/// parsed from settings and tested without the engine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    pub fn to_hex(self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+}
+
+impl TryFrom<&str> for Rgb {
+    type Error = RgbParseError;
+
+    fn try_from(value: &str) -> Result<Self, RgbParseError> {
+        let error = || RgbParseError::Format(value.to_owned());
+
+        let hex = value.strip_prefix('#').ok_or_else(error)?;
+
+        // `from_str_radix` alone would also accept a sign, e.g. `#+f+f+f`
+        if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(error());
+        }
+
+        let channel = |idx: usize| u8::from_str_radix(&hex[idx..idx + 2], 16).map_err(|_| error());
+
+        Ok(Rgb(channel(0)?, channel(2)?, channel(4)?))
+    }
+}
+
+// Indexed by `PlayerName as usize`: White, Orange, Red, Blue
+const CLASSIC: [Rgb; 4] = [
+    Rgb(255, 255, 255),
+    Rgb(255, 128, 0),
+    Rgb(255, 0, 0),
+    Rgb(0, 0, 255),
+];
+// Picked from the Okabe-Ito and Tol sets. They keep players apart along the
+// blue-yellow axis and by lightness, which red-green colorblindness leaves
+// intact.
+const DEUTERANOPIA: [Rgb; 4] = [
+    Rgb(255, 255, 255),
+    Rgb(240, 228, 66),
+    Rgb(213, 94, 0),
+    Rgb(0, 114, 178),
+];
+const PROTANOPIA: [Rgb; 4] = [
+    Rgb(255, 255, 255),
+    Rgb(230, 159, 0),
+    Rgb(136, 34, 85),
+    Rgb(0, 114, 178),
+];
+
+pub const DEFAULT_CUSTOM_COLORS: [Rgb; 4] = CLASSIC;
+
+/// The colors players are drawn in for the selected palette.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerPalette {
+    colors: [Rgb; 4],
+}
+
+impl PlayerPalette {
+    pub fn new(palette: ColorblindPalette, custom_colors: &[Rgb; 4]) -> Self {
+        let colors = match palette {
+            ColorblindPalette::Classic => CLASSIC,
+            ColorblindPalette::Deuteranopia => DEUTERANOPIA,
+            ColorblindPalette::Protanopia => PROTANOPIA,
+            ColorblindPalette::Custom => *custom_colors,
+        };
+
+        Self { colors }
+    }
+    pub fn color(&self, player: PlayerName) -> Rgb {
+        self.colors[player as usize]
+    }
+}
+
+/// A shape drawn on tokens and buildings, so players can be told apart
+/// without relying on color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarkerShape {
+    Circle,
+    Square,
+    Triangle,
+    Diamond,
+}
+
+impl From<PlayerName> for MarkerShape {
+    fn from(value: PlayerName) -> Self {
+        match value {
+            PlayerName::White => MarkerShape::Circle,
+            PlayerName::Orange => MarkerShape::Square,
+            PlayerName::Red => MarkerShape::Triangle,
+            PlayerName::Blue => MarkerShape::Diamond,
+        }
+    }
+}
+
+impl MarkerShape {
+    /// Outline of the shape centered on the origin, fitting in a circle of
+    /// `radius`. Points run clockwise in screen space (y pointing down).
+    pub fn polygon(&self, radius: f32) -> Vec<(f32, f32)> {
+        let regular = |corners: usize, start_angle: f32| -> Vec<(f32, f32)> {
+            (0..corners)
+                .map(|idx| {
+                    let angle = start_angle + 2. * PI * idx as f32 / corners as f32;
+
+                    (radius * angle.cos(), radius * angle.sin())
+                })
+                .collect()
+        };
+
+        match self {
+            MarkerShape::Circle => regular(MARKER_CIRCLE_SEGMENTS, 0.),
+            MarkerShape::Square => regular(4, PI / 4.),
+            MarkerShape::Triangle => regular(3, -PI / 2.),
+            MarkerShape::Diamond => regular(4, -PI / 2.),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::entities::player::PlayerName;
+
+    const PLAYERS: [PlayerName; 4] = [
+        PlayerName::White,
+        PlayerName::Orange,
+        PlayerName::Red,
+        PlayerName::Blue,
+    ];
+
+    // Viénot, Brettel & Mollon (1999) dichromat projections in linear RGB
+    const DEUTERANOPIA_SIMULATION: [[f32; 3]; 3] = [
+        [0.29275, 0.70725, 0.],
+        [0.29275, 0.70725, 0.],
+        [-0.02234, 0.02234, 1.],
+    ];
+    const PROTANOPIA_SIMULATION: [[f32; 3]; 3] = [
+        [0.11238, 0.88762, 0.],
+        [0.11238, 0.88762, 0.],
+        [0.00401, -0.00401, 1.],
+    ];
+
+    fn to_linear(channel: u8) -> f32 {
+        let c = channel as f32 / 255.;
+
+        if c <= 0.04045 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    }
+
+    fn to_srgb(c: f32) -> f32 {
+        let c = c.clamp(0., 1.);
+
+        if c <= 0.0031308 {
+            c * 12.92
+        } else {
+            1.055 * c.powf(1. / 2.4) - 0.055
+        }
+    }
+
+    fn simulate(color: Rgb, matrix: &[[f32; 3]; 3]) -> [f32; 3] {
+        let linear = [to_linear(color.0), to_linear(color.1), to_linear(color.2)];
+
+        matrix.map(|row| to_srgb(row.iter().zip(linear).map(|(m, c)| m * c).sum()))
+    }
+
+    /// Smallest distance between two players' colors as a dichromat sees
+    /// them, in sRGB units from 0 to sqrt(3).
+    fn closest_pair(palette: &PlayerPalette, matrix: &[[f32; 3]; 3]) -> f32 {
+        let seen: Vec<[f32; 3]> = PLAYERS
+            .iter()
+            .map(|p| simulate(palette.color(*p), matrix))
+            .collect();
+
+        let mut closest = f32::MAX;
+
+        for (idx, a) in seen.iter().enumerate() {
+            for b in &seen[idx + 1..] {
+                let distance = a
+                    .iter()
+                    .zip(b)
+                    .map(|(a, b)| (a - b).powi(2))
+                    .sum::<f32>()
+                    .sqrt();
+
+                closest = closest.min(distance);
+            }
+        }
+
+        closest
+    }
+
+    #[test]
+    fn parses_hex_colors() {
+        assert_eq!(Rgb::try_from("#ff8000"), Ok(Rgb(255, 128, 0)));
+        assert_eq!(Rgb::try_from("#FF8000"), Ok(Rgb(255, 128, 0)));
+        assert_eq!(Rgb(0, 114, 178).to_hex(), "#0072b2");
+        assert!(Rgb::try_from("ff8000").is_err());
+        assert!(Rgb::try_from("#ff80").is_err());
+        assert!(Rgb::try_from("#gg8000").is_err());
+        assert!(Rgb::try_from("#+f+f+f").is_err());
+        assert!(Rgb::try_from("#ff80+0").is_err());
+    }
+
+    #[test]
+    fn the_classic_orange_and_red_are_hard_to_tell_apart() {
+        let classic = PlayerPalette::new(ColorblindPalette::Classic, &DEFAULT_CUSTOM_COLORS);
+
+        assert!(closest_pair(&classic, &DEUTERANOPIA_SIMULATION) < 0.2);
+    }
+
+    #[test]
+    fn colorblind_palettes_keep_every_player_distinct() {
+        let classic = PlayerPalette::new(ColorblindPalette::Classic, &DEFAULT_CUSTOM_COLORS);
+
+        for (palette, simulation) in [
+            (ColorblindPalette::Deuteranopia, DEUTERANOPIA_SIMULATION),
+            (ColorblindPalette::Protanopia, PROTANOPIA_SIMULATION),
+        ] {
+            let palette = PlayerPalette::new(palette, &DEFAULT_CUSTOM_COLORS);
+            let closest = closest_pair(&palette, &simulation);
+
+            assert!(closest > 0.4);
+            assert!(closest > closest_pair(&classic, &simulation));
+        }
+    }
+
+    #[test]
+    fn the_custom_palette_uses_the_custom_colors() {
+        let custom_colors = [Rgb(1, 2, 3), Rgb(4, 5, 6), Rgb(7, 8, 9), Rgb(10, 11, 12)];
+        let palette = PlayerPalette::new(ColorblindPalette::Custom, &custom_colors);
+
+        assert_eq!(palette.color(PlayerName::Red), Rgb(7, 8, 9));
+    }
+
+    #[test]
+    fn every_player_has_a_distinct_marker() {
+        let shapes: Vec<MarkerShape> = PLAYERS.iter().map(|p| MarkerShape::from(*p)).collect();
+
+        for (idx, shape) in shapes.iter().enumerate() {
+            assert!(!shapes[idx + 1..].contains(shape));
+        }
+    }
+
+    #[test]
+    fn marker_polygons_fit_their_radius() {
+        for player in PLAYERS {
+            let polygon = MarkerShape::from(player).polygon(10.);
+
+            assert!(polygon.len() >= 3);
+            assert!(polygon
+                .iter()
+                .all(|(x, y)| (x * x + y * y).sqrt() <= 10. + f32::EPSILON * 10.));
+        }
+    }
+}
@@ -0,0 +1,538 @@
+use thiserror::Error;
+use toml::Table;
+use toml::Value;
+
+use crate::game::entities::movement::InteractionRule;
+use crate::game::entities::movement::BOARD_SIZE;
+use crate::game::entities::movement::DEFAULT_OASIS_CROSSINGS;
+use crate::game::entities::movement::STARTING_POSITIONS;
+use crate::game::entities::player::PlayerName;
+use crate::game::entities::turn::DesertReward;
+use crate::game::entities::turn::ExtraMoveRules;
+
+pub const OASIS_CROSSINGS_RANGE: (i64, i64) = (0, 4);
+pub const WATER_TOLL_RANGE: (i64, i64) = (0, 10);
+// 0 means no limit
+pub const MAX_CHAIN_RANGE: (i64, i64) = (0, u8::MAX as i64);
+const DEFAULT_WATER_TOLL: u32 = 1;
+// Keys of `[start]`, in `PlayerName` order
+const PLAYER_KEYS: [&str; 4] = ["white", "orange", "red", "blue"];
+const PLAYERS: [PlayerName; 4] = [
+    PlayerName::White,
+    PlayerName::Orange,
+    PlayerName::Red,
+    PlayerName::Blue,
+];
+// The arms of the starting cross run along these rows and columns
+const CROSS_LINE: u8 = BOARD_SIZE / 2;
+
+type StartingPositions = [((u8, u8), PlayerName); 4];
+
+#[derive(Error, Debug, PartialEq)]
+pub enum RulesParseError {
+    #[error("Rule {0} expected one of {1}, received {2}")]
+    UnknownVariant(&'static str, &'static str, String),
+    #[error("Rule {0} must be between {1} and {2}, received {3}")]
+    OutOfRange(&'static str, i64, i64, i64),
+    #[error("Rule {0} has the wrong type, expected {1}")]
+    WrongType(String, &'static str),
+    #[error("Starting position start.{0} must be on the starting cross, received {1:?}")]
+    OffCrossStartingPosition(&'static str, (i64, i64)),
+    #[error("{0:?} and {1:?} can't start on the same tile")]
+    SharedStartingPosition(PlayerName, PlayerName),
+    #[error("Rule decks.order must list decks by their number from 1, each at most once, received {0:?}")]
+    InvalidDeckOrder(Vec<i64>),
+}
+
+/// The rule variants a game is played with, loaded from `config/rules.toml`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RulesConfig {
+    /// Tile each caravan starts on, in `PlayerName` order
+    pub starting_positions: StartingPositions,
+    /// Oasis (white) lines a single move may cross. Crossing the last one
+    /// ends the move.
+    pub oasis_crossings: u8,
+    pub interaction: InteractionRule,
+    pub extra_moves: ExtraMoveRules,
+    /// Deck indices drawn from first, in this order. Decks left out are
+    /// drawn from after them, in tileset order.
+    pub deck_order: Vec<u8>,
+}
+
+impl Default for RulesConfig {
+    fn default() -> Self {
+        Self {
+            starting_positions: STARTING_POSITIONS,
+            oasis_crossings: DEFAULT_OASIS_CROSSINGS,
+            interaction: InteractionRule::default(),
+            extra_moves: ExtraMoveRules::default(),
+            deck_order: vec![],
+        }
+    }
+}
+
+impl RulesConfig {
+    pub fn starting_position(&self, player: PlayerName) -> (u8, u8) {
+        self.starting_positions[player as usize].0
+    }
+    /// Every index of a tileset with `deck_count` decks, in the order the
+    /// decks are drawn from. Listed decks the tileset doesn't have are left
+    /// out.
+    pub fn draw_order(&self, deck_count: usize) -> Vec<u8> {
+        let listed = self
+            .deck_order
+            .iter()
+            .copied()
+            .filter(|deck| (*deck as usize) < deck_count);
+        let unlisted = (0..deck_count)
+            .map(|deck| deck as u8)
+            .filter(|deck| !self.deck_order.contains(deck));
+
+        listed.chain(unlisted).collect()
+    }
+    /// The deck to draw from: the first deck in draw order that still has
+    /// tiles and isn't locked. `unlocks_after` holds, for each deck, the deck
+    /// that must be empty before it can be drawn from.
+    pub fn active_deck(&self, unlocks_after: &[Option<u8>], exhausted: &[bool]) -> Option<u8> {
+        let is_exhausted = |deck: u8| exhausted.get(deck as usize).copied().unwrap_or(true);
+
+        self.draw_order(exhausted.len()).into_iter().find(|&deck| {
+            !is_exhausted(deck)
+                && unlocks_after
+                    .get(deck as usize)
+                    .copied()
+                    .flatten()
+                    .is_none_or(is_exhausted)
+        })
+    }
+}
+
+fn section<'a>(key: &str, table: &'a Table) -> Option<&'a Table> {
+    table.get(key).and_then(|v| v.as_table())
+}
+
+fn integer_in_range(
+    key: &'static str,
+    table: Option<&Table>,
+    range: (i64, i64),
+    default: i64,
+) -> Result<i64, RulesParseError> {
+    let Some(value) = table.and_then(|t| t.get(key)) else {
+        return Ok(default);
+    };
+
+    let value = value
+        .as_integer()
+        .ok_or(RulesParseError::WrongType(key.to_owned(), "an integer"))?;
+
+    if value < range.0 || value > range.1 {
+        return Err(RulesParseError::OutOfRange(key, range.0, range.1, value));
+    }
+
+    Ok(value)
+}
+
+fn string_rule(
+    key: &'static str,
+    table: Option<&Table>,
+) -> Result<Option<String>, RulesParseError> {
+    table
+        .and_then(|t| t.get(key))
+        .map(|value| {
+            value
+                .as_str()
+                .map(str::to_owned)
+                .ok_or(RulesParseError::WrongType(key.to_owned(), "a string"))
+        })
+        .transpose()
+}
+
+fn integer_pair(key: String, value: &Value) -> Result<(i64, i64), RulesParseError> {
+    let error = || RulesParseError::WrongType(key.clone(), "an array of 2 integers");
+
+    match value.as_array().map(Vec::as_slice) {
+        Some([x, y]) => Ok((
+            x.as_integer().ok_or_else(error)?,
+            y.as_integer().ok_or_else(error)?,
+        )),
+        _ => Err(error()),
+    }
+}
+
+fn starting_positions(
+    table: Option<&Table>,
+    defaults: StartingPositions,
+) -> Result<StartingPositions, RulesParseError> {
+    let mut positions = defaults;
+
+    if let Some(table) = table {
+        for (idx, key) in PLAYER_KEYS.iter().enumerate() {
+            let Some(value) = table.get(*key) else {
+                continue;
+            };
+
+            let (x, y) = integer_pair(format!("start.{key}"), value)?;
+            let on_board =
+                (0..BOARD_SIZE as i64).contains(&x) && (0..BOARD_SIZE as i64).contains(&y);
+
+            if !on_board || (x != CROSS_LINE as i64 && y != CROSS_LINE as i64) {
+                return Err(RulesParseError::OffCrossStartingPosition(key, (x, y)));
+            }
+
+            positions[idx] = ((x as u8, y as u8), PLAYERS[idx]);
+        }
+    }
+
+    for (idx, (coordinates, player)) in positions.iter().enumerate() {
+        if let Some((_, other)) = positions[idx + 1..].iter().find(|(c, _)| c == coordinates) {
+            return Err(RulesParseError::SharedStartingPosition(*player, *other));
+        }
+    }
+
+    Ok(positions)
+}
+
+fn deck_order(table: Option<&Table>, defaults: Vec<u8>) -> Result<Vec<u8>, RulesParseError> {
+    let Some(value) = table.and_then(|t| t.get("order")) else {
+        return Ok(defaults);
+    };
+
+    let numbers: Vec<i64> = value
+        .as_array()
+        .and_then(|values| values.iter().map(Value::as_integer).collect())
+        .ok_or(RulesParseError::WrongType(
+            "decks.order".to_owned(),
+            "an array of integers",
+        ))?;
+
+    // Decks are numbered from 1 in the file, as on their labels
+    let mut sorted = numbers.clone();
+    sorted.sort();
+    sorted.dedup();
+
+    let is_deck_number = |number: &i64| (1..=u8::MAX as i64).contains(number);
+
+    if sorted.len() != numbers.len() || !numbers.iter().all(is_deck_number) {
+        return Err(RulesParseError::InvalidDeckOrder(numbers));
+    }
+
+    Ok(numbers.iter().map(|number| (number - 1) as u8).collect())
+}
+
+impl TryFrom<&Table> for RulesConfig {
+    type Error = RulesParseError;
+
+    /// Missing keys keep their default value, so a partial or empty file is
+    /// valid. Keys that are present must hold a valid value.
+    fn try_from(table: &Table) -> Result<Self, RulesParseError> {
+        let defaults = RulesConfig::default();
+
+        let start = section("start", table);
+        let movement = section("movement", table);
+        let desert = section("desert", table);
+        let decks = section("decks", table);
+
+        let starting_positions = starting_positions(start, defaults.starting_positions)?;
+
+        let oasis_crossings = integer_in_range(
+            "oasis_crossings",
+            movement,
+            OASIS_CROSSINGS_RANGE,
+            defaults.oasis_crossings as i64,
+        )? as u8;
+        let water_toll = integer_in_range(
+            "water_toll",
+            movement,
+            WATER_TOLL_RANGE,
+            DEFAULT_WATER_TOLL as i64,
+        )? as u32;
+        let interaction = string_rule("interaction", movement)?
+            .map(|name| {
+                InteractionRule::from_name(&name, water_toll).map_err(|_| {
+                    RulesParseError::UnknownVariant(
+                        "interaction",
+                        "pass_through, blocking, water_toll, displacing",
+                        name.clone(),
+                    )
+                })
+            })
+            .transpose()?
+            .unwrap_or(defaults.interaction);
+
+        let max_chain = integer_in_range(
+            "max_chain",
+            desert,
+            MAX_CHAIN_RANGE,
+            defaults.extra_moves.max_chain.unwrap_or(0) as i64,
+        )?;
+        let reward = string_rule("reward", desert)?
+            .map(|name| {
+                DesertReward::from_name(&name).map_err(|_| {
+                    RulesParseError::UnknownVariant("reward", "move, explore", name.clone())
+                })
+            })
+            .transpose()?
+            .unwrap_or(defaults.extra_moves.reward);
+
+        let deck_order = deck_order(decks, defaults.deck_order)?;
+
+        Ok(Self {
+            starting_positions,
+            oasis_crossings,
+            interaction,
+            extra_moves: ExtraMoveRules {
+                max_chain: (max_chain > 0).then_some(max_chain as u8),
+                reward,
+            },
+            deck_order,
+        })
+    }
+}
+
+impl From<&RulesConfig> for Table {
+    fn from(rules: &RulesConfig) -> Self {
+        let mut start = Table::new();
+        for (idx, key) in PLAYER_KEYS.iter().enumerate() {
+            let (x, y) = rules.starting_positions[idx].0;
+
+            start.insert(
+                key.to_string(),
+                Value::Array(vec![Value::Integer(x as i64), Value::Integer(y as i64)]),
+            );
+        }
+
+        let mut movement = Table::new();
+        movement.insert(
+            "oasis_crossings".into(),
+            Value::Integer(rules.oasis_crossings as i64),
+        );
+        movement.insert(
+            "interaction".into(),
+            Value::String(rules.interaction.name().to_owned()),
+        );
+        if let InteractionRule::WaterToll(toll) = rules.interaction {
+            movement.insert("water_toll".into(), Value::Integer(toll as i64));
+        }
+
+        let mut desert = Table::new();
+        desert.insert(
+            "max_chain".into(),
+            Value::Integer(rules.extra_moves.max_chain.unwrap_or(0) as i64),
+        );
+        desert.insert(
+            "reward".into(),
+            Value::String(rules.extra_moves.reward.name().to_owned()),
+        );
+
+        let mut decks = Table::new();
+
+        // Left out, every deck is drawn from in tileset order
+        if !rules.deck_order.is_empty() {
+            decks.insert(
+                "order".into(),
+                Value::Array(
+                    rules
+                        .deck_order
+                        .iter()
+                        .map(|idx| Value::Integer(*idx as i64 + 1))
+                        .collect(),
+                ),
+            );
+        }
+
+        let mut table = Table::new();
+        table.insert("start".into(), Value::Table(start));
+        table.insert("movement".into(), Value::Table(movement));
+        table.insert("desert".into(), Value::Table(desert));
+        table.insert("decks".into(), Value::Table(decks));
+
+        table
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+    use test_case::test_case;
+    use toml::Table;
+
+    use super::*;
+
+    fn parse(input: &str) -> Result<RulesConfig, RulesParseError> {
+        let table = toml::from_str::<Table>(input).unwrap();
+
+        RulesConfig::try_from(&table)
+    }
+
+    #[test]
+    fn an_empty_file_yields_the_defaults() {
+        assert_eq!(parse(""), Ok(RulesConfig::default()));
+    }
+
+    #[test]
+    fn the_shipped_rules_are_the_defaults() {
+        let shipped = include_str!("../../../godot/config/rules.toml");
+
+        assert_eq!(parse(shipped), Ok(RulesConfig::default()));
+    }
+
+    #[test]
+    fn parses_every_section() {
+        let rules = parse(
+            r#"
+[start]
+white = [5, 9]
+
+[movement]
+oasis_crossings = 2
+interaction = "water_toll"
+water_toll = 3
+
+[desert]
+max_chain = 2
+reward = "explore"
+
+[decks]
+order = [2, 1, 3, 5, 4]
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(rules.starting_position(PlayerName::White), (5, 9));
+        assert_eq!(rules.starting_position(PlayerName::Red), (5, 0));
+        assert_eq!(rules.oasis_crossings, 2);
+        assert_eq!(rules.interaction, InteractionRule::WaterToll(3));
+        assert_eq!(rules.extra_moves.max_chain, Some(2));
+        assert_eq!(rules.extra_moves.reward, DesertReward::ExtraExplore);
+        assert_eq!(rules.deck_order, vec![1, 0, 2, 4, 3]);
+    }
+
+    #[test]
+    fn rejects_out_of_range_values() {
+        assert_matches!(
+            parse("[movement]\noasis_crossings = 5"),
+            Err(RulesParseError::OutOfRange("oasis_crossings", _, _, 5))
+        );
+        assert_matches!(
+            parse("[desert]\nmax_chain = -1"),
+            Err(RulesParseError::OutOfRange("max_chain", _, _, -1))
+        );
+        assert_matches!(
+            parse("[desert]\nmax_chain = \"2\""),
+            Err(RulesParseError::WrongType(_, _))
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_variants() {
+        assert_matches!(
+            parse("[movement]\ninteraction = \"ramming\""),
+            Err(RulesParseError::UnknownVariant("interaction", _, _))
+        );
+        assert_matches!(
+            parse("[desert]\nreward = \"treasure\""),
+            Err(RulesParseError::UnknownVariant("reward", _, _))
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_starting_positions() {
+        assert_matches!(
+            parse("[start]\nwhite = [1, 1]"),
+            Err(RulesParseError::OffCrossStartingPosition("white", (1, 1)))
+        );
+        assert_matches!(
+            parse("[start]\nwhite = [5, 11]"),
+            Err(RulesParseError::OffCrossStartingPosition("white", (5, 11)))
+        );
+        assert_matches!(
+            parse("[start]\nwhite = [5]"),
+            Err(RulesParseError::WrongType(_, _))
+        );
+        assert_eq!(
+            parse("[start]\nwhite = [5, 0]"),
+            Err(RulesParseError::SharedStartingPosition(
+                PlayerName::White,
+                PlayerName::Red
+            ))
+        );
+    }
+
+    #[test]
+    fn rejects_deck_orders_listing_a_deck_twice_or_deck_0() {
+        for order in ["[1, 1, 2, 3, 4]", "[0, 1, 2, 3, 4]", "[1, 256]"] {
+            assert_matches!(
+                parse(&format!("[decks]\norder = {order}")),
+                Err(RulesParseError::InvalidDeckOrder(_))
+            );
+        }
+    }
+
+    #[test]
+    fn draws_decks_in_order_until_exhausted() {
+        let rules = RulesConfig {
+            deck_order: vec![1, 0, 2, 4, 3],
+            ..Default::default()
+        };
+        let mut exhausted = [false; 5];
+
+        assert_eq!(rules.active_deck(&[None; 5], &exhausted), Some(1));
+
+        exhausted[1] = true;
+        assert_eq!(rules.active_deck(&[None; 5], &exhausted), Some(0));
+
+        exhausted = [true; 5];
+        assert_eq!(rules.active_deck(&[None; 5], &exhausted), None);
+    }
+
+    #[test_case(vec![], 3 => vec![0, 1, 2] ; "without an order decks go in tileset order")]
+    #[test_case(vec![2, 0], 4 => vec![2, 0, 1, 3] ; "unlisted decks follow the listed ones")]
+    #[test_case(vec![4, 1, 0], 2 => vec![1, 0] ; "decks the tileset lacks are skipped")]
+    fn draw_order(deck_order: Vec<u8>, deck_count: usize) -> Vec<u8> {
+        let rules = RulesConfig {
+            deck_order,
+            ..Default::default()
+        };
+
+        rules.draw_order(deck_count)
+    }
+
+    #[test]
+    fn locked_decks_are_skipped_until_unlocked() {
+        let rules = RulesConfig::default();
+        // Deck 1 unlocks after deck 3 is empty
+        let unlocks_after = [Some(2), None, None, None, None];
+        let mut exhausted = [false; 5];
+
+        assert_eq!(rules.active_deck(&unlocks_after, &exhausted), Some(1));
+
+        exhausted[1] = true;
+        assert_eq!(rules.active_deck(&unlocks_after, &exhausted), Some(2));
+
+        exhausted[2] = true;
+        assert_eq!(rules.active_deck(&unlocks_after, &exhausted), Some(0));
+    }
+
+    #[test]
+    fn survives_a_round_trip_through_toml() {
+        let rules = RulesConfig {
+            oasis_crossings: 0,
+            interaction: InteractionRule::WaterToll(2),
+            extra_moves: ExtraMoveRules {
+                max_chain: Some(3),
+                reward: DesertReward::ExtraExplore,
+            },
+            deck_order: vec![4, 3, 2, 1, 0],
+            ..Default::default()
+        };
+
+        let table = Table::from(&rules);
+        let serialized = toml::to_string(&table).unwrap();
+
+        assert_eq!(parse(&serialized), Ok(rules));
+
+        let defaults = toml::to_string(&Table::from(&RulesConfig::default())).unwrap();
+
+        assert_eq!(parse(&defaults), Ok(RulesConfig::default()));
+    }
+}
@@ -0,0 +1,404 @@
+use thiserror::Error;
+use toml::Table;
+use toml::Value;
+
+use crate::game::entities::board_model::BoardModel;
+use crate::game::entities::board_model::BoardModelError;
+use crate::game::entities::movement::BOARD_SIZE;
+use crate::util::flags::CardinalDirectionFlags;
+use crate::util::rules::RulesConfig;
+use crate::util::rules::RulesParseError;
+use crate::util::tileset::CrossConfig;
+
+const NO_DIRECTIONS: &str = "-";
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ScenarioError {
+    #[error("Invalid rules: {0}")]
+    RulesError(#[from] RulesParseError),
+    #[error("{0} has the wrong type, expected {1}")]
+    WrongType(&'static str, &'static str),
+    #[error("Tile {0}: {1}")]
+    InvalidTile(usize, &'static str),
+    #[error("Action {0}: {1}")]
+    InvalidAction(usize, &'static str),
+    #[error("Action {0}: {1}")]
+    IllegalAction(usize, BoardModelError),
+}
+
+/// A tile on the board before the first action.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScenarioTile {
+    pub coordinates: (u8, u8),
+    pub oasis_directions: CardinalDirectionFlags,
+    pub treasure_directions: CardinalDirectionFlags,
+}
+
+/// A step of the active player, written as e.g. `move 5 8`, `skip-move` or
+/// `explore 4 8 oasis=NE treasures=N desert`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScenarioAction {
+    Move((u8, u8)),
+    SkipMove,
+    Explore {
+        coordinates: (u8, u8),
+        oasis_directions: CardinalDirectionFlags,
+        treasure_directions: CardinalDirectionFlags,
+        is_desert: bool,
+    },
+}
+
+/// A board and the actions played on it, read from a scenario file:
+///
+/// ```toml
+/// cross = true
+/// actions = ["move 5 8", "explore 4 8 oasis=E desert"]
+///
+/// [rules.movement]
+/// oasis_crossings = 2
+///
+/// [[tile]]
+/// at = [4, 4]
+/// oasis = "NE"
+/// treasures = "N"
+/// ```
+///
+/// `rules` is laid out like `config/rules.toml`, and `cross` places the
+/// tileset's starting cross before the listed tiles, which replace the cross
+/// tiles they land on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scenario {
+    pub rules: RulesConfig,
+    pub cross: bool,
+    pub tiles: Vec<ScenarioTile>,
+    pub actions: Vec<ScenarioAction>,
+}
+
+/// Sides written as letters, e.g. `NE`, or `-` for none.
+fn directions(value: &str) -> Option<CardinalDirectionFlags> {
+    if value == NO_DIRECTIONS {
+        return Some(CardinalDirectionFlags::empty());
+    }
+
+    value
+        .chars()
+        .map(|side| match side {
+            'N' => Some(CardinalDirectionFlags::N),
+            'E' => Some(CardinalDirectionFlags::E),
+            'S' => Some(CardinalDirectionFlags::S),
+            'W' => Some(CardinalDirectionFlags::W),
+            _ => None,
+        })
+        .try_fold(CardinalDirectionFlags::empty(), |flags, side| {
+            Some(flags | side?)
+        })
+}
+
+fn coordinate(value: &str) -> Option<u8> {
+    value.parse::<i64>().ok().and_then(coordinate_value)
+}
+
+fn coordinate_value(value: i64) -> Option<u8> {
+    u8::try_from(value).ok().filter(|value| *value < BOARD_SIZE)
+}
+
+/// The tiles of the starting cross, where the game places them.
+fn cross_tiles(cross: &CrossConfig) -> Vec<ScenarioTile> {
+    cross
+        .placed_tiles()
+        .into_iter()
+        .map(|(coordinates, tile)| ScenarioTile {
+            coordinates,
+            oasis_directions: tile.oasis_directions(),
+            treasure_directions: tile.treasure_directions(),
+        })
+        .collect()
+}
+
+impl TryFrom<&Table> for ScenarioTile {
+    type Error = &'static str;
+
+    fn try_from(table: &Table) -> Result<Self, &'static str> {
+        let coordinates = match table.get("at").and_then(Value::as_array) {
+            Some(at) if at.len() == 2 => at
+                .iter()
+                .map(|value| value.as_integer().and_then(coordinate_value))
+                .collect::<Option<Vec<u8>>>(),
+            _ => None,
+        }
+        .ok_or("Expected at = [x, y] on the board")?;
+        let sides = |key: &str| match table.get(key) {
+            None => Some(CardinalDirectionFlags::empty()),
+            Some(value) => value.as_str().and_then(directions),
+        };
+
+        Ok(Self {
+            coordinates: (coordinates[0], coordinates[1]),
+            oasis_directions: sides("oasis").ok_or("Expected oasis sides like \"NE\"")?,
+            treasure_directions: sides("treasures").ok_or("Expected treasure sides like \"NE\"")?,
+        })
+    }
+}
+
+impl TryFrom<&str> for ScenarioAction {
+    type Error = &'static str;
+
+    fn try_from(line: &str) -> Result<Self, &'static str> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+
+        match words.as_slice() {
+            ["move", x, y] => Ok(Self::Move((
+                coordinate(x).ok_or("Expected coordinates on the board")?,
+                coordinate(y).ok_or("Expected coordinates on the board")?,
+            ))),
+            ["skip-move"] => Ok(Self::SkipMove),
+            ["explore", x, y, options @ ..] => {
+                let mut oasis_directions = CardinalDirectionFlags::empty();
+                let mut treasure_directions = CardinalDirectionFlags::empty();
+                let mut is_desert = false;
+
+                for option in options {
+                    match option.split_once('=') {
+                        Some(("oasis", sides)) => {
+                            oasis_directions =
+                                directions(sides).ok_or("Expected oasis sides like NE")?;
+                        }
+                        Some(("treasures", sides)) => {
+                            treasure_directions =
+                                directions(sides).ok_or("Expected treasure sides like NE")?;
+                        }
+                        None if *option == "desert" => is_desert = true,
+                        _ => return Err("Expected oasis=, treasures= or desert"),
+                    }
+                }
+
+                Ok(Self::Explore {
+                    coordinates: (
+                        coordinate(x).ok_or("Expected coordinates on the board")?,
+                        coordinate(y).ok_or("Expected coordinates on the board")?,
+                    ),
+                    oasis_directions,
+                    treasure_directions,
+                    is_desert,
+                })
+            }
+            _ => Err("Expected move X Y, skip-move or explore X Y [oasis=] [treasures=] [desert]"),
+        }
+    }
+}
+
+impl TryFrom<&Table> for Scenario {
+    type Error = ScenarioError;
+
+    fn try_from(table: &Table) -> Result<Self, ScenarioError> {
+        let rules = match table.get("rules") {
+            None => RulesConfig::default(),
+            Some(Value::Table(rules)) => RulesConfig::try_from(rules)?,
+            Some(_) => return Err(ScenarioError::WrongType("rules", "a table")),
+        };
+
+        let cross = match table.get("cross") {
+            None => false,
+            Some(Value::Boolean(cross)) => *cross,
+            Some(_) => return Err(ScenarioError::WrongType("cross", "a boolean")),
+        };
+        let mut tiles = vec![];
+
+        let listed_tiles = match table.get("tile") {
+            None => &vec![],
+            Some(Value::Array(listed_tiles)) => listed_tiles,
+            Some(_) => return Err(ScenarioError::WrongType("tile", "an array of tables")),
+        };
+
+        for (idx, tile) in listed_tiles.iter().enumerate() {
+            let tile = tile
+                .as_table()
+                .ok_or(ScenarioError::WrongType("tile", "an array of tables"))?;
+
+            tiles.push(
+                ScenarioTile::try_from(tile)
+                    .map_err(|error| ScenarioError::InvalidTile(idx + 1, error))?,
+            );
+        }
+
+        let actions = match table.get("actions") {
+            None => &vec![],
+            Some(Value::Array(actions)) => actions,
+            Some(_) => return Err(ScenarioError::WrongType("actions", "an array of strings")),
+        };
+
+        let actions = actions
+            .iter()
+            .enumerate()
+            .map(|(idx, action)| {
+                action
+                    .as_str()
+                    .ok_or("Expected a string")
+                    .and_then(ScenarioAction::try_from)
+                    .map_err(|error| ScenarioError::InvalidAction(idx + 1, error))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            rules,
+            cross,
+            tiles,
+            actions,
+        })
+    }
+}
+
+impl Scenario {
+    /// Sets up the board, with `cross` as the starting cross, and plays every
+    /// action in order, stopping at the first one the rules don't allow.
+    pub fn play(&self, cross: &CrossConfig) -> Result<BoardModel, ScenarioError> {
+        let mut game = BoardModel::new(self.rules.clone());
+        let cross_tiles = if self.cross {
+            cross_tiles(cross)
+        } else {
+            vec![]
+        };
+
+        game.place_starting_caravans();
+
+        for tile in cross_tiles.iter().chain(&self.tiles) {
+            game.place_tile(
+                tile.coordinates,
+                tile.oasis_directions.clone(),
+                tile.treasure_directions.clone(),
+            );
+        }
+
+        for (idx, action) in self.actions.iter().enumerate() {
+            let result = match action {
+                ScenarioAction::Move(coordinates) => game.move_caravan(*coordinates).map(|_| ()),
+                ScenarioAction::SkipMove => game.skip_move(),
+                ScenarioAction::Explore {
+                    coordinates,
+                    oasis_directions,
+                    treasure_directions,
+                    is_desert,
+                } => game
+                    .explore(
+                        *coordinates,
+                        oasis_directions.clone(),
+                        treasure_directions.clone(),
+                        *is_desert,
+                    )
+                    .map(|_| ()),
+            };
+
+            result.map_err(|error| ScenarioError::IllegalAction(idx + 1, error))?;
+        }
+
+        Ok(game)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+    use test_case::test_case;
+
+    use super::*;
+    use crate::game::entities::player::PlayerName;
+    use crate::util::tileset::TilesetConfig;
+
+    fn scenario(input: &str) -> Result<Scenario, ScenarioError> {
+        Scenario::try_from(&toml::from_str::<Table>(input).unwrap())
+    }
+
+    fn shipped_cross() -> CrossConfig {
+        let table =
+            toml::from_str::<Table>(include_str!("../../../godot/config/tileset.toml")).unwrap();
+
+        TilesetConfig::try_from(&table).unwrap().cross
+    }
+
+    #[test]
+    fn lays_out_the_starting_cross() {
+        let tiles = cross_tiles(&shipped_cross());
+        let mut coordinates: Vec<(u8, u8)> = tiles.iter().map(|tile| tile.coordinates).collect();
+
+        coordinates.sort();
+        coordinates.dedup();
+
+        assert_eq!(tiles.len(), 21);
+        assert_eq!(coordinates.len(), 21);
+        assert!(coordinates
+            .iter()
+            .all(|(x, y)| *x == BOARD_SIZE / 2 || *y == BOARD_SIZE / 2));
+        assert_eq!(tiles[0].coordinates, (5, 5));
+        assert_matches!(
+            tiles.iter().find(|tile| tile.coordinates == (5, 6)),
+            Some(ScenarioTile { oasis_directions, .. }) if *oasis_directions == CardinalDirectionFlags::W
+        );
+    }
+
+    #[test_case("move 5 8" => Ok(ScenarioAction::Move((5, 8))))]
+    #[test_case("skip-move" => Ok(ScenarioAction::SkipMove))]
+    #[test_case("explore 4 8 oasis=NE treasures=N desert" => Ok(ScenarioAction::Explore {
+        coordinates: (4, 8),
+        oasis_directions: CardinalDirectionFlags::N | CardinalDirectionFlags::E,
+        treasure_directions: CardinalDirectionFlags::N,
+        is_desert: true,
+    }))]
+    #[test_case("explore 4 8 oasis=-" => matches Ok(ScenarioAction::Explore { is_desert: false, .. }))]
+    #[test_case("move 5 11" => Err("Expected coordinates on the board") ; "off the board")]
+    #[test_case("explore 4 8 oasis=NX" => Err("Expected oasis sides like NE") ; "unknown side")]
+    #[test_case("explore 4 8 lush" => Err("Expected oasis=, treasures= or desert") ; "unknown option")]
+    #[test_case("teleport 4 8" => matches Err(_) ; "unknown action")]
+    fn parses_actions(line: &str) -> Result<ScenarioAction, &'static str> {
+        ScenarioAction::try_from(line)
+    }
+
+    #[test]
+    fn reads_rules_tiles_and_actions() {
+        let scenario = scenario(
+            "cross = true\nactions = [\"skip-move\"]\n[rules.movement]\noasis_crossings = 2\n[[tile]]\nat = [4, 4]\noasis = \"NE\"\n",
+        )
+        .unwrap();
+
+        assert_eq!(scenario.rules.oasis_crossings, 2);
+        assert!(scenario.cross);
+        assert_eq!(scenario.tiles.len(), 1);
+        assert_eq!(
+            scenario.tiles[0].oasis_directions,
+            CardinalDirectionFlags::N | CardinalDirectionFlags::E
+        );
+        assert_eq!(scenario.actions, vec![ScenarioAction::SkipMove]);
+    }
+
+    #[test_case("cross = 1" => matches Err(ScenarioError::WrongType("cross", _)) ; "cross not a boolean")]
+    #[test_case("[rules.movement]\noasis_crossings = 9" => matches Err(ScenarioError::RulesError(_)) ; "invalid rules")]
+    #[test_case("[[tile]]\nat = [4]" => matches Err(ScenarioError::InvalidTile(1, _)) ; "tile without coordinates")]
+    #[test_case("actions = [\"move 1\"]" => matches Err(ScenarioError::InvalidAction(1, _)) ; "invalid action")]
+    fn rejects_invalid_scenarios(input: &str) -> Result<Scenario, ScenarioError> {
+        scenario(input)
+    }
+
+    #[test]
+    fn plays_the_actions_in_order() {
+        let game = scenario("cross = true\nactions = [\"move 5 6\", \"explore 4 6 desert\"]")
+            .unwrap()
+            .play(&shipped_cross())
+            .unwrap();
+
+        assert_eq!(game.position(PlayerName::White), Some((4, 6)));
+        assert!(game.turn().can_move());
+        assert_eq!(game.turn().active_player(), PlayerName::White);
+    }
+
+    #[test]
+    fn points_at_the_first_illegal_action() {
+        assert_matches!(
+            scenario("cross = true\nactions = [\"skip-move\", \"move 5 6\"]")
+                .unwrap()
+                .play(&shipped_cross()),
+            Err(ScenarioError::IllegalAction(
+                2,
+                BoardModelError::WrongPhase(_)
+            ))
+        );
+    }
+}
@@ -0,0 +1,626 @@
+use thiserror::Error;
+use toml::Table;
+use toml::Value;
+
+use crate::util::config::ConfigBool;
+use crate::util::config::ConfigFloat;
+use crate::util::config::ConfigString;
+use crate::util::palette::Rgb;
+use crate::util::palette::DEFAULT_CUSTOM_COLORS;
+use crate::util::LogLevel;
+
+const VOLUME_RANGE: (f64, f64) = (0., 1.);
+const UI_SCALE_RANGE: (f64, f64) = (0.5, 2.);
+// Keys of `[accessibility.custom_colors]`, in `PlayerName` order
+const CUSTOM_COLOR_KEYS: [&str; 4] = ["white", "orange", "red", "blue"];
+/// Largest fixed seed, as TOML integers are signed 64-bit.
+pub const MAX_SEED: u64 = i64::MAX as u64;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum SettingsParseError {
+    #[error("Setting {0} expected one of {1}, received {2}")]
+    UnknownVariant(&'static str, &'static str, String),
+    #[error("Setting {0} must be between {1} and {2}, received {3}")]
+    OutOfRange(&'static str, f64, f64, f64),
+    #[error("Setting {0} has the wrong type, expected {1}")]
+    WrongType(&'static str, &'static str),
+    #[error("Setting {0}: {1}")]
+    InvalidColor(String, String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WindowMode {
+    #[default]
+    Windowed,
+    Fullscreen,
+    ExclusiveFullscreen,
+}
+
+impl WindowMode {
+    pub const ALL: [WindowMode; 3] = [
+        WindowMode::Windowed,
+        WindowMode::Fullscreen,
+        WindowMode::ExclusiveFullscreen,
+    ];
+}
+
+impl TryFrom<&str> for WindowMode {
+    type Error = SettingsParseError;
+
+    fn try_from(value: &str) -> Result<Self, SettingsParseError> {
+        match value {
+            "windowed" => Ok(WindowMode::Windowed),
+            "fullscreen" => Ok(WindowMode::Fullscreen),
+            "exclusive_fullscreen" => Ok(WindowMode::ExclusiveFullscreen),
+            _ => Err(SettingsParseError::UnknownVariant(
+                "window_mode",
+                "windowed, fullscreen, exclusive_fullscreen",
+                value.to_owned(),
+            )),
+        }
+    }
+}
+
+impl From<WindowMode> for &'static str {
+    fn from(value: WindowMode) -> Self {
+        match value {
+            WindowMode::Windowed => "windowed",
+            WindowMode::Fullscreen => "fullscreen",
+            WindowMode::ExclusiveFullscreen => "exclusive_fullscreen",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorblindPalette {
+    #[default]
+    Classic,
+    Deuteranopia,
+    Protanopia,
+    Custom,
+}
+
+impl ColorblindPalette {
+    pub const ALL: [ColorblindPalette; 4] = [
+        ColorblindPalette::Classic,
+        ColorblindPalette::Deuteranopia,
+        ColorblindPalette::Protanopia,
+        ColorblindPalette::Custom,
+    ];
+}
+
+impl TryFrom<&str> for ColorblindPalette {
+    type Error = SettingsParseError;
+
+    fn try_from(value: &str) -> Result<Self, SettingsParseError> {
+        match value {
+            "classic" => Ok(ColorblindPalette::Classic),
+            "deuteranopia" => Ok(ColorblindPalette::Deuteranopia),
+            "protanopia" => Ok(ColorblindPalette::Protanopia),
+            "custom" => Ok(ColorblindPalette::Custom),
+            _ => Err(SettingsParseError::UnknownVariant(
+                "colorblind_palette",
+                "classic, deuteranopia, protanopia, custom",
+                value.to_owned(),
+            )),
+        }
+    }
+}
+
+impl From<ColorblindPalette> for &'static str {
+    fn from(value: ColorblindPalette) -> Self {
+        match value {
+            ColorblindPalette::Classic => "classic",
+            ColorblindPalette::Deuteranopia => "deuteranopia",
+            ColorblindPalette::Protanopia => "protanopia",
+            ColorblindPalette::Custom => "custom",
+        }
+    }
+}
+
+/// How a new game picks its random seed: a fresh one every game, or the same
+/// one every time so a game can be replayed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SeedBehavior {
+    #[default]
+    Random,
+    Fixed(u64),
+}
+
+impl TryFrom<&Value> for SeedBehavior {
+    type Error = SettingsParseError;
+
+    fn try_from(value: &Value) -> Result<Self, SettingsParseError> {
+        match value {
+            Value::String(value) if value == "random" => Ok(SeedBehavior::Random),
+            Value::Integer(seed) if *seed >= 0 => Ok(SeedBehavior::Fixed(*seed as u64)),
+            _ => Err(SettingsParseError::WrongType(
+                "seed",
+                "\"random\" or a non-negative integer",
+            )),
+        }
+    }
+}
+
+impl From<SeedBehavior> for Value {
+    fn from(value: SeedBehavior) -> Self {
+        match value {
+            SeedBehavior::Random => Value::String(String::from("random")),
+            SeedBehavior::Fixed(seed) => Value::Integer(seed.min(MAX_SEED) as i64),
+        }
+    }
+}
+
+/// Volumes are linear, from 0 (muted) to 1 (full volume).
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioSettings {
+    pub master_volume: f64,
+    pub music_volume: f64,
+    pub effects_volume: f64,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            master_volume: 1.,
+            music_volume: 0.8,
+            effects_volume: 0.8,
+        }
+    }
+}
+
+/// Player preferences, persisted to `user://settings.toml`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    pub audio: AudioSettings,
+    pub window_mode: WindowMode,
+    pub ui_scale: f64,
+    /// Overrides the `DEBUG` environment flag and build profile when set.
+    pub log_level: Option<LogLevel>,
+    pub colorblind_palette: ColorblindPalette,
+    /// Player colors for the `custom` palette, in `PlayerName` order.
+    pub custom_colors: [Rgb; 4],
+    /// Draws a per-player shape on tokens and buildings.
+    pub show_markers: bool,
+    pub seed: SeedBehavior,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            audio: AudioSettings::default(),
+            window_mode: WindowMode::default(),
+            ui_scale: 1.,
+            log_level: None,
+            colorblind_palette: ColorblindPalette::default(),
+            custom_colors: DEFAULT_CUSTOM_COLORS,
+            show_markers: false,
+            seed: SeedBehavior::default(),
+        }
+    }
+}
+
+fn section<'a>(key: &str, table: &'a Table) -> Option<&'a Table> {
+    table.get(key).and_then(|v| v.as_table())
+}
+
+/// The value of `key`, `None` when it's missing. A value of another type
+/// than `C` reads is an error like any other invalid value.
+fn setting<'a, C>(
+    key: &'static str,
+    table: Option<&'a Table>,
+    expected: &'static str,
+) -> Result<Option<C>, SettingsParseError>
+where
+    C: TryFrom<&'a Value>,
+{
+    table
+        .and_then(|t| t.get(key))
+        .map(|value| C::try_from(value).or(Err(SettingsParseError::WrongType(key, expected))))
+        .transpose()
+}
+
+/// The valid `result`, or `default` with the problem added to `errors`.
+fn or_default<T>(
+    result: Result<T, SettingsParseError>,
+    default: T,
+    errors: &mut Vec<SettingsParseError>,
+) -> T {
+    result.unwrap_or_else(|error| {
+        errors.push(error);
+        default
+    })
+}
+
+fn float_in_range(
+    key: &'static str,
+    table: Option<&Table>,
+    range: (f64, f64),
+    default: f64,
+) -> Result<f64, SettingsParseError> {
+    let Some(value) = setting::<ConfigFloat>(key, table, "a number")?.map(f64::from) else {
+        return Ok(default);
+    };
+
+    if value < range.0 || value > range.1 {
+        return Err(SettingsParseError::OutOfRange(key, range.0, range.1, value));
+    }
+
+    Ok(value)
+}
+
+fn string_setting(
+    key: &'static str,
+    table: Option<&Table>,
+) -> Result<Option<String>, SettingsParseError> {
+    Ok(setting::<ConfigString>(key, table, "a string")?.map(String::from))
+}
+
+fn custom_colors(
+    table: Option<&Table>,
+    defaults: [Rgb; 4],
+    errors: &mut Vec<SettingsParseError>,
+) -> [Rgb; 4] {
+    let mut colors = defaults;
+
+    for (idx, key) in CUSTOM_COLOR_KEYS.iter().enumerate() {
+        let color = string_setting(key, table).and_then(|hex| {
+            hex.map(|hex| {
+                Rgb::try_from(hex.as_str()).map_err(|error| {
+                    SettingsParseError::InvalidColor(
+                        format!("custom_colors.{key}"),
+                        error.to_string(),
+                    )
+                })
+            })
+            .transpose()
+        });
+
+        if let Some(color) = or_default(color, None, errors) {
+            colors[idx] = color;
+        }
+    }
+
+    colors
+}
+
+impl Settings {
+    /// Reads the settings in `table`. Missing and invalid keys keep their
+    /// default value, and the problems with the invalid ones are returned,
+    /// so one bad value doesn't reset every other setting.
+    pub fn from_table_lossy(table: &Table) -> (Self, Vec<SettingsParseError>) {
+        let defaults = Settings::default();
+        let mut errors = vec![];
+
+        let audio = section("audio", table);
+        let display = section("display", table);
+        let accessibility = section("accessibility", table);
+        let debug = section("debug", table);
+        let game = section("game", table);
+
+        let audio = AudioSettings {
+            master_volume: or_default(
+                float_in_range(
+                    "master_volume",
+                    audio,
+                    VOLUME_RANGE,
+                    defaults.audio.master_volume,
+                ),
+                defaults.audio.master_volume,
+                &mut errors,
+            ),
+            music_volume: or_default(
+                float_in_range(
+                    "music_volume",
+                    audio,
+                    VOLUME_RANGE,
+                    defaults.audio.music_volume,
+                ),
+                defaults.audio.music_volume,
+                &mut errors,
+            ),
+            effects_volume: or_default(
+                float_in_range(
+                    "effects_volume",
+                    audio,
+                    VOLUME_RANGE,
+                    defaults.audio.effects_volume,
+                ),
+                defaults.audio.effects_volume,
+                &mut errors,
+            ),
+        };
+
+        let window_mode = string_setting("window_mode", display).and_then(|s| {
+            s.map(|s| WindowMode::try_from(s.as_str()))
+                .transpose()
+                .map(|mode| mode.unwrap_or(defaults.window_mode))
+        });
+        let window_mode = or_default(window_mode, defaults.window_mode, &mut errors);
+        let ui_scale = or_default(
+            float_in_range("ui_scale", display, UI_SCALE_RANGE, defaults.ui_scale),
+            defaults.ui_scale,
+            &mut errors,
+        );
+
+        let colorblind_palette =
+            string_setting("colorblind_palette", accessibility).and_then(|s| {
+                s.map(|s| ColorblindPalette::try_from(s.as_str()))
+                    .transpose()
+                    .map(|palette| palette.unwrap_or(defaults.colorblind_palette))
+            });
+        let colorblind_palette =
+            or_default(colorblind_palette, defaults.colorblind_palette, &mut errors);
+        let custom_colors = custom_colors(
+            accessibility.and_then(|t| section("custom_colors", t)),
+            defaults.custom_colors,
+            &mut errors,
+        );
+        let show_markers = setting::<ConfigBool>("show_markers", accessibility, "true or false")
+            .map(|value| value.map_or(defaults.show_markers, bool::from));
+        let show_markers = or_default(show_markers, defaults.show_markers, &mut errors);
+
+        let log_level = string_setting("log_level", debug).and_then(|s| {
+            s.map(|s| {
+                LogLevel::try_from(s.as_str()).map_err(|_| {
+                    SettingsParseError::UnknownVariant(
+                        "log_level",
+                        "debug, info, warn, error",
+                        s.clone(),
+                    )
+                })
+            })
+            .transpose()
+        });
+        let log_level = or_default(log_level, defaults.log_level, &mut errors);
+
+        let seed = game
+            .and_then(|t| t.get("seed"))
+            .map(SeedBehavior::try_from)
+            .transpose()
+            .map(|seed| seed.unwrap_or(defaults.seed));
+        let seed = or_default(seed, defaults.seed, &mut errors);
+
+        let settings = Self {
+            audio,
+            window_mode,
+            ui_scale,
+            log_level,
+            colorblind_palette,
+            custom_colors,
+            show_markers,
+            seed,
+        };
+
+        (settings, errors)
+    }
+}
+
+impl TryFrom<&Table> for Settings {
+    type Error = SettingsParseError;
+
+    /// Missing keys keep their default value, so a partial or empty file is
+    /// valid. Keys that are present must hold a valid value.
+    fn try_from(table: &Table) -> Result<Self, SettingsParseError> {
+        let (settings, mut errors) = Settings::from_table_lossy(table);
+
+        match errors.is_empty() {
+            true => Ok(settings),
+            false => Err(errors.remove(0)),
+        }
+    }
+}
+
+impl From<&Settings> for Table {
+    fn from(settings: &Settings) -> Self {
+        let mut audio = Table::new();
+        audio.insert(
+            String::from("master_volume"),
+            Value::Float(settings.audio.master_volume),
+        );
+        audio.insert(
+            String::from("music_volume"),
+            Value::Float(settings.audio.music_volume),
+        );
+        audio.insert(
+            String::from("effects_volume"),
+            Value::Float(settings.audio.effects_volume),
+        );
+
+        let window_mode: &str = settings.window_mode.into();
+        let mut display = Table::new();
+        display.insert(String::from("window_mode"), Value::from(window_mode));
+        display.insert(String::from("ui_scale"), Value::Float(settings.ui_scale));
+
+        let palette: &str = settings.colorblind_palette.into();
+        let mut accessibility = Table::new();
+        accessibility.insert(String::from("colorblind_palette"), Value::from(palette));
+        accessibility.insert(
+            String::from("show_markers"),
+            Value::Boolean(settings.show_markers),
+        );
+
+        let mut custom_colors = Table::new();
+        for (key, color) in CUSTOM_COLOR_KEYS.iter().zip(settings.custom_colors) {
+            custom_colors.insert(String::from(*key), Value::String(color.to_hex()));
+        }
+        accessibility.insert(String::from("custom_colors"), Value::Table(custom_colors));
+
+        let mut debug = Table::new();
+        if let Some(log_level) = &settings.log_level {
+            let log_level: &str = log_level.into();
+            debug.insert(String::from("log_level"), Value::from(log_level));
+        }
+
+        let mut game = Table::new();
+        game.insert(String::from("seed"), settings.seed.into());
+
+        let mut table = Table::new();
+        table.insert(String::from("audio"), Value::Table(audio));
+        table.insert(String::from("display"), Value::Table(display));
+        table.insert(String::from("accessibility"), Value::Table(accessibility));
+        table.insert(String::from("debug"), Value::Table(debug));
+        table.insert(String::from("game"), Value::Table(game));
+
+        table
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+    use toml::Table;
+
+    use super::*;
+
+    fn parse(input: &str) -> Result<Settings, SettingsParseError> {
+        let table = toml::from_str::<Table>(input).unwrap();
+
+        Settings::try_from(&table)
+    }
+
+    #[test]
+    fn an_empty_file_yields_the_defaults() {
+        assert_eq!(parse(""), Ok(Settings::default()));
+    }
+
+    #[test]
+    fn parses_every_section() {
+        let settings = parse(
+            r##"
+[audio]
+master_volume = 0.5
+music_volume = 0.0
+effects_volume = 1.0
+
+[display]
+window_mode = "fullscreen"
+ui_scale = 1.5
+
+[accessibility]
+colorblind_palette = "custom"
+show_markers = true
+
+[accessibility.custom_colors]
+red = "#cc79a7"
+
+[debug]
+log_level = "warn"
+
+[game]
+seed = 1234
+"##,
+        )
+        .unwrap();
+
+        assert_eq!(settings.audio.master_volume, 0.5);
+        assert_eq!(settings.audio.music_volume, 0.);
+        assert_eq!(settings.window_mode, WindowMode::Fullscreen);
+        assert_eq!(settings.ui_scale, 1.5);
+        assert_eq!(settings.colorblind_palette, ColorblindPalette::Custom);
+        assert_eq!(settings.custom_colors[2], Rgb(204, 121, 167));
+        assert_eq!(settings.custom_colors[0], DEFAULT_CUSTOM_COLORS[0]);
+        assert!(settings.show_markers);
+        assert_eq!(settings.log_level, Some(LogLevel::Warn));
+        assert_eq!(settings.seed, SeedBehavior::Fixed(1234));
+    }
+
+    #[test]
+    fn rejects_out_of_range_volumes() {
+        assert_matches!(
+            parse("[audio]\nmaster_volume = 1.5"),
+            Err(SettingsParseError::OutOfRange("master_volume", _, _, _))
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_variants() {
+        assert_matches!(
+            parse("[display]\nwindow_mode = \"maximized\""),
+            Err(SettingsParseError::UnknownVariant("window_mode", _, _))
+        );
+        assert_matches!(
+            parse("[accessibility.custom_colors]\nblue = \"blue\""),
+            Err(SettingsParseError::InvalidColor(_, _))
+        );
+        assert_matches!(
+            parse("[game]\nseed = -1"),
+            Err(SettingsParseError::WrongType("seed", _))
+        );
+    }
+
+    #[test]
+    fn rejects_values_of_the_wrong_type() {
+        assert_eq!(
+            parse("[accessibility]\nshow_markers = \"yes\""),
+            Err(SettingsParseError::WrongType(
+                "show_markers",
+                "true or false"
+            ))
+        );
+        assert_matches!(
+            parse("[audio]\nmaster_volume = \"loud\""),
+            Err(SettingsParseError::WrongType("master_volume", _))
+        );
+        assert_matches!(
+            parse("[display]\nwindow_mode = 2"),
+            Err(SettingsParseError::WrongType("window_mode", _))
+        );
+    }
+
+    #[test]
+    fn drops_only_the_invalid_settings() {
+        let table = toml::from_str::<Table>(
+            "[accessibility]\nshow_markers = \"yes\"\ncolorblind_palette = \"protanopia\"\n\n[game]\nseed = -1",
+        )
+        .unwrap();
+
+        let (settings, errors) = Settings::from_table_lossy(&table);
+
+        assert_eq!(settings.colorblind_palette, ColorblindPalette::Protanopia);
+        assert_eq!(settings.show_markers, Settings::default().show_markers);
+        assert_eq!(settings.seed, SeedBehavior::Random);
+        assert_matches!(
+            errors.as_slice(),
+            [
+                SettingsParseError::WrongType("show_markers", _),
+                SettingsParseError::WrongType("seed", _)
+            ]
+        );
+    }
+
+    #[test]
+    fn writes_seeds_toml_can_read_back() {
+        let written = Table::from(&Settings {
+            seed: SeedBehavior::Fixed(u64::MAX),
+            ..Settings::default()
+        });
+
+        assert_eq!(
+            Settings::try_from(&written).map(|settings| settings.seed),
+            Ok(SeedBehavior::Fixed(MAX_SEED))
+        );
+    }
+
+    #[test]
+    fn survives_a_round_trip_through_toml() {
+        let settings = Settings {
+            audio: AudioSettings {
+                master_volume: 0.25,
+                music_volume: 0.5,
+                effects_volume: 0.75,
+            },
+            window_mode: WindowMode::ExclusiveFullscreen,
+            ui_scale: 0.75,
+            log_level: Some(LogLevel::Debug),
+            colorblind_palette: ColorblindPalette::Protanopia,
+            custom_colors: [Rgb(1, 2, 3), Rgb(4, 5, 6), Rgb(7, 8, 9), Rgb(10, 11, 12)],
+            show_markers: true,
+            seed: SeedBehavior::Fixed(7),
+        };
+
+        let written = Table::from(&settings).to_string();
+
+        assert_eq!(parse(&written), Ok(settings));
+    }
+}
@@ -0,0 +1,158 @@
+use serde_json::json;
+use serde_json::Value;
+
+/// How a test ended, with the reason it failed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TestOutcome {
+    Passed,
+    Failed(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestResult {
+    pub name: String,
+    pub outcome: TestOutcome,
+    pub seconds: f64,
+}
+
+/// Results of a test run outside of `cargo test`, written the way libtest's
+/// `--format json` writes them so the same tools can read both.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TestReport {
+    results: Vec<TestResult>,
+}
+
+impl TestReport {
+    pub fn push(&mut self, result: TestResult) {
+        self.results.push(result);
+    }
+    pub fn failed(&self) -> usize {
+        self.results
+            .iter()
+            .filter(|result| result.outcome != TestOutcome::Passed)
+            .count()
+    }
+    pub fn passed(&self) -> usize {
+        self.results.len() - self.failed()
+    }
+    /// One JSON object per line: the suite starting, each test starting and
+    /// ending, then the suite ending.
+    pub fn to_json_lines(&self) -> String {
+        let mut events = vec![json!({
+            "type": "suite",
+            "event": "started",
+            "test_count": self.results.len(),
+        })];
+
+        for result in &self.results {
+            events.push(json!({
+                "type": "test",
+                "event": "started",
+                "name": result.name,
+            }));
+
+            let mut event = json!({
+                "type": "test",
+                "name": result.name,
+                "event": "ok",
+                "exec_time": result.seconds,
+            });
+
+            if let TestOutcome::Failed(reason) = &result.outcome {
+                event["event"] = Value::from("failed");
+                event["stdout"] = Value::from(format!("{reason}\n"));
+            }
+
+            events.push(event);
+        }
+
+        events.push(json!({
+            "type": "suite",
+            "event": if self.failed() == 0 { "ok" } else { "failed" },
+            "passed": self.passed(),
+            "failed": self.failed(),
+            "ignored": 0,
+            "measured": 0,
+            "filtered_out": 0,
+            "exec_time": self.results.iter().map(|result| result.seconds).sum::<f64>(),
+        }));
+
+        events
+            .iter()
+            .map(Value::to_string)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+    /// The closing line of `cargo test`, e.g. `test result: ok. 3 passed; 0
+    /// failed`, after a line per failed test.
+    pub fn summary(&self) -> String {
+        let mut lines: Vec<String> = self
+            .results
+            .iter()
+            .filter_map(|result| match &result.outcome {
+                TestOutcome::Passed => None,
+                TestOutcome::Failed(reason) => Some(format!("{} failed: {reason}", result.name)),
+            })
+            .collect();
+
+        lines.push(format!(
+            "test result: {}. {} passed; {} failed",
+            if self.failed() == 0 { "ok" } else { "FAILED" },
+            self.passed(),
+            self.failed()
+        ));
+
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report() -> TestReport {
+        let mut report = TestReport::default();
+
+        report.push(TestResult {
+            name: String::from("moves_by_click"),
+            outcome: TestOutcome::Passed,
+            seconds: 0.5,
+        });
+        report.push(TestResult {
+            name: String::from("exhausts_decks"),
+            outcome: TestOutcome::Failed(String::from("expected deck 2")),
+            seconds: 0.25,
+        });
+
+        report
+    }
+
+    #[test]
+    fn writes_libtest_json_events() {
+        let events: Vec<Value> = report()
+            .to_json_lines()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        assert_eq!(events.len(), 6);
+        assert_eq!(events[0]["test_count"], 2);
+        assert_eq!(events[2]["event"], "ok");
+        assert_eq!(events[4]["event"], "failed");
+        assert_eq!(events[4]["stdout"], "expected deck 2\n");
+        assert_eq!(events[5]["event"], "failed");
+        assert_eq!(events[5]["exec_time"], 0.75);
+    }
+
+    #[test]
+    fn summarizes_like_cargo_test() {
+        assert_eq!(
+            report().summary(),
+            "exhausts_decks failed: expected deck 2\ntest result: FAILED. 1 passed; 1 failed"
+        );
+        assert_eq!(
+            TestReport::default().summary(),
+            "test result: ok. 0 passed; 0 failed"
+        );
+    }
+}
@@ -0,0 +1,571 @@
+use thiserror::Error;
+use toml::Table;
+use toml::Value;
+
+use crate::util::config::ConfigBool;
+use crate::util::config::ConfigString;
+use crate::util::flags::CardinalDirectionFlags;
+use crate::util::flags::OasisLayoutFlags;
+use crate::util::treasures::is_no_treasure;
+use crate::util::treasures::TileLocation;
+use crate::util::treasures::TreasureConfigError;
+use crate::util::treasures::TreasureReference;
+use crate::util::treasures::UndefinedTreasureError;
+
+pub mod format;
+pub mod generate;
+pub mod lint;
+pub mod notation;
+pub mod render;
+pub mod stats;
+mod test;
+
+#[derive(Error, Debug)]
+pub enum TilesetError {
+    #[error("Couldn't load config/tileset.toml")]
+    LoadError,
+    #[error("Couldn't parse config/tileset.toml: {0}")]
+    ParseError(&'static str),
+    #[error("Couldn't load config/treasures.toml")]
+    TreasuresLoadError,
+    #[error("Couldn't parse config/treasures.toml: {0}")]
+    TreasuresError(#[from] TreasureConfigError),
+    #[error("{0}")]
+    UndefinedTreasureError(#[from] UndefinedTreasureError),
+}
+
+#[derive(Clone, Debug)]
+pub struct ConfigOasisFlags(CardinalDirectionFlags);
+
+impl From<ConfigOasisFlags> for CardinalDirectionFlags {
+    fn from(value: ConfigOasisFlags) -> Self {
+        value.0
+    }
+}
+
+impl TryFrom<&Value> for ConfigOasisFlags {
+    type Error = &'static str;
+
+    fn try_from(value: &Value) -> Result<Self, &'static str> {
+        match value {
+            Value::String(value) => {
+                let flags: CardinalDirectionFlags = bitflags::parser::from_str(value)
+                    .or(Err("Expected oasis flags to be bit flags"))?;
+
+                // Hex flags such as `0x10` parse with bits that name no
+                // direction, which would spill into the next oasis
+                CardinalDirectionFlags::from_bits(flags.bits())
+                    .map(ConfigOasisFlags)
+                    .ok_or("Expected oasis flags to only name N, E, S and W")
+            }
+            _ => Err("Couldn't parse value as boolean"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ConfigOasisLayoutFlags(OasisLayoutFlags);
+
+impl ConfigOasisLayoutFlags {
+    fn from_key_in_table(key: &str, table: &Table) -> Option<Self> {
+        let res = table
+            .get(key)
+            .and_then(|v| ConfigOasisLayoutFlags::try_from(v).ok());
+
+        res
+    }
+    fn from_key_in_table_as_oasis_flags_array(
+        key: &str,
+        table: &Table,
+    ) -> Option<ConfigOasisLayoutFlags> {
+        ConfigOasisLayoutFlags::from_key_in_table(key, table)
+    }
+}
+
+impl From<ConfigOasisLayoutFlags> for OasisLayoutFlags {
+    fn from(value: ConfigOasisLayoutFlags) -> Self {
+        value.0
+    }
+}
+
+// impl TryFrom<&Vec<Value>> for ConfigOasisFlagsArray {
+//     type Error = &'static str;
+//
+//     fn try_from(value: &Vec<Value>) -> Result<Self, &'static str> {
+//         if value.len() != 5 {
+//             return Err("Could not parse value as array of 4 elements");
+//         }
+//
+//         let oasis_flags: Vec<ConfigOasisFlags> = value
+//             .iter()
+//             .map(ConfigOasisFlags::try_from)
+//             .collect::<Result<Vec<ConfigOasisFlags>, &str>>(
+//         )?;
+//
+//         let array = std::array::from_fn(|idx| oasis_flags[idx].clone());
+//
+//         Ok(ConfigOasisFlagsArray(array))
+//     }
+// }
+
+impl TryFrom<&Value> for ConfigOasisLayoutFlags {
+    type Error = &'static str;
+
+    fn try_from(value: &Value) -> Result<Self, &'static str> {
+        match value {
+            Value::Array(value) => {
+                let mut oasis_flags: Vec<ConfigOasisFlags> = value
+                    .iter()
+                    .map(ConfigOasisFlags::try_from)
+                    .collect::<Result<Vec<ConfigOasisFlags>, &'static str>>()?;
+
+                oasis_flags.resize(4, ConfigOasisFlags(CardinalDirectionFlags::empty()));
+
+                let output_flags = oasis_flags.iter().enumerate().fold(
+                    OasisLayoutFlags::empty(),
+                    |acc, (idx, flags)| {
+                        acc | OasisLayoutFlags::from_cardinal_direction_flags(&flags.0, idx as u8)
+                    },
+                );
+
+                Ok(ConfigOasisLayoutFlags(output_flags))
+            }
+            _ => Err("Couldn't parse value as array"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CrossConfigArray([TileConfig; 5]);
+
+impl CrossConfigArray {
+    fn from_key_in_table(key: &str, table: &Table) -> Option<Self> {
+        let val = table
+            .get(key)
+            .and_then(|v| CrossConfigArray::try_from(v).ok());
+
+        val
+    }
+    fn from_key_in_table_as_array(key: &str, table: &Table) -> Option<[TileConfig; 5]> {
+        CrossConfigArray::from_key_in_table(key, table).map(|b| b.into())
+    }
+}
+
+impl From<CrossConfigArray> for [TileConfig; 5] {
+    fn from(value: CrossConfigArray) -> Self {
+        value.0
+    }
+}
+
+impl TryFrom<&Vec<Value>> for CrossConfigArray {
+    type Error = &'static str;
+
+    fn try_from(value: &Vec<Value>) -> Result<Self, &'static str> {
+        if value.len() != 5 {
+            return Err("Could not parse value as array of 5 elements");
+        }
+
+        let tile_configs: Vec<TileConfig> = value
+            .iter()
+            .map(TileConfig::try_from)
+            .collect::<Result<Vec<TileConfig>, &str>>()?;
+
+        let array = std::array::from_fn(|idx| tile_configs[idx].clone());
+
+        Ok(CrossConfigArray(array))
+    }
+}
+
+impl TryFrom<&Value> for CrossConfigArray {
+    type Error = &'static str;
+
+    fn try_from(value: &Value) -> Result<Self, &'static str> {
+        match value {
+            Value::Array(value) => {
+                let tile_configs: Vec<TileConfig> = value
+                    .iter()
+                    .map(TileConfig::try_from_for_cross)
+                    .collect::<Result<Vec<TileConfig>, &'static str>>()?;
+
+                let array: [TileConfig; 5] = tile_configs.try_into().or(Err(
+                    "Couldn't coerce TileConfig for center cross into array of 5 tiles",
+                ))?;
+
+                Ok(CrossConfigArray(array))
+            }
+            _ => Err("Couldn't parse value as array"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TileConfig {
+    pub is_cross: Option<bool>,
+    pub is_desert: Option<bool>,
+    pub oasis: Option<OasisLayoutFlags>,
+    pub treasure_n: Option<String>,
+    pub treasure_e: Option<String>,
+    pub treasure_s: Option<String>,
+    pub treasure_w: Option<String>,
+}
+
+impl TileConfig {
+    /// Every side an oasis of the tile touches.
+    pub fn oasis_directions(&self) -> CardinalDirectionFlags {
+        self.oasis.clone().map_or(
+            CardinalDirectionFlags::empty(),
+            CardinalDirectionFlags::from,
+        )
+    }
+    /// The sides that hold a treasure.
+    pub fn treasure_directions(&self) -> CardinalDirectionFlags {
+        [
+            (CardinalDirectionFlags::N, &self.treasure_n),
+            (CardinalDirectionFlags::E, &self.treasure_e),
+            (CardinalDirectionFlags::S, &self.treasure_s),
+            (CardinalDirectionFlags::W, &self.treasure_w),
+        ]
+        .into_iter()
+        .filter(|(_, id)| id.as_deref().is_some_and(|id| !is_no_treasure(id)))
+        .fold(CardinalDirectionFlags::empty(), |flags, (side, _)| {
+            flags | side
+        })
+    }
+    /// The treasure ids on each side, keyed like the tileset.
+    fn treasure_references(&self, location: TileLocation) -> Vec<TreasureReference> {
+        [
+            ("treasure_n", &self.treasure_n),
+            ("treasure_e", &self.treasure_e),
+            ("treasure_s", &self.treasure_s),
+            ("treasure_w", &self.treasure_w),
+        ]
+        .into_iter()
+        .filter_map(|(key, id)| {
+            Some(TreasureReference {
+                location: location.clone(),
+                key,
+                id: id.clone()?,
+            })
+        })
+        .collect()
+    }
+    fn try_from_for_cross(value: &Value) -> Result<Self, &'static str> {
+        let mut base = Self::try_from(value)?;
+
+        base.is_cross = Some(true);
+
+        Ok(base)
+    }
+}
+
+impl TryFrom<&Value> for TileConfig {
+    type Error = &'static str;
+
+    fn try_from(value: &Value) -> Result<Self, &'static str> {
+        match value {
+            Value::Table(table) => {
+                let is_desert = ConfigBool::from_key_in_table_as_bool("is_desert", table);
+                let oasis =
+                    ConfigOasisLayoutFlags::from_key_in_table_as_oasis_flags_array("oasis", table);
+                let treasure_n = ConfigString::from_key_in_table_as_string("treasure_n", table);
+                let treasure_e = ConfigString::from_key_in_table_as_string("treasure_e", table);
+                let treasure_s = ConfigString::from_key_in_table_as_string("treasure_s", table);
+                let treasure_w = ConfigString::from_key_in_table_as_string("treasure_w", table);
+
+                Ok(Self {
+                    is_cross: Some(false),
+                    is_desert,
+                    oasis: oasis.map(|f| f.0),
+                    treasure_n,
+                    treasure_e,
+                    treasure_s,
+                    treasure_w,
+                })
+            }
+            _ => Err("Could not parse value as tile config table"),
+        }
+    }
+}
+
+// Row and column the arms of the starting cross run along
+const CROSS_LINE: u8 = 5;
+
+/// Board coordinates of tile `index` of the cross arm `arm`, one of the
+/// letters of the `[[cross.*]]` tables, counting outwards from the center.
+pub fn cross_coordinates(arm: char, index: u8) -> Option<(u8, u8)> {
+    let offset = index + 1;
+
+    match arm {
+        'c' => Some((CROSS_LINE, CROSS_LINE)),
+        'n' => Some((CROSS_LINE, CROSS_LINE + offset)),
+        'e' => Some((CROSS_LINE + offset, CROSS_LINE)),
+        's' => Some((CROSS_LINE, CROSS_LINE.checked_sub(offset)?)),
+        'w' => Some((CROSS_LINE.checked_sub(offset)?, CROSS_LINE)),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CrossConfig {
+    c: TileConfig,
+    n: [TileConfig; 5],
+    e: [TileConfig; 5],
+    s: [TileConfig; 5],
+    w: [TileConfig; 5],
+}
+
+impl CrossConfig {
+    pub fn get_side(&self, side: &str) -> Result<[TileConfig; 5], &'static str> {
+        match side {
+            "cross_n" => Ok(self.n.clone()),
+            "cross_e" => Ok(self.e.clone()),
+            "cross_s" => Ok(self.s.clone()),
+            "cross_w" => Ok(self.w.clone()),
+            _ => Err("Requested invalid side for cross config"),
+        }
+    }
+    pub fn get_center(&self) -> TileConfig {
+        self.c.clone()
+    }
+    /// The four arms, keyed by the letter of their `[[cross.*]]` tables, in
+    /// file order.
+    pub fn arms(&self) -> [(char, &[TileConfig; 5]); 4] {
+        [
+            ('n', &self.n),
+            ('e', &self.e),
+            ('s', &self.s),
+            ('w', &self.w),
+        ]
+    }
+    /// Every tile of the cross with the board coordinates it's placed at,
+    /// the center first.
+    pub fn placed_tiles(&self) -> Vec<((u8, u8), &TileConfig)> {
+        let arms = self.arms().into_iter().flat_map(|(arm, tiles)| {
+            tiles
+                .iter()
+                .enumerate()
+                .filter_map(move |(index, tile)| Some((cross_coordinates(arm, index as u8)?, tile)))
+        });
+
+        std::iter::once(((CROSS_LINE, CROSS_LINE), &self.c))
+            .chain(arms)
+            .collect()
+    }
+}
+
+impl TryFrom<&Option<&Value>> for CrossConfig {
+    type Error = &'static str;
+
+    fn try_from(value: &Option<&Value>) -> Result<Self, &'static str> {
+        if let Some(value) = value {
+            if let Value::Table(table) = value {
+                let center_value = value
+                    .get("c")
+                    .ok_or("Missing center entry in cross config")?;
+                let c = TileConfig::try_from_for_cross(center_value)
+                    .map_err(|_| "Missing center entry in cross config")?;
+                let n = CrossConfigArray::from_key_in_table_as_array("n", table)
+                    .ok_or("Missing north entry in cross config")?;
+                let e = CrossConfigArray::from_key_in_table_as_array("e", table)
+                    .ok_or("Missing east entry in cross config")?;
+                let s = CrossConfigArray::from_key_in_table_as_array("s", table)
+                    .ok_or("Missing south entry in cross config")?;
+                let w = CrossConfigArray::from_key_in_table_as_array("w", table)
+                    .ok_or("Missing west entry in cross config")?;
+
+                Ok(Self { c, n, e, s, w })
+            } else {
+                Err("Cross config was not a table")
+            }
+        } else {
+            Err("Could not find cross config")
+        }
+    }
+}
+
+const DEFAULT_DECK_COLOR: &str = "#ffffff";
+
+// Accepts #rgb, #rrggbb and #rrggbbaa
+fn is_html_color(color: &str) -> bool {
+    color.strip_prefix('#').is_some_and(|hex| {
+        [3, 6, 8].contains(&hex.len()) && hex.chars().all(|c| c.is_ascii_hexdigit())
+    })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeckConfig {
+    /// Shown on the deck, defaults to its number
+    pub name: String,
+    /// HTML color of the name, e.g. `#fee17c`
+    pub color: String,
+    /// Resource path of an icon shown on the deck
+    pub icon: Option<String>,
+    /// Index of the deck that must be empty before this one can be drawn from
+    pub unlocks_after: Option<u8>,
+    pub tiles: [TileConfig; 17],
+}
+
+impl DeckConfig {
+    /// Parses one `[[decks]]` entry, the `idx`th in the file.
+    fn try_from_entry(idx: usize, value: &Value) -> Result<Self, &'static str> {
+        let Value::Table(table) = value else {
+            return Err("Couldn't parse deck as table");
+        };
+
+        let tiles = Self::tiles_from_value(
+            table
+                .get("deck")
+                .ok_or("Missing property 'deck' in decks array")?,
+        )?;
+        let name = ConfigString::from_key_in_table_as_string("name", table)
+            .unwrap_or_else(|| (idx + 1).to_string());
+        let color = ConfigString::from_key_in_table_as_string("color", table)
+            .unwrap_or_else(|| DEFAULT_DECK_COLOR.to_owned());
+        let icon = ConfigString::from_key_in_table_as_string("icon", table);
+
+        if !is_html_color(&color) {
+            return Err("Deck color must be a hex color such as #fee17c");
+        }
+
+        // Decks are numbered from 1 in the file, as on their labels
+        let unlocks_after = match table.get("unlocks_after") {
+            None => None,
+            Some(value) => {
+                let deck = value
+                    .as_integer()
+                    .filter(|deck| (1..=u8::MAX as i64).contains(deck))
+                    .ok_or("Deck unlocks_after must be the number of another deck")?;
+
+                if deck as usize == idx + 1 {
+                    return Err("A deck can't unlock after itself");
+                }
+
+                Some((deck - 1) as u8)
+            }
+        };
+
+        Ok(Self {
+            name,
+            color,
+            icon,
+            unlocks_after,
+            tiles,
+        })
+    }
+    fn tiles_from_value(value: &Value) -> Result<[TileConfig; 17], &'static str> {
+        match value {
+            Value::Array(value) => {
+                if value.len() != 17 {
+                    return Err("Deck requires exactly 17 tiles");
+                }
+
+                let tiles: Vec<TileConfig> = value
+                    .iter()
+                    .map(TileConfig::try_from)
+                    .collect::<Result<_, _>>()?;
+
+                tiles.try_into().or(Err("Deck requires exactly 17 tiles"))
+            }
+            _ => Err("Couldn't parse value as array"),
+        }
+    }
+}
+
+pub struct DeckConfigArray(Vec<DeckConfig>);
+
+impl TryFrom<&Table> for DeckConfigArray {
+    type Error = &'static str;
+
+    fn try_from(table: &Table) -> Result<Self, &'static str> {
+        let value = table
+            .get("decks")
+            .ok_or("Could not find list of decks in config")?;
+
+        match value {
+            Value::Array(value) => {
+                let array: Vec<DeckConfig> = value
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, e)| DeckConfig::try_from_entry(idx, e))
+                    .collect::<Result<_, _>>()?;
+
+                if array.is_empty() || array.len() > u8::MAX as usize {
+                    return Err("Config requires between 1 and 255 tile decks");
+                }
+
+                if array
+                    .iter()
+                    .filter_map(|deck| deck.unlocks_after)
+                    .any(|other| other as usize >= array.len())
+                {
+                    return Err("Deck unlocks_after must be the number of another deck");
+                }
+
+                // Following a chain of unlocks for longer than there are
+                // decks means it loops back on itself
+                for start in 0..array.len() {
+                    let mut deck = start;
+
+                    for _ in 0..array.len() {
+                        match array[deck].unlocks_after {
+                            Some(other) => deck = other as usize,
+                            None => break,
+                        }
+                    }
+
+                    if array[deck].unlocks_after.is_some() {
+                        return Err("Deck unlock rules can't form a cycle");
+                    }
+                }
+
+                Ok(DeckConfigArray(array))
+            }
+            _ => Err("Couldn't parse value as array"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TilesetConfig {
+    pub cross: CrossConfig,
+    pub deck: Vec<DeckConfig>,
+}
+
+impl TilesetConfig {
+    /// Every treasure id placed in the tileset, with where it's placed.
+    pub fn treasure_references(&self) -> Vec<TreasureReference> {
+        let mut references = self
+            .cross
+            .c
+            .treasure_references(TileLocation::Cross { arm: 'c', index: 0 });
+
+        for (arm, tiles) in self.cross.arms() {
+            for (index, tile) in tiles.iter().enumerate() {
+                references.extend(tile.treasure_references(TileLocation::Cross { arm, index }));
+            }
+        }
+
+        for (deck, deck_config) in self.deck.iter().enumerate() {
+            for (index, tile) in deck_config.tiles.iter().enumerate() {
+                references.extend(tile.treasure_references(TileLocation::Deck { deck, index }));
+            }
+        }
+
+        references
+    }
+    /// For each deck, the deck that must be empty before it can be drawn from.
+    pub fn deck_unlocks(&self) -> Vec<Option<u8>> {
+        self.deck.iter().map(|deck| deck.unlocks_after).collect()
+    }
+}
+
+impl TryFrom<&Table> for TilesetConfig {
+    type Error = &'static str;
+
+    fn try_from(table: &Table) -> Result<Self, Self::Error> {
+        let cross = CrossConfig::try_from(&table.get("cross"))?;
+        let deck = DeckConfigArray::try_from(table)?.0;
+
+        Ok(Self { cross, deck })
+    }
+}
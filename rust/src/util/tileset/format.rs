@@ -0,0 +1,435 @@
+use std::fmt::Write;
+
+use bitflags::parser::to_writer;
+use thiserror::Error;
+use toml::Table;
+use toml::Value;
+
+use crate::util::flags::OasisLayoutFlags;
+use crate::util::flags::OASIS_CONNECTION_FLAGS;
+use crate::util::flags::OASIS_CONNECTION_LABELS;
+use crate::util::tileset::DeckConfig;
+use crate::util::tileset::TileConfig;
+use crate::util::tileset::TilesetConfig;
+
+#[derive(Error, Debug)]
+pub enum FormatError {
+    #[error("Couldn't parse TOML: {0}")]
+    TomlError(#[from] toml::de::Error),
+    #[error("Couldn't parse JSON: {0}")]
+    JsonError(#[from] serde_json::Error),
+    #[error("Line {0}: {1}")]
+    NotationError(usize, &'static str),
+    #[error("Invalid tileset: {0}")]
+    TilesetError(&'static str),
+}
+
+const HEADER: &str = r#"# Treasures are determined based on cardinal direction
+# Each tile is allowed to have up to 4 non-overlapping oasis entries
+# Which are in a list called "oasis"
+# A single oasis is described by a u8 of bit flags as follows:
+# 0b0001 = NORTH
+# 0b0010 = EAST
+# 0b0100 = SOUTH
+# 0b1000 = WEST
+
+# Read the directions like you would read a map
+"#;
+
+const CROSS_COMMENT: &str = "# 4 lists describing each part of the starting cross
+# Numbered going outwards from the center
+";
+
+const DECKS_COMMENT: &str = r#"# Lists describing each of the "decks" used in the game
+# Each deck has a name and the color it's shown in, and optionally
+# icon = "res://..."   an icon shown below the name
+# unlocks_after = N    deck N must be empty before this deck can be drawn from
+"#;
+
+/// The oasis entries of a layout, e.g. `["N | W", "S | E"]`, up to the last
+/// non-empty one. Connections between two sides read like
+/// `OASIS_CONNECTION_LABELS`, other entries list their sides as N, E, S, W.
+pub fn oasis_entries(oasis: &OasisLayoutFlags) -> Vec<String> {
+    let groups = oasis.to_chunks();
+    let len = groups
+        .iter()
+        .rposition(|group| !group.is_empty())
+        .map_or(0, |idx| idx + 1);
+
+    groups[..len]
+        .iter()
+        .map(|group| {
+            if let Some(idx) = OASIS_CONNECTION_FLAGS.iter().position(|c| c == group) {
+                return OASIS_CONNECTION_LABELS[idx].to_owned();
+            }
+
+            let mut entry = String::new();
+            to_writer(group, &mut entry).expect("Writing to a String can't fail");
+            entry
+        })
+        .collect()
+}
+
+pub(super) fn quoted(value: &str) -> String {
+    Value::String(value.to_owned()).to_string()
+}
+
+fn tile_table(tile: &TileConfig) -> Table {
+    let mut table = Table::new();
+
+    if let Some(is_desert) = tile.is_desert {
+        table.insert("is_desert".into(), Value::Boolean(is_desert));
+    }
+
+    if let Some(oasis) = &tile.oasis {
+        let entries = oasis_entries(oasis)
+            .into_iter()
+            .map(Value::String)
+            .collect();
+
+        table.insert("oasis".into(), Value::Array(entries));
+    }
+
+    let treasures = [
+        ("treasure_n", &tile.treasure_n),
+        ("treasure_e", &tile.treasure_e),
+        ("treasure_s", &tile.treasure_s),
+        ("treasure_w", &tile.treasure_w),
+    ];
+
+    for (key, treasure) in treasures {
+        if let Some(treasure) = treasure {
+            table.insert(key.into(), Value::String(treasure.clone()));
+        }
+    }
+
+    table
+}
+
+fn tile_array(tiles: &[TileConfig]) -> Value {
+    Value::Array(tiles.iter().map(|t| Value::Table(tile_table(t))).collect())
+}
+
+/// `tileset` as the table `tileset.toml` parses into, for embedding it in
+/// other documents.
+pub fn to_table(tileset: &TilesetConfig) -> Table {
+    let mut cross = Table::new();
+
+    cross.insert("c".into(), Value::Table(tile_table(&tileset.cross.c)));
+
+    for (arm, tiles) in tileset.cross.arms() {
+        cross.insert(arm.to_string(), tile_array(tiles));
+    }
+
+    let decks = tileset
+        .deck
+        .iter()
+        .map(|deck| {
+            let mut table = Table::new();
+
+            table.insert("name".into(), Value::String(deck.name.clone()));
+            table.insert("color".into(), Value::String(deck.color.clone()));
+
+            if let Some(icon) = &deck.icon {
+                table.insert("icon".into(), Value::String(icon.clone()));
+            }
+
+            if let Some(unlocks_after) = deck.unlocks_after {
+                table.insert(
+                    "unlocks_after".into(),
+                    Value::Integer(unlocks_after as i64 + 1),
+                );
+            }
+
+            table.insert("deck".into(), tile_array(&deck.tiles));
+
+            Value::Table(table)
+        })
+        .collect();
+
+    let mut table = Table::new();
+
+    table.insert("cross".into(), Value::Table(cross));
+    table.insert("decks".into(), Value::Array(decks));
+
+    table
+}
+
+/// Reads a tileset from the contents of a `tileset.toml`.
+pub fn from_toml(input: &str) -> Result<TilesetConfig, FormatError> {
+    let table = toml::from_str::<Table>(input)?;
+
+    TilesetConfig::try_from(&table).map_err(FormatError::TilesetError)
+}
+
+/// Writes `tileset` as JSON, laid out like `tileset.toml`.
+pub fn to_json(tileset: &TilesetConfig) -> String {
+    serde_json::to_string_pretty(&to_table(tileset)).expect("A toml::Table is valid JSON")
+}
+
+/// Reads a tileset written by [`to_json`].
+pub fn from_json(input: &str) -> Result<TilesetConfig, FormatError> {
+    let table = serde_json::from_str::<Table>(input)?;
+
+    TilesetConfig::try_from(&table).map_err(FormatError::TilesetError)
+}
+
+fn write_tile(output: &mut String, header: &str, tile: &TileConfig) {
+    let _ = writeln!(output, "{header}");
+
+    if let Some(is_desert) = tile.is_desert {
+        let _ = writeln!(output, "is_desert = {is_desert}");
+    }
+
+    if let Some(oasis) = &tile.oasis {
+        let entries: Vec<String> = oasis_entries(oasis)
+            .iter()
+            .map(|entry| quoted(entry))
+            .collect();
+
+        let _ = writeln!(output, "oasis = [{}]", entries.join(", "));
+    }
+
+    let treasures = [
+        ("treasure_n", &tile.treasure_n),
+        ("treasure_e", &tile.treasure_e),
+        ("treasure_s", &tile.treasure_s),
+        ("treasure_w", &tile.treasure_w),
+    ];
+
+    for (key, treasure) in treasures {
+        if let Some(treasure) = treasure {
+            let _ = writeln!(output, "{key} = {}", quoted(treasure));
+        }
+    }
+}
+
+fn write_deck(output: &mut String, number: usize, deck: &DeckConfig) {
+    let _ = writeln!(output, "# Deck {number}\n[[decks]]");
+    let _ = writeln!(output, "name = {}", quoted(&deck.name));
+    let _ = writeln!(output, "color = {}", quoted(&deck.color));
+
+    if let Some(icon) = &deck.icon {
+        let _ = writeln!(output, "icon = {}", quoted(icon));
+    }
+
+    if let Some(unlocks_after) = deck.unlocks_after {
+        let _ = writeln!(output, "unlocks_after = {}", unlocks_after + 1);
+    }
+
+    let tiles: Vec<String> = deck
+        .tiles
+        .iter()
+        .enumerate()
+        .map(|(idx, tile)| {
+            let mut output = String::new();
+            write_tile(&mut output, &format!("# {}\n[[decks.deck]]", idx + 1), tile);
+            output
+        })
+        .collect();
+
+    output.push_str(&tiles.join("\n"));
+}
+
+/// The comments of a TOML file, without their `#` and surrounding spaces.
+/// A `#` inside a string doesn't start a comment.
+fn comments(input: &str) -> Vec<&str> {
+    let mut comments = vec![];
+    let mut rest = input;
+
+    while let Some(start) = rest.find(['#', '"', '\'']) {
+        rest = &rest[start..];
+
+        if let Some(comment) = rest.strip_prefix('#') {
+            let end = comment.find('\n').unwrap_or(comment.len());
+
+            comments.push(comment[..end].trim());
+            rest = &comment[end..];
+            continue;
+        }
+
+        let quote = if rest.starts_with("\"\"\"") || rest.starts_with("'''") {
+            &rest[..3]
+        } else {
+            &rest[..1]
+        };
+
+        rest = &rest[quote.len()..];
+
+        // Skips to the closing quote, past quotes escaped in basic strings
+        loop {
+            let Some(end) = rest.find(quote) else {
+                return comments;
+            };
+            let backslashes = rest[..end].chars().rev().take_while(|c| *c == '\\').count();
+
+            rest = &rest[end + quote.len()..];
+
+            if quote.starts_with('\'') || backslashes % 2 == 0 {
+                break;
+            }
+        }
+    }
+
+    comments
+}
+
+/// The comments of `original` that `canonical` doesn't have, which
+/// replacing the file with its canonical form would lose.
+pub fn lost_comments<'a>(original: &'a str, canonical: &str) -> Vec<&'a str> {
+    let kept = comments(canonical);
+
+    comments(original)
+        .into_iter()
+        .filter(|comment| !comment.is_empty() && !kept.contains(comment))
+        .collect()
+}
+
+/// Writes `tileset` as `tileset.toml` in canonical form: the shipped file's
+/// comments and table order, keys in a fixed order and oasis entries written
+/// as [`oasis_entries`].
+pub fn to_canonical_toml(tileset: &TilesetConfig) -> String {
+    let mut output = String::from(HEADER);
+
+    output.push('\n');
+    write_tile(
+        &mut output,
+        "# Configuration for the cross center\n[cross.c]",
+        &tileset.cross.c,
+    );
+    output.push('\n');
+    output.push_str(CROSS_COMMENT);
+
+    let arms: Vec<String> = tileset
+        .cross
+        .arms()
+        .into_iter()
+        .map(|(arm, tiles)| {
+            let mut output = String::new();
+
+            for (idx, tile) in tiles.iter().enumerate() {
+                let header = format!("# {}{}\n[[cross.{arm}]]", arm.to_ascii_uppercase(), idx + 1);
+
+                write_tile(&mut output, &header, tile);
+            }
+
+            output
+        })
+        .collect();
+
+    output.push_str(&arms.join("\n"));
+    output.push('\n');
+    output.push_str(DECKS_COMMENT);
+    output.push('\n');
+
+    let decks: Vec<String> = tileset
+        .deck
+        .iter()
+        .enumerate()
+        .map(|(idx, deck)| {
+            let mut output = String::new();
+            write_deck(&mut output, idx + 1, deck);
+            output
+        })
+        .collect();
+
+    output.push_str(&decks.join("\n\n"));
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+    use toml::Table;
+
+    use super::*;
+
+    const SHIPPED: &str = include_str!("../../../../godot/config/tileset.toml");
+
+    fn parse(input: &str) -> TilesetConfig {
+        TilesetConfig::try_from(&toml::from_str::<Table>(input).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn normalizing_twice_changes_nothing() {
+        let canonical = to_canonical_toml(&parse(SHIPPED));
+
+        assert_eq!(to_canonical_toml(&parse(&canonical)), canonical);
+    }
+
+    #[test]
+    fn normalizing_keeps_the_tileset() {
+        let shuffled = SHIPPED
+            .replacen(
+                "is_desert = false\noasis = [\"W\"]",
+                "oasis = [\"W\"]\nis_desert = false",
+                1,
+            )
+            .replacen("[\"N | E\"]", "[\"E | N\"]", 1);
+
+        assert_ne!(shuffled, SHIPPED);
+        assert_eq!(parse(&to_canonical_toml(&parse(&shuffled))), parse(SHIPPED));
+        assert_eq!(
+            to_canonical_toml(&parse(&shuffled)),
+            to_canonical_toml(&parse(SHIPPED))
+        );
+    }
+
+    #[test]
+    fn finds_the_comments_normalizing_would_lose() {
+        let commented = SHIPPED
+            .replacen("[[decks]]\n", "[[decks]]\n# Easy tiles first\n", 1)
+            .replacen("name = \"1\"", "name = \"#1\" # was \"1\"", 1);
+
+        assert_eq!(
+            lost_comments(SHIPPED, &to_canonical_toml(&parse(SHIPPED))),
+            Vec::<&str>::new()
+        );
+        assert_eq!(
+            lost_comments(&commented, &to_canonical_toml(&parse(&commented))),
+            ["Easy tiles first", "was \"1\""]
+        );
+    }
+
+    #[test]
+    fn round_trips_through_toml_and_json() {
+        let tileset = parse(SHIPPED);
+        let table = to_table(&tileset);
+
+        assert_eq!(TilesetConfig::try_from(&table).unwrap(), tileset);
+        assert_eq!(from_toml(&to_canonical_toml(&tileset)).unwrap(), tileset);
+        assert_eq!(from_json(&to_json(&tileset)).unwrap(), tileset);
+        assert_eq!(
+            from_toml(&toml::to_string(&table).unwrap()).unwrap(),
+            tileset
+        );
+    }
+
+    #[test]
+    fn reports_why_json_is_rejected() {
+        assert_matches!(from_json("{"), Err(FormatError::JsonError(_)));
+        assert_matches!(
+            from_json(r#"{"decks": []}"#),
+            Err(FormatError::TilesetError(_))
+        );
+    }
+
+    #[test]
+    fn writes_oasis_entries_in_direction_order() {
+        let oasis = OasisLayoutFlags::W1 | OasisLayoutFlags::N1 | OasisLayoutFlags::S2;
+        let three = OasisLayoutFlags::W1 | OasisLayoutFlags::S1 | OasisLayoutFlags::E1;
+
+        assert_eq!(oasis_entries(&oasis), ["N | W", "S"]);
+        assert_eq!(oasis_entries(&three), ["E | S | W"]);
+        assert_eq!(
+            oasis_entries(&(OasisLayoutFlags::E1 | OasisLayoutFlags::S1)),
+            ["S | E"]
+        );
+        assert_eq!(
+            oasis_entries(&OasisLayoutFlags::empty()),
+            Vec::<String>::new()
+        );
+        assert_eq!(oasis_entries(&OasisLayoutFlags::E2), ["", "E"]);
+    }
+}
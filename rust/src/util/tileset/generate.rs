@@ -0,0 +1,659 @@
+use thiserror::Error;
+
+use crate::util::flags::CardinalDirectionFlags;
+use crate::util::flags::OasisLayoutFlags;
+use crate::util::flags::OASIS_CONNECTION_FLAGS;
+use crate::util::tileset::stats::TilesetStats;
+use crate::util::tileset::CrossConfig;
+use crate::util::tileset::DeckConfig;
+use crate::util::tileset::TileConfig;
+use crate::util::tileset::TilesetConfig;
+use crate::util::tileset::DEFAULT_DECK_COLOR;
+
+const DECK_SIZE: usize = 17;
+const ARM_LENGTH: usize = 5;
+const SIDES: [CardinalDirectionFlags; 4] = [
+    CardinalDirectionFlags::N,
+    CardinalDirectionFlags::E,
+    CardinalDirectionFlags::S,
+    CardinalDirectionFlags::W,
+];
+// Keeps seeds that differ in few bits apart, and xorshift away from 0
+const SEED_MIX: u64 = 0x9e37_79b9_7f4a_7c15;
+// Placements tried before a layout counts as impossible
+const MAX_STEPS: usize = 100_000;
+// Shuffles of a deck's oases tried before giving up on it
+const MAX_ATTEMPTS: usize = 8;
+
+const DEFAULT_DOUBLE_OASIS_PERCENT: u8 = 25;
+const DEFAULT_TREASURE_PERCENT: u8 = 70;
+const DEFAULT_DEAD_END_WEIGHT: u32 = 4;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum GenerateError {
+    #[error("Couldn't lay out the oases of the cross")]
+    CrossError,
+    #[error("Couldn't fit the oases of deck {0} onto its tiles")]
+    DeckError(usize),
+    #[error("Couldn't place the treasures of deck {0}")]
+    TreasureError(usize),
+    #[error("Treasures are placed but no treasure has a weight")]
+    NoTreasuresError,
+    #[error("Oases are placed but no oasis pattern has a weight")]
+    NoOasisError,
+}
+
+/// What a generated tileset should look like. Weights are relative, so
+/// `[2, 1, ...]` places the first pattern twice as often as the second.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeneratorTargets {
+    /// Number of decks to generate
+    pub deck_count: usize,
+    /// Share of pure-desert tiles in each deck, in percent
+    pub desert_percent: u8,
+    /// Share of the pure-desert tiles that still get an oasis, in percent
+    pub desert_oasis_percent: u8,
+    /// Share of the other tiles that get a second oasis, in percent
+    pub double_oasis_percent: u8,
+    /// Share of oasis exits that hold a treasure, in percent
+    pub treasure_percent: u8,
+    /// Weight of each of `OASIS_CONNECTION_FLAGS`
+    pub connections: [u32; OASIS_CONNECTION_FLAGS.len()],
+    /// Weight of an oasis touching a single side
+    pub dead_ends: u32,
+    /// Weight of each treasure id
+    pub treasures: Vec<(String, u32)>,
+}
+
+impl From<&TilesetStats> for GeneratorTargets {
+    /// Targets that reproduce the balance and deck count of an existing
+    /// tileset, such as the shipped one.
+    fn from(tileset: &TilesetStats) -> Self {
+        let stats = &tileset.overall;
+
+        Self {
+            deck_count: tileset.decks.len(),
+            desert_percent: stats.desert_share().round() as u8,
+            desert_oasis_percent: stats.desert_oasis_share().round() as u8,
+            double_oasis_percent: DEFAULT_DOUBLE_OASIS_PERCENT,
+            treasure_percent: DEFAULT_TREASURE_PERCENT,
+            connections: stats.connections.map(|count| count as u32),
+            dead_ends: DEFAULT_DEAD_END_WEIGHT,
+            treasures: stats
+                .treasures
+                .iter()
+                .map(|(id, count)| (id.clone(), *count as u32))
+                .collect(),
+        }
+    }
+}
+
+/// xorshift64, so a seed gives the same tileset on every platform
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Self((seed ^ SEED_MIX).max(1))
+    }
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+    fn shuffle<T>(&mut self, items: &mut [T]) {
+        for idx in (1..items.len()).rev() {
+            items.swap(idx, self.below(idx + 1));
+        }
+    }
+    /// Orders `items` by drawing them one at a time, each with a chance
+    /// proportional to its weight. Items without weight are left out.
+    fn weighted_order<T>(&mut self, items: Vec<(T, u32)>) -> Vec<T> {
+        let mut items: Vec<(T, u32)> = items.into_iter().filter(|(_, w)| *w > 0).collect();
+        let mut ordered = vec![];
+
+        while !items.is_empty() {
+            let total: u64 = items.iter().map(|(_, w)| *w as u64).sum();
+            let mut pick = self.next() % total;
+            let idx = items
+                .iter()
+                .position(|(_, w)| {
+                    let hit = pick < *w as u64;
+                    pick = pick.saturating_sub(*w as u64);
+                    hit
+                })
+                .unwrap_or(0);
+
+            ordered.push(items.remove(idx).0);
+        }
+
+        ordered
+    }
+}
+
+/// Splits `total` in proportion to `weights`, handing what rounding leaves
+/// over to the largest remainders.
+fn apportion(total: usize, weights: &[u32]) -> Vec<usize> {
+    let sum: u64 = weights.iter().map(|w| *w as u64).sum();
+
+    if sum == 0 {
+        return vec![0; weights.len()];
+    }
+
+    let exact: Vec<u64> = weights.iter().map(|w| *w as u64 * total as u64).collect();
+    let mut counts: Vec<usize> = exact.iter().map(|e| (e / sum) as usize).collect();
+    let mut by_remainder: Vec<usize> = (0..weights.len()).collect();
+
+    by_remainder.sort_by_key(|idx| std::cmp::Reverse(exact[*idx] % sum));
+
+    for idx in by_remainder
+        .into_iter()
+        .take(total - counts.iter().sum::<usize>())
+    {
+        counts[idx] += 1;
+    }
+
+    counts
+}
+
+fn percent_of(total: usize, percent: u8) -> usize {
+    (total * percent.min(100) as usize + 50) / 100
+}
+
+/// Backtracking search: gives each variable the first value of its domain
+/// that `fits` the values chosen before it, and revisits earlier choices
+/// when a variable has none left.
+fn solve<T: Clone>(domains: &[Vec<T>], fits: impl Fn(&[T], &T) -> bool) -> Option<Vec<T>> {
+    let mut assigned: Vec<T> = vec![];
+    let mut next_value = vec![0; domains.len()];
+
+    for _ in 0..MAX_STEPS {
+        let variable = assigned.len();
+
+        if variable == domains.len() {
+            return Some(assigned);
+        }
+
+        let found = domains[variable][next_value[variable]..]
+            .iter()
+            .position(|value| fits(&assigned, value));
+
+        match found {
+            Some(offset) => {
+                let idx = next_value[variable] + offset;
+
+                next_value[variable] = idx + 1;
+                assigned.push(domains[variable][idx].clone());
+            }
+            None => {
+                next_value[variable] = 0;
+                assigned.pop()?;
+            }
+        }
+    }
+
+    None
+}
+
+fn layout(groups: &[CardinalDirectionFlags]) -> Option<OasisLayoutFlags> {
+    if groups.is_empty() {
+        return None;
+    }
+
+    Some(
+        groups
+            .iter()
+            .enumerate()
+            .map(|(idx, group)| OasisLayoutFlags::from_cardinal_direction_flags(group, idx as u8))
+            .fold(OasisLayoutFlags::empty(), |acc, flags| acc | flags),
+    )
+}
+
+/// The oasis patterns a tile can get, with their weights.
+fn patterns(targets: &GeneratorTargets) -> Vec<(CardinalDirectionFlags, u32)> {
+    let dead_end = targets.dead_ends.div_ceil(SIDES.len() as u32);
+
+    OASIS_CONNECTION_FLAGS
+        .into_iter()
+        .zip(targets.connections)
+        .chain(SIDES.into_iter().map(|side| (side, dead_end)))
+        .collect()
+}
+
+fn tile(
+    is_cross: bool,
+    is_desert: bool,
+    groups: &[CardinalDirectionFlags],
+    treasures: Option<[String; 4]>,
+) -> TileConfig {
+    let [treasure_n, treasure_e, treasure_s, treasure_w] = match treasures {
+        Some(treasures) => treasures.map(Some),
+        None => [None, None, None, None],
+    };
+
+    TileConfig {
+        is_cross: Some(is_cross),
+        is_desert: Some(is_desert),
+        oasis: layout(groups),
+        treasure_n,
+        treasure_e,
+        treasure_s,
+        treasure_w,
+    }
+}
+
+/// An arm of the cross, going outwards from the center towards `outward`.
+/// Oases that leave a tile along the arm continue on the next one, and the
+/// arm's end, where a caravan starts, stays clear.
+fn generate_arm(
+    rng: &mut Rng,
+    patterns: &[(CardinalDirectionFlags, u32)],
+    outward: CardinalDirectionFlags,
+) -> Option<[TileConfig; ARM_LENGTH]> {
+    // About half of the tiles inside the arm get an oasis
+    let clear_weight = patterns.iter().map(|(_, w)| *w).sum::<u32>().max(1);
+    let inward = outward.rotate_left(2);
+    let domains: Vec<Vec<CardinalDirectionFlags>> = (0..ARM_LENGTH)
+        .map(|idx| {
+            if idx == ARM_LENGTH - 1 {
+                return vec![CardinalDirectionFlags::empty()];
+            }
+
+            let mut options = patterns.to_vec();
+            options.push((CardinalDirectionFlags::empty(), clear_weight));
+
+            rng.weighted_order(options)
+        })
+        .collect();
+
+    let arm = solve(&domains, |previous, group| {
+        // The center has no oasis
+        let leaves_previous = previous
+            .last()
+            .is_some_and(|previous| previous.contains(outward.clone()));
+
+        leaves_previous == group.contains(inward.clone())
+    })?;
+
+    let tiles: Vec<TileConfig> = arm
+        .into_iter()
+        .map(|group| {
+            let groups: Vec<CardinalDirectionFlags> = Some(group)
+                .filter(|group| !group.is_empty())
+                .into_iter()
+                .collect();
+
+            tile(true, false, &groups, None)
+        })
+        .collect();
+
+    tiles.try_into().ok()
+}
+
+fn generate_cross(
+    rng: &mut Rng,
+    patterns: &[(CardinalDirectionFlags, u32)],
+) -> Result<CrossConfig, GenerateError> {
+    let [n, e, s, w] = SIDES.map(|side| generate_arm(rng, patterns, side));
+
+    Ok(CrossConfig {
+        c: tile(true, false, &[], None),
+        n: n.ok_or(GenerateError::CrossError)?,
+        e: e.ok_or(GenerateError::CrossError)?,
+        s: s.ok_or(GenerateError::CrossError)?,
+        w: w.ok_or(GenerateError::CrossError)?,
+    })
+}
+
+/// Oases for `tiles` tiles, one each plus the requested share of second
+/// ones, with patterns in proportion to their weights. Two oases on a tile
+/// never share a side.
+fn generate_oases(
+    rng: &mut Rng,
+    patterns: &[(CardinalDirectionFlags, u32)],
+    tiles: usize,
+    targets: &GeneratorTargets,
+) -> Option<Vec<Vec<CardinalDirectionFlags>>> {
+    let count = tiles + percent_of(tiles, targets.double_oasis_percent);
+    let weights: Vec<u32> = patterns.iter().map(|(_, w)| *w).collect();
+    let mut oases: Vec<CardinalDirectionFlags> = apportion(count, &weights)
+        .into_iter()
+        .zip(patterns)
+        .flat_map(|(count, (group, _))| std::iter::repeat_n(group.clone(), count))
+        .collect();
+
+    rng.shuffle(&mut oases);
+
+    let (first, second) = oases.split_at(tiles);
+    let domains: Vec<Vec<usize>> = second
+        .iter()
+        .map(|_| {
+            let mut tiles: Vec<usize> = (0..tiles).collect();
+            rng.shuffle(&mut tiles);
+            tiles
+        })
+        .collect();
+
+    let placement = solve(&domains, |placed, tile| {
+        let oasis = &second[placed.len()];
+
+        !placed.contains(tile) && !first[*tile].intersects(oasis.clone())
+    })?;
+
+    let mut tiles: Vec<Vec<CardinalDirectionFlags>> =
+        first.iter().map(|oasis| vec![oasis.clone()]).collect();
+
+    for (oasis, tile) in second.iter().zip(placement) {
+        tiles[tile].push(oasis.clone());
+    }
+
+    Some(tiles)
+}
+
+/// Puts the requested share of treasures on the sides where oases leave
+/// `tiles`, where they show, with ids in proportion to their weights. A
+/// tile never holds the same treasure twice.
+fn generate_treasures(
+    rng: &mut Rng,
+    tiles: &[Vec<CardinalDirectionFlags>],
+    targets: &GeneratorTargets,
+) -> Option<Vec<[String; 4]>> {
+    let exits: Vec<(usize, usize)> = tiles
+        .iter()
+        .enumerate()
+        .flat_map(|(tile, groups)| {
+            SIDES
+                .iter()
+                .enumerate()
+                .filter(|(_, side)| groups.iter().any(|group| group.contains((*side).clone())))
+                .map(move |(side, _)| (tile, side))
+        })
+        .collect();
+    let count = percent_of(exits.len(), targets.treasure_percent);
+    let weights: Vec<u32> = targets.treasures.iter().map(|(_, w)| *w).collect();
+    let mut ids: Vec<&String> = apportion(count, &weights)
+        .into_iter()
+        .zip(&targets.treasures)
+        .flat_map(|(count, (id, _))| std::iter::repeat_n(id, count))
+        .collect();
+
+    rng.shuffle(&mut ids);
+
+    let domains: Vec<Vec<(usize, usize)>> = ids
+        .iter()
+        .map(|_| {
+            let mut exits = exits.clone();
+            rng.shuffle(&mut exits);
+            exits
+        })
+        .collect();
+
+    let placement = solve(&domains, |placed, (tile, side)| {
+        let id = ids[placed.len()];
+
+        placed
+            .iter()
+            .enumerate()
+            .all(|(other, (other_tile, other_side))| {
+                let same_side = other_tile == tile && other_side == side;
+                let same_treasure = other_tile == tile && ids[other] == id;
+
+                !same_side && !same_treasure
+            })
+    })?;
+
+    let mut treasures = vec![[(); 4].map(|_| String::from("none")); tiles.len()];
+
+    for (id, (tile, side)) in ids.into_iter().zip(placement) {
+        treasures[tile][side] = id.clone();
+    }
+
+    Some(treasures)
+}
+
+fn generate_deck(
+    rng: &mut Rng,
+    patterns: &[(CardinalDirectionFlags, u32)],
+    deck: usize,
+    targets: &GeneratorTargets,
+) -> Result<DeckConfig, GenerateError> {
+    let desert_tiles = percent_of(DECK_SIZE, targets.desert_percent);
+    let desert_oasis_tiles = percent_of(desert_tiles, targets.desert_oasis_percent);
+    // Pure-desert tiles with an oasis come last, after the other tiles
+    let oasis_tiles = DECK_SIZE - desert_tiles + desert_oasis_tiles;
+    let first_desert = oasis_tiles - desert_oasis_tiles;
+
+    let oases = (0..MAX_ATTEMPTS)
+        .find_map(|_| generate_oases(rng, patterns, oasis_tiles, targets))
+        .ok_or(GenerateError::DeckError(deck + 1))?;
+    let treasures =
+        generate_treasures(rng, &oases, targets).ok_or(GenerateError::TreasureError(deck + 1))?;
+
+    let mut tiles: Vec<TileConfig> = oases
+        .iter()
+        .zip(treasures)
+        .enumerate()
+        .map(|(idx, (groups, treasures))| tile(false, idx >= first_desert, groups, Some(treasures)))
+        .chain((desert_oasis_tiles..desert_tiles).map(|_| {
+            tile(
+                false,
+                true,
+                &[],
+                Some([(); 4].map(|_| String::from("none"))),
+            )
+        }))
+        .collect();
+
+    rng.shuffle(&mut tiles);
+
+    Ok(DeckConfig {
+        name: (deck + 1).to_string(),
+        color: DEFAULT_DECK_COLOR.to_owned(),
+        icon: None,
+        unlocks_after: None,
+        tiles: tiles
+            .try_into()
+            .map_err(|_| GenerateError::DeckError(deck + 1))?,
+    })
+}
+
+/// Generates a complete tileset from `seed`: a cross whose oases line up
+/// along its arms, and decks that follow `targets`. The same seed and
+/// targets always give the same tileset. Decks are named by their number.
+pub fn generate(seed: u64, targets: &GeneratorTargets) -> Result<TilesetConfig, GenerateError> {
+    let patterns = patterns(targets);
+
+    if patterns.iter().all(|(_, w)| *w == 0) {
+        return Err(GenerateError::NoOasisError);
+    }
+
+    if targets.treasure_percent > 0 && targets.treasures.iter().all(|(_, w)| *w == 0) {
+        return Err(GenerateError::NoTreasuresError);
+    }
+
+    let mut rng = Rng::new(seed);
+    let cross = generate_cross(&mut rng, &patterns)?;
+    let deck = (0..targets.deck_count)
+        .map(|deck| generate_deck(&mut rng, &patterns, deck, targets))
+        .collect::<Result<Vec<DeckConfig>, GenerateError>>()?;
+
+    Ok(TilesetConfig { cross, deck })
+}
+
+/// Gives the decks of `generated` the names, colors, icons and unlock rules
+/// of the decks of `template`.
+pub fn keep_deck_metadata(generated: &mut TilesetConfig, template: &TilesetConfig) {
+    for (deck, template) in generated.deck.iter_mut().zip(&template.deck) {
+        deck.name = template.name.clone();
+        deck.color = template.color.clone();
+        deck.icon = template.icon.clone();
+        deck.unlocks_after = template.unlocks_after;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+    use toml::Table;
+
+    use super::*;
+    use crate::util::tileset::format::to_canonical_toml;
+    use crate::util::tileset::lint::lint;
+    use crate::util::tileset::stats::TilesetStats;
+    use crate::util::treasures::TreasureCatalog;
+
+    fn shipped() -> TilesetConfig {
+        let table =
+            toml::from_str::<Table>(include_str!("../../../../godot/config/tileset.toml")).unwrap();
+
+        TilesetConfig::try_from(&table).unwrap()
+    }
+
+    fn targets() -> GeneratorTargets {
+        GeneratorTargets::from(&TilesetStats::from(&shipped()))
+    }
+
+    #[test_case(0)]
+    #[test_case(1)]
+    #[test_case(0x5a4a_5241)]
+    #[test_case(u64::MAX)]
+    fn generated_tilesets_are_valid(seed: u64) {
+        let treasures =
+            toml::from_str::<Table>(include_str!("../../../../godot/config/treasures.toml"))
+                .unwrap();
+        let generated = generate(seed, &targets()).unwrap();
+        let written = toml::from_str::<Table>(&to_canonical_toml(&generated)).unwrap();
+
+        let (parsed, warnings) =
+            lint(&written, &TreasureCatalog::try_from(&treasures).unwrap()).unwrap();
+
+        assert_eq!(parsed, generated);
+        assert_eq!(warnings, vec![]);
+    }
+
+    #[test]
+    fn a_seed_gives_the_same_tileset() {
+        assert_eq!(generate(7, &targets()), generate(7, &targets()));
+        assert_ne!(generate(7, &targets()), generate(8, &targets()));
+    }
+
+    #[test]
+    fn decks_follow_the_targets() {
+        let targets = GeneratorTargets {
+            deck_count: 3,
+            desert_percent: 30,
+            desert_oasis_percent: 0,
+            double_oasis_percent: 0,
+            treasure_percent: 100,
+            connections: [1, 0, 0, 0, 0, 0],
+            dead_ends: 0,
+            treasures: vec![(String::from("water"), 1), (String::from("rumors"), 1)],
+        };
+        let stats = TilesetStats::from(&generate(3, &targets).unwrap());
+
+        assert_eq!(stats.decks.len(), 3);
+
+        for (_, deck) in &stats.decks {
+            assert_eq!(deck.desert_tiles, 5);
+            assert_eq!(deck.oases, 12);
+            assert_eq!(deck.connections, [12, 0, 0, 0, 0, 0]);
+            assert_eq!(deck.treasures.get("water"), Some(&12));
+            assert_eq!(deck.treasures.get("rumors"), Some(&12));
+        }
+    }
+
+    #[test]
+    fn desert_tiles_follow_the_targets() {
+        let targets = GeneratorTargets {
+            desert_oasis_percent: 40,
+            ..targets()
+        };
+        let stats = TilesetStats::from(&generate(5, &targets).unwrap());
+
+        for (_, deck) in &stats.decks {
+            assert_eq!(
+                deck.desert_tiles,
+                percent_of(DECK_SIZE, targets.desert_percent)
+            );
+            assert_eq!(
+                deck.desert_tiles_with_oasis,
+                percent_of(deck.desert_tiles, 40)
+            );
+        }
+    }
+
+    #[test]
+    fn shipped_desert_tiles_set_the_targets() {
+        let stats = TilesetStats::from(&shipped());
+
+        assert_eq!(stats.overall.desert_tiles_with_oasis, 4);
+        assert_eq!(targets().desert_oasis_percent, 25);
+    }
+
+    #[test]
+    fn cross_oases_line_up_along_the_arms() {
+        for seed in 0..50 {
+            let cross = generate(seed, &targets()).unwrap().cross;
+
+            for ((_, arm), outward) in cross.arms().into_iter().zip(SIDES) {
+                let exits: Vec<CardinalDirectionFlags> = arm
+                    .iter()
+                    .map(|tile| {
+                        tile.oasis.clone().map_or(
+                            CardinalDirectionFlags::empty(),
+                            CardinalDirectionFlags::from,
+                        )
+                    })
+                    .collect();
+
+                assert!(!exits[0].contains(outward.rotate_left(2)));
+                assert!(exits[ARM_LENGTH - 1].is_empty());
+
+                for pair in exits.windows(2) {
+                    assert_eq!(
+                        pair[0].contains(outward.clone()),
+                        pair[1].contains(outward.rotate_left(2))
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_targets_without_weights() {
+        let mut targets = targets();
+        targets.treasures = vec![];
+
+        assert_eq!(generate(1, &targets), Err(GenerateError::NoTreasuresError));
+
+        targets.connections = [0; 6];
+        targets.dead_ends = 0;
+
+        assert_eq!(generate(1, &targets), Err(GenerateError::NoOasisError));
+    }
+
+    #[test]
+    fn apportions_by_largest_remainder() {
+        assert_eq!(apportion(10, &[1, 1, 1]), [4, 3, 3]);
+        assert_eq!(apportion(5, &[0, 3, 1]), [0, 4, 1]);
+        assert_eq!(apportion(3, &[0, 0]), [0, 0]);
+    }
+
+    #[test]
+    fn backtracks_out_of_dead_ends() {
+        // The first value of the first variable leaves nothing for the second
+        let domains = vec![vec![1, 2], vec![1]];
+
+        assert_eq!(
+            solve(&domains, |placed, v| !placed.contains(v)),
+            Some(vec![2, 1])
+        );
+        assert_eq!(
+            solve(&[vec![1], vec![1]], |placed, v| !placed.contains(v)),
+            None
+        );
+    }
+}
@@ -0,0 +1,247 @@
+use std::fmt::Display;
+
+use toml::Table;
+use toml::Value;
+
+use crate::util::tileset::TileConfig;
+use crate::util::tileset::TilesetConfig;
+use crate::util::tileset::TilesetError;
+use crate::util::treasures::TileLocation;
+use crate::util::treasures::TreasureCatalog;
+
+const TOP_LEVEL_KEYS: [&str; 2] = ["cross", "decks"];
+const CROSS_KEYS: [&str; 5] = ["c", "n", "e", "s", "w"];
+const DECK_KEYS: [&str; 5] = ["name", "color", "icon", "unlocks_after", "deck"];
+const TILE_KEYS: [&str; 6] = [
+    "is_desert",
+    "oasis",
+    "treasure_n",
+    "treasure_e",
+    "treasure_s",
+    "treasure_w",
+];
+
+/// Something the game accepts in a tileset but that's likely a mistake.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LintWarning {
+    /// A key the game doesn't read, e.g. a misspelled `treasure_x`
+    UnknownKey { table: String, key: String },
+    /// A key whose value the game ignores because it couldn't be parsed
+    IgnoredValue {
+        location: TileLocation,
+        key: &'static str,
+    },
+    /// Two oasis entries of a tile share a side
+    OverlappingOasis { location: TileLocation },
+    /// A tile without `is_desert`
+    MissingDesertFlag { location: TileLocation },
+}
+
+impl Display for LintWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LintWarning::UnknownKey { table, key } => write!(f, "{table}: unknown key {key}"),
+            LintWarning::IgnoredValue { location, key } => {
+                write!(f, "{location}: invalid {key} is ignored")
+            }
+            LintWarning::OverlappingOasis { location } => {
+                write!(f, "{location}: oasis entries overlap")
+            }
+            LintWarning::MissingDesertFlag { location } => {
+                write!(f, "{location}: missing is_desert")
+            }
+        }
+    }
+}
+
+fn unknown_keys(table: &Table, allowed: &[&str], name: &str) -> Vec<LintWarning> {
+    table
+        .keys()
+        .filter(|key| !allowed.contains(&key.as_str()))
+        .map(|key| LintWarning::UnknownKey {
+            table: name.to_owned(),
+            key: key.clone(),
+        })
+        .collect()
+}
+
+fn lint_tile(raw: Option<&Value>, tile: &TileConfig, location: TileLocation) -> Vec<LintWarning> {
+    let Some(raw) = raw.and_then(Value::as_table) else {
+        return vec![];
+    };
+
+    let mut warnings = unknown_keys(raw, &TILE_KEYS, &location.to_string());
+
+    let parsed = [
+        tile.is_desert.is_some(),
+        tile.oasis.is_some(),
+        tile.treasure_n.is_some(),
+        tile.treasure_e.is_some(),
+        tile.treasure_s.is_some(),
+        tile.treasure_w.is_some(),
+    ];
+
+    for (key, parsed) in TILE_KEYS.into_iter().zip(parsed) {
+        if raw.contains_key(key) && !parsed {
+            warnings.push(LintWarning::IgnoredValue {
+                location: location.clone(),
+                key,
+            });
+        }
+    }
+
+    if !raw.contains_key("is_desert") {
+        warnings.push(LintWarning::MissingDesertFlag {
+            location: location.clone(),
+        });
+    }
+
+    if let Some(oasis) = &tile.oasis {
+        let groups = oasis.to_chunks();
+        let overlaps = groups.iter().enumerate().any(|(idx, group)| {
+            groups[idx + 1..]
+                .iter()
+                .any(|other| group.intersects(other.clone()))
+        });
+
+        if overlaps {
+            warnings.push(LintWarning::OverlappingOasis { location });
+        }
+    }
+
+    warnings
+}
+
+/// Runs the game's own validation over a `tileset.toml`, including its
+/// treasures, then looks for values the game would silently ignore.
+pub fn lint(
+    table: &Table,
+    treasures: &TreasureCatalog,
+) -> Result<(TilesetConfig, Vec<LintWarning>), TilesetError> {
+    let tileset = TilesetConfig::try_from(table).map_err(TilesetError::ParseError)?;
+
+    treasures.validate(tileset.treasure_references())?;
+
+    let mut warnings = unknown_keys(table, &TOP_LEVEL_KEYS, "top level");
+    let raw_cross = table.get("cross").and_then(Value::as_table);
+
+    if let Some(raw_cross) = raw_cross {
+        warnings.extend(unknown_keys(raw_cross, &CROSS_KEYS, "cross"));
+        warnings.extend(lint_tile(
+            raw_cross.get("c"),
+            &tileset.cross.c,
+            TileLocation::Cross { arm: 'c', index: 0 },
+        ));
+    }
+
+    for (arm, tiles) in tileset.cross.arms() {
+        let raw_tiles = raw_cross
+            .and_then(|cross| cross.get(&arm.to_string()))
+            .and_then(Value::as_array);
+
+        for (index, tile) in tiles.iter().enumerate() {
+            let raw = raw_tiles.and_then(|raw| raw.get(index));
+
+            warnings.extend(lint_tile(raw, tile, TileLocation::Cross { arm, index }));
+        }
+    }
+
+    let raw_decks = table.get("decks").and_then(Value::as_array);
+
+    for (deck, deck_config) in tileset.deck.iter().enumerate() {
+        let raw_deck = raw_decks
+            .and_then(|decks| decks.get(deck))
+            .and_then(Value::as_table);
+
+        if let Some(raw_deck) = raw_deck {
+            warnings.extend(unknown_keys(
+                raw_deck,
+                &DECK_KEYS,
+                &format!("deck {}", deck + 1),
+            ));
+        }
+
+        let raw_tiles = raw_deck
+            .and_then(|raw| raw.get("deck"))
+            .and_then(Value::as_array);
+
+        for (index, tile) in deck_config.tiles.iter().enumerate() {
+            let raw = raw_tiles.and_then(|raw| raw.get(index));
+
+            warnings.extend(lint_tile(raw, tile, TileLocation::Deck { deck, index }));
+        }
+    }
+
+    Ok((tileset, warnings))
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+
+    use super::*;
+
+    const SHIPPED: &str = include_str!("../../../../godot/config/tileset.toml");
+
+    fn catalog() -> TreasureCatalog {
+        let table =
+            toml::from_str::<Table>(include_str!("../../../../godot/config/treasures.toml"))
+                .unwrap();
+
+        TreasureCatalog::try_from(&table).unwrap()
+    }
+
+    fn lint_str(input: &str) -> Result<Vec<LintWarning>, TilesetError> {
+        lint(&toml::from_str::<Table>(input).unwrap(), &catalog()).map(|(_, warnings)| warnings)
+    }
+
+    #[test]
+    fn the_shipped_tileset_is_clean() {
+        assert_eq!(lint_str(SHIPPED).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn reports_what_the_game_would_ignore() {
+        let edited = SHIPPED
+            .replacen("treasure_w = \"none\"", "treasure_x = \"none\"", 1)
+            .replacen("oasis = [\"E | S\"]", "oasis = [\"E | Q\"]", 1)
+            .replacen(
+                "oasis = [\"N\", \"S | E\"]",
+                "oasis = [\"N | E\", \"S | E\"]",
+                1,
+            );
+
+        let warnings = lint_str(&edited).unwrap();
+        let first = TileLocation::Deck { deck: 0, index: 0 };
+
+        assert!(warnings.contains(&LintWarning::UnknownKey {
+            table: String::from("deck 1 tile 1"),
+            key: String::from("treasure_x"),
+        }));
+        assert!(warnings.contains(&LintWarning::IgnoredValue {
+            location: first,
+            key: "oasis",
+        }));
+        assert!(warnings.contains(&LintWarning::OverlappingOasis {
+            location: TileLocation::Deck { deck: 0, index: 1 },
+        }));
+        assert_eq!(
+            warnings[0].to_string(),
+            "deck 1 tile 1: unknown key treasure_x"
+        );
+    }
+
+    #[test]
+    fn fails_on_what_the_game_would_reject() {
+        let undefined = SHIPPED.replacen("\"goods:salt\"", "\"goods:gold\"", 1);
+
+        assert_matches!(
+            lint_str(&undefined),
+            Err(TilesetError::UndefinedTreasureError(_))
+        );
+        assert_matches!(
+            lint_str("[cross.c]\nis_desert = false"),
+            Err(TilesetError::ParseError(_))
+        );
+    }
+}
@@ -0,0 +1,311 @@
+use toml::Table;
+use toml::Value;
+
+use crate::util::tileset::format::oasis_entries;
+use crate::util::tileset::format::quoted;
+use crate::util::tileset::format::FormatError;
+use crate::util::tileset::TileConfig;
+use crate::util::tileset::TilesetConfig;
+
+const HEADER: &str = "# One tile per line: OASIS TREASURES [desert]
+# OASIS lists the oasis entries split by `:`, `[]` for an empty list or `-`
+# for none
+# TREASURES is the treasure id on the N, E, S and W side, `-` for none
+# e.g. `N|E:W goods:salt,-,rumors,-`
+";
+const NO_OASIS: &str = "-";
+const EMPTY_OASIS: &str = "[]";
+const NO_TREASURE: &str = "-";
+const DESERT: &str = "desert";
+const CROSS_ARMS: [&str; 5] = ["c", "n", "e", "s", "w"];
+
+fn write_tile(tile: &TileConfig) -> String {
+    let mut line = match tile.oasis.as_ref().map(oasis_entries) {
+        Some(entries) if entries.is_empty() => String::from(EMPTY_OASIS),
+        Some(entries) => entries.join(":").replace(' ', ""),
+        None => String::from(NO_OASIS),
+    };
+    let treasures = [
+        &tile.treasure_n,
+        &tile.treasure_e,
+        &tile.treasure_s,
+        &tile.treasure_w,
+    ];
+
+    // Tiles of the cross have no treasure keys at all
+    if treasures.iter().any(|treasure| treasure.is_some()) {
+        let treasures: Vec<&str> = treasures
+            .iter()
+            .map(|treasure| match treasure.as_deref() {
+                Some("none") => NO_TREASURE,
+                Some(id) => id,
+                None => "",
+            })
+            .collect();
+
+        line.push(' ');
+        line.push_str(&treasures.join(","));
+    }
+
+    if tile.is_desert == Some(true) {
+        line.push(' ');
+        line.push_str(DESERT);
+    }
+
+    line
+}
+
+/// Writes `tileset` in compact notation: a `cross` line and a line per
+/// cross tile prefixed with its arm, then a `deck` line with each deck's
+/// metadata and a line per tile. Tiles without `is_desert` read back as not
+/// desert, and treasure ids can't hold spaces or commas.
+pub fn to_notation(tileset: &TilesetConfig) -> String {
+    let mut lines = vec![String::from("cross")];
+
+    lines.push(format!("c {}", write_tile(&tileset.cross.c)));
+
+    for (arm, tiles) in tileset.cross.arms() {
+        lines.extend(
+            tiles
+                .iter()
+                .map(|tile| format!("{arm} {}", write_tile(tile))),
+        );
+    }
+
+    for deck in &tileset.deck {
+        let mut metadata = vec![
+            format!("name = {}", quoted(&deck.name)),
+            format!("color = {}", quoted(&deck.color)),
+        ];
+
+        if let Some(icon) = &deck.icon {
+            metadata.push(format!("icon = {}", quoted(icon)));
+        }
+
+        if let Some(unlocks_after) = deck.unlocks_after {
+            metadata.push(format!("unlocks_after = {}", unlocks_after + 1));
+        }
+
+        lines.push(String::new());
+        lines.push(format!("deck {{ {} }}", metadata.join(", ")));
+        lines.extend(deck.tiles.iter().map(write_tile));
+    }
+
+    format!("{HEADER}\n{}\n", lines.join("\n"))
+}
+
+fn read_tile<'a>(mut tokens: impl Iterator<Item = &'a str>) -> Result<Table, &'static str> {
+    let mut table = Table::new();
+    let oasis = tokens.next().ok_or("Expected a tile")?;
+
+    match oasis {
+        NO_OASIS => {}
+        EMPTY_OASIS => {
+            table.insert("oasis".into(), Value::Array(vec![]));
+        }
+        oasis => {
+            let entries = oasis.split(':').map(|e| Value::String(e.to_owned()));
+
+            table.insert("oasis".into(), Value::Array(entries.collect()));
+        }
+    }
+
+    let mut is_desert = false;
+
+    for token in tokens {
+        if token == DESERT && !is_desert {
+            is_desert = true;
+            continue;
+        }
+
+        let treasures: Vec<&str> = token.split(',').collect();
+
+        if treasures.len() != 4 || table.contains_key("treasure_n") || is_desert {
+            return Err("Expected four treasures split by `,`, then `desert` for desert tiles");
+        }
+
+        for (key, treasure) in ["treasure_n", "treasure_e", "treasure_s", "treasure_w"]
+            .into_iter()
+            .zip(treasures)
+        {
+            let id = match treasure {
+                "" => continue,
+                NO_TREASURE => "none",
+                id => id,
+            };
+
+            table.insert(key.into(), Value::String(id.to_owned()));
+        }
+    }
+
+    table.insert("is_desert".into(), Value::Boolean(is_desert));
+
+    Ok(table)
+}
+
+fn read_deck_metadata(metadata: &str) -> Result<Table, &'static str> {
+    let table = toml::from_str::<Table>(&format!("deck = {metadata}"))
+        .map_err(|_| "Expected deck metadata as an inline table, e.g. `{ name = \"1\" }`")?;
+
+    match table.get("deck") {
+        Some(Value::Table(deck)) if !deck.contains_key("deck") => Ok(deck.clone()),
+        _ => Err("Expected deck metadata as an inline table, e.g. `{ name = \"1\" }`"),
+    }
+}
+
+fn push_tile(table: &mut Table, key: &str, tile: Table) {
+    if let Value::Array(tiles) = table.entry(key).or_insert_with(|| Value::Array(vec![])) {
+        tiles.push(Value::Table(tile));
+    }
+}
+
+/// Reads a tileset written by [`to_notation`]. Blank lines and lines
+/// starting with `#` are skipped.
+pub fn from_notation(input: &str) -> Result<TilesetConfig, FormatError> {
+    let mut cross: Option<Table> = None;
+    let mut decks: Vec<Table> = vec![];
+
+    for (idx, line) in input.lines().enumerate() {
+        let error = |message| FormatError::NotationError(idx + 1, message);
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if line == "cross" {
+            if cross.is_some() || !decks.is_empty() {
+                return Err(error("The cross must come once, before the decks"));
+            }
+
+            cross = Some(Table::new());
+            continue;
+        }
+
+        if let Some(metadata) = line.strip_prefix("deck ") {
+            let mut deck = read_deck_metadata(metadata).map_err(error)?;
+
+            deck.insert("deck".into(), Value::Array(vec![]));
+            decks.push(deck);
+            continue;
+        }
+
+        if let Some(deck) = decks.last_mut() {
+            let tile = read_tile(line.split_whitespace()).map_err(error)?;
+
+            push_tile(deck, "deck", tile);
+            continue;
+        }
+
+        let Some(cross) = cross.as_mut() else {
+            return Err(error("Expected `cross` before the first tile"));
+        };
+        let mut tokens = line.split_whitespace();
+        let arm = tokens
+            .next()
+            .filter(|arm| CROSS_ARMS.contains(arm))
+            .ok_or(error(
+                "Expected a cross tile to start with its arm: c, n, e, s or w",
+            ))?;
+        let tile = read_tile(tokens).map_err(error)?;
+
+        if arm == "c" {
+            cross.insert(arm.into(), Value::Table(tile));
+        } else {
+            push_tile(cross, arm, tile);
+        }
+    }
+
+    let mut table = Table::new();
+
+    if let Some(cross) = cross {
+        table.insert("cross".into(), Value::Table(cross));
+    }
+
+    table.insert(
+        "decks".into(),
+        Value::Array(decks.into_iter().map(Value::Table).collect()),
+    );
+
+    TilesetConfig::try_from(&table).map_err(FormatError::TilesetError)
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+    use test_case::test_case;
+
+    use super::*;
+    use crate::util::flags::OasisLayoutFlags;
+    use crate::util::tileset::format::from_toml;
+    use crate::util::tileset::generate::generate;
+    use crate::util::tileset::generate::GeneratorTargets;
+    use crate::util::tileset::stats::TilesetStats;
+
+    fn shipped() -> TilesetConfig {
+        from_toml(include_str!("../../../../godot/config/tileset.toml")).unwrap()
+    }
+
+    #[test]
+    fn writes_a_line_per_tile() {
+        let tile = TileConfig {
+            is_cross: Some(false),
+            is_desert: Some(false),
+            oasis: Some(OasisLayoutFlags::N1 | OasisLayoutFlags::E1 | OasisLayoutFlags::W2),
+            treasure_n: Some(String::from("goods:salt")),
+            treasure_e: Some(String::from("none")),
+            treasure_s: Some(String::from("rumors")),
+            treasure_w: Some(String::from("none")),
+        };
+
+        assert_eq!(write_tile(&tile), "N|E:W goods:salt,-,rumors,-");
+        assert_eq!(
+            read_tile(write_tile(&tile).split_whitespace()).unwrap()["oasis"],
+            Value::Array(vec!["N|E".into(), "W".into()])
+        );
+    }
+
+    #[test]
+    fn round_trips_the_shipped_tileset() {
+        let notation = to_notation(&shipped());
+
+        assert_eq!(from_notation(&notation).unwrap(), shipped());
+        assert_eq!(
+            notation.lines().filter(|l| l.starts_with("deck ")).count(),
+            5
+        );
+    }
+
+    #[test_case(0)]
+    #[test_case(7)]
+    fn round_trips_generated_tilesets(seed: u64) {
+        let targets = GeneratorTargets::from(&TilesetStats::from(&shipped()));
+        let mut tileset = generate(seed, &targets).unwrap();
+
+        tileset.deck[1].icon = Some(String::from("res://assets/deck, two.png"));
+        tileset.deck[1].unlocks_after = Some(0);
+
+        assert_eq!(from_notation(&to_notation(&tileset)).unwrap(), tileset);
+    }
+
+    #[test_case("n W", 1 ; "tile before the cross")]
+    #[test_case("cross\nx W", 2 ; "unknown arm")]
+    #[test_case("cross\n\nc - a,b,c", 3 ; "three treasures")]
+    #[test_case("cross\nc - desert desert", 2 ; "desert twice")]
+    #[test_case("deck { name = 1\nN", 1 ; "unclosed metadata")]
+    #[test_case("deck {}\ncross", 2 ; "cross after the decks")]
+    fn points_at_the_offending_line(input: &str, line: usize) {
+        assert_matches!(
+            from_notation(input),
+            Err(FormatError::NotationError(at, _)) if at == line
+        );
+    }
+
+    #[test]
+    fn hands_incomplete_tilesets_to_the_tileset_parser() {
+        assert_matches!(
+            from_notation("cross\nc -"),
+            Err(FormatError::TilesetError(_))
+        );
+    }
+}
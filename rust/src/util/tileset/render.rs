@@ -0,0 +1,316 @@
+use std::collections::BTreeMap;
+
+use crate::util::flags::CardinalDirectionFlags;
+use crate::util::tileset::TileConfig;
+use crate::util::tileset::TilesetConfig;
+use crate::util::treasures::is_no_treasure;
+
+// A tile is a 5x7 box with its treasures just outside the edges and a
+// caption above: 8 rows of 11 columns
+const TILE_ROWS: usize = 8;
+const TILE_COLUMNS: usize = 11;
+const TILES_PER_ROW: usize = 6;
+
+const LEGEND: &str = "o = oasis exit, 1-4 = exit of that oasis entry, . = pure desert tile";
+
+/// Single-character codes for treasure ids, assigned in order of appearance.
+#[derive(Debug, Default)]
+pub(crate) struct TreasureCodes(BTreeMap<String, char>);
+
+impl TreasureCodes {
+    pub(crate) fn code(&mut self, id: &str) -> char {
+        if let Some(code) = self.0.get(id) {
+            return *code;
+        }
+
+        // `goods:salt` is `s`, then `S` if that's taken, then a free digit
+        let initial = id
+            .rsplit(':')
+            .next()
+            .and_then(|name| name.chars().next())
+            .unwrap_or('?');
+        let code = [initial.to_ascii_lowercase(), initial.to_ascii_uppercase()]
+            .into_iter()
+            .chain('0'..='9')
+            .find(|code| !self.0.values().any(|taken| taken == code))
+            .unwrap_or('?');
+
+        self.0.insert(id.to_owned(), code);
+
+        code
+    }
+    pub(crate) fn legend(&self) -> String {
+        let mut entries: Vec<(char, &String)> =
+            self.0.iter().map(|(id, code)| (*code, id)).collect();
+        entries.sort();
+
+        entries
+            .iter()
+            .map(|(code, id)| format!("{code} = {id}"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// The exit marker on each side: the number of the oasis entry leaving
+/// through it, or `o` when the tile has a single entry.
+fn exits(tile: &TileConfig) -> [Option<char>; 4] {
+    let mut exits = [None; 4];
+    let Some(oasis) = &tile.oasis else {
+        return exits;
+    };
+
+    let groups: Vec<CardinalDirectionFlags> = oasis
+        .to_chunks()
+        .into_iter()
+        .filter(|group| !group.is_empty())
+        .collect();
+
+    for (idx, group) in groups.iter().enumerate() {
+        let marker = if groups.len() == 1 {
+            'o'
+        } else {
+            char::from_digit(idx as u32 + 1, 10).unwrap_or('?')
+        };
+
+        for (side, direction) in [
+            CardinalDirectionFlags::N,
+            CardinalDirectionFlags::E,
+            CardinalDirectionFlags::S,
+            CardinalDirectionFlags::W,
+        ]
+        .iter()
+        .enumerate()
+        {
+            if group.contains(direction.clone()) {
+                exits[side] = Some(marker);
+            }
+        }
+    }
+
+    exits
+}
+
+fn render_tile(caption: &str, tile: &TileConfig, codes: &mut TreasureCodes) -> [String; TILE_ROWS] {
+    let mut grid = [[' '; TILE_COLUMNS]; TILE_ROWS];
+    let [n, e, s, w] = exits(tile);
+    let fill = if tile.is_desert == Some(true) {
+        '.'
+    } else {
+        ' '
+    };
+
+    for (column, c) in caption.chars().take(TILE_COLUMNS).enumerate() {
+        grid[0][column] = c;
+    }
+
+    // The box spans rows 2 to 6 and columns 2 to 8, centered on (4, 5)
+    for (row, cells) in grid.iter_mut().enumerate().take(7).skip(2) {
+        for (column, cell) in cells.iter_mut().enumerate().take(9).skip(2) {
+            *cell = match (row, column) {
+                (2 | 6, 2 | 8) => '+',
+                (2 | 6, _) => '-',
+                (_, 2 | 8) => '|',
+                _ => fill,
+            };
+        }
+    }
+
+    if let Some(marker) = n {
+        grid[2][5] = marker;
+        grid[3][5] = '|';
+    }
+    if let Some(marker) = s {
+        grid[6][5] = marker;
+        grid[5][5] = '|';
+    }
+    if let Some(marker) = e {
+        grid[4][8] = marker;
+        grid[4][6] = '-';
+        grid[4][7] = '-';
+    }
+    if let Some(marker) = w {
+        grid[4][2] = marker;
+        grid[4][3] = '-';
+        grid[4][4] = '-';
+    }
+    if [n, e, s, w].iter().any(Option::is_some) {
+        grid[4][5] = '+';
+    }
+
+    let treasures = [
+        (&tile.treasure_n, (1, 5)),
+        (&tile.treasure_e, (4, 10)),
+        (&tile.treasure_s, (7, 5)),
+        (&tile.treasure_w, (4, 0)),
+    ];
+
+    for (treasure, (row, column)) in treasures {
+        if let Some(id) = treasure.as_deref().filter(|id| !is_no_treasure(id)) {
+            grid[row][column] = codes.code(id);
+        }
+    }
+
+    grid.map(|row| row.iter().collect::<String>().trim_end().to_owned())
+}
+
+fn render_row(tiles: &[(String, &TileConfig)], codes: &mut TreasureCodes) -> String {
+    let rendered: Vec<[String; TILE_ROWS]> = tiles
+        .iter()
+        .map(|(caption, tile)| render_tile(caption, tile, codes))
+        .collect();
+
+    (0..TILE_ROWS)
+        .map(|row| {
+            let line: Vec<String> = rendered
+                .iter()
+                .map(|tile| format!("{:<TILE_COLUMNS$}", tile[row]))
+                .collect();
+
+            line.join(" ").trim_end().to_owned()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn render_tiles(tiles: &[(String, &TileConfig)], codes: &mut TreasureCodes) -> String {
+    tiles
+        .chunks(TILES_PER_ROW)
+        .map(|row| render_row(row, codes))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Draws the tiles of a tileset as ASCII art for code review: the cross and
+/// every deck, or only deck `deck` (from 0) when given.
+pub fn render_tileset(tileset: &TilesetConfig, deck: Option<usize>) -> String {
+    let mut codes = TreasureCodes::default();
+    let mut sections: Vec<String> = vec![];
+
+    if deck.is_none() {
+        let mut tiles = vec![(String::from("C"), &tileset.cross.c)];
+
+        for (arm, arm_tiles) in tileset.cross.arms() {
+            tiles.extend(
+                arm_tiles
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, tile)| (format!("{}{}", arm.to_ascii_uppercase(), idx + 1), tile)),
+            );
+        }
+
+        sections.push(format!("Cross\n\n{}", render_tiles(&tiles, &mut codes)));
+    }
+
+    for (idx, deck_config) in tileset.deck.iter().enumerate() {
+        if deck.is_some_and(|deck| deck != idx) {
+            continue;
+        }
+
+        let tiles: Vec<(String, &TileConfig)> = deck_config
+            .tiles
+            .iter()
+            .enumerate()
+            .map(|(idx, tile)| (format!("{}", idx + 1), tile))
+            .collect();
+
+        sections.push(format!(
+            "Deck {} ({})\n\n{}",
+            idx + 1,
+            deck_config.name,
+            render_tiles(&tiles, &mut codes)
+        ));
+    }
+
+    sections.push(format!("{LEGEND}\nTreasures: {}", codes.legend()));
+
+    sections.join("\n\n") + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use toml::Table;
+
+    use super::*;
+    use crate::util::flags::OasisLayoutFlags;
+
+    fn tile(oasis: OasisLayoutFlags, treasure_e: &str, is_desert: bool) -> TileConfig {
+        TileConfig {
+            is_cross: Some(false),
+            is_desert: Some(is_desert),
+            oasis: Some(oasis),
+            treasure_n: Some(String::from("none")),
+            treasure_e: Some(String::from(treasure_e)),
+            treasure_s: None,
+            treasure_w: None,
+        }
+    }
+
+    #[test]
+    fn draws_exits_and_treasures() {
+        let rendered = render_tile(
+            "7",
+            &tile(
+                OasisLayoutFlags::N1 | OasisLayoutFlags::E1,
+                "goods:salt",
+                false,
+            ),
+            &mut TreasureCodes::default(),
+        );
+
+        assert_eq!(
+            rendered.join("\n"),
+            [
+                "7",
+                "",
+                "  +--o--+",
+                "  |  |  |",
+                "  |  +--o s",
+                "  |     |",
+                "  +-----+",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn numbers_exits_of_separate_oases() {
+        let rendered = render_tile(
+            "D",
+            &tile(OasisLayoutFlags::N1 | OasisLayoutFlags::S2, "none", true),
+            &mut TreasureCodes::default(),
+        );
+
+        assert_eq!(rendered[2], "  +--1--+");
+        assert_eq!(rendered[3], "  |..|..|");
+        assert_eq!(rendered[4], "  |..+..|");
+        assert_eq!(rendered[6], "  +--2--+");
+    }
+
+    #[test]
+    fn treasure_codes_stay_distinct() {
+        let mut codes = TreasureCodes::default();
+
+        assert_eq!(codes.code("goods:salt"), 's');
+        assert_eq!(codes.code("goods:salt"), 's');
+        assert_eq!(codes.code("spice"), 'S');
+        assert_eq!(codes.code("goods:sugar"), '0');
+        assert_eq!(codes.legend(), "0 = goods:sugar, S = spice, s = goods:salt");
+    }
+
+    #[test]
+    fn renders_the_shipped_tileset() {
+        let table =
+            toml::from_str::<Table>(include_str!("../../../../godot/config/tileset.toml")).unwrap();
+        let tileset = TilesetConfig::try_from(&table).unwrap();
+
+        let everything = render_tileset(&tileset, None);
+        let deck = render_tileset(&tileset, Some(1));
+
+        assert!(everything.starts_with("Cross\n"));
+        assert!(everything.contains("Deck 5 (5)"));
+        assert!(deck.starts_with("Deck 2 (2)\n"));
+        assert!(!deck.contains("Deck 1"));
+    }
+}
@@ -0,0 +1,340 @@
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::fmt::Display;
+
+use crate::util::flags::CardinalDirectionFlags;
+use crate::util::flags::OASIS_CONNECTION_LABELS;
+use crate::util::tileset::TileConfig;
+use crate::util::tileset::TilesetConfig;
+use crate::util::treasures::is_no_treasure;
+
+/// What makes tiles play differently: whether they're pure desert, their
+/// oasis entries as sets of sides, and the treasure on each side.
+type TileType = (bool, Vec<u8>, [Option<String>; 4]);
+
+fn oasis_groups(tile: &TileConfig) -> Vec<CardinalDirectionFlags> {
+    let Some(oasis) = &tile.oasis else {
+        return vec![];
+    };
+
+    oasis
+        .to_chunks()
+        .into_iter()
+        .filter(|group| !group.is_empty())
+        .collect()
+}
+
+/// The same key for a tile in any of its four rotations, the smallest one.
+fn tile_type(tile: &TileConfig) -> TileType {
+    let mut groups = oasis_groups(tile);
+    let mut treasures = [
+        &tile.treasure_n,
+        &tile.treasure_e,
+        &tile.treasure_s,
+        &tile.treasure_w,
+    ]
+    .map(|treasure| treasure.clone().filter(|id| !is_no_treasure(id)));
+    let mut rotations = vec![];
+
+    for _ in 0..4 {
+        let mut bits: Vec<u8> = groups.iter().map(CardinalDirectionFlags::bits).collect();
+        bits.sort();
+
+        rotations.push((tile.is_desert == Some(true), bits, treasures.clone()));
+
+        // A quarter turn clockwise moves every side one step, N to E
+        groups = groups.iter().map(|group| group.rotate_left(1)).collect();
+        treasures.rotate_right(1);
+    }
+
+    rotations.into_iter().min().unwrap_or_default()
+}
+
+/// Counts over a group of tiles, such as a deck.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TileStats {
+    pub tiles: usize,
+    /// Pure-desert tiles, which grant an extra move
+    pub desert_tiles: usize,
+    /// Pure-desert tiles that still have an oasis
+    pub desert_tiles_with_oasis: usize,
+    /// Separate oases over all tiles
+    pub oases: usize,
+    /// Tiles without any oasis
+    pub tiles_without_oasis: usize,
+    /// How often each treasure id is placed
+    pub treasures: BTreeMap<String, usize>,
+    /// How often each of `OASIS_CONNECTION_LABELS` is drawn on a tile
+    pub connections: [usize; OASIS_CONNECTION_LABELS.len()],
+    tile_types: BTreeSet<TileType>,
+}
+
+impl TileStats {
+    pub fn add(&mut self, tile: &TileConfig) {
+        let groups = oasis_groups(tile);
+        let oases = groups.len();
+
+        self.tiles += 1;
+        self.oases += oases;
+        self.tile_types.insert(tile_type(tile));
+
+        for group in groups {
+            let connections: Vec<&str> = group.into();
+
+            for connection in connections {
+                if let Some(idx) = OASIS_CONNECTION_LABELS
+                    .iter()
+                    .position(|l| *l == connection)
+                {
+                    self.connections[idx] += 1;
+                }
+            }
+        }
+
+        if tile.is_desert == Some(true) {
+            self.desert_tiles += 1;
+
+            if oases > 0 {
+                self.desert_tiles_with_oasis += 1;
+            }
+        }
+
+        if oases == 0 {
+            self.tiles_without_oasis += 1;
+        }
+
+        let treasures = [
+            &tile.treasure_n,
+            &tile.treasure_e,
+            &tile.treasure_s,
+            &tile.treasure_w,
+        ];
+
+        for id in treasures.into_iter().flatten() {
+            if !is_no_treasure(id) {
+                *self.treasures.entry(id.clone()).or_default() += 1;
+            }
+        }
+    }
+    pub fn treasure_count(&self) -> usize {
+        self.treasures.values().sum()
+    }
+    /// Share of pure-desert tiles in percent
+    pub fn desert_share(&self) -> f64 {
+        if self.tiles == 0 {
+            return 0.0;
+        }
+
+        100.0 * self.desert_tiles as f64 / self.tiles as f64
+    }
+    /// Share of pure-desert tiles that have an oasis, in percent
+    pub fn desert_oasis_share(&self) -> f64 {
+        if self.desert_tiles == 0 {
+            return 0.0;
+        }
+
+        100.0 * self.desert_tiles_with_oasis as f64 / self.desert_tiles as f64
+    }
+    /// Tile types that differ by more than a rotation
+    pub fn distinct_tile_types(&self) -> usize {
+        self.tile_types.len()
+    }
+    /// A few lines for the deck tooltip's debug overlay.
+    pub fn summary(&self) -> String {
+        let connections: Vec<String> = OASIS_CONNECTION_LABELS
+            .iter()
+            .zip(self.connections)
+            .filter(|(_, count)| *count > 0)
+            .map(|(label, count)| format!("{label}: {count}"))
+            .collect();
+        let treasures: Vec<String> = self
+            .treasures
+            .iter()
+            .map(|(id, count)| format!("{id}: {count}"))
+            .collect();
+
+        format!(
+            "{} tiles, {} types, {:.0}% desert\n{}\n{}",
+            self.tiles,
+            self.distinct_tile_types(),
+            self.desert_share(),
+            connections.join(", "),
+            treasures.join(", ")
+        )
+    }
+}
+
+impl<'a> FromIterator<&'a TileConfig> for TileStats {
+    fn from_iter<T: IntoIterator<Item = &'a TileConfig>>(tiles: T) -> Self {
+        let mut stats = TileStats::default();
+
+        for tile in tiles {
+            stats.add(tile);
+        }
+
+        stats
+    }
+}
+
+impl Display for TileStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "  tiles:               {}", self.tiles)?;
+        writeln!(f, "  tile types:          {}", self.distinct_tile_types())?;
+        writeln!(
+            f,
+            "  pure desert tiles:   {} ({:.0}%)",
+            self.desert_tiles,
+            self.desert_share()
+        )?;
+        writeln!(f, "    with an oasis:     {}", self.desert_tiles_with_oasis)?;
+        writeln!(f, "  oases:               {}", self.oases)?;
+        writeln!(f, "  tiles without oasis: {}", self.tiles_without_oasis)?;
+        writeln!(f, "  oasis connections:")?;
+
+        for (label, count) in OASIS_CONNECTION_LABELS.iter().zip(self.connections) {
+            writeln!(f, "    {label:<18} {count}")?;
+        }
+
+        writeln!(f, "  treasures:           {}", self.treasure_count())?;
+
+        for (id, count) in &self.treasures {
+            writeln!(f, "    {id:<18} {count}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Per-deck statistics of a tileset, for reasoning about deck balance.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TilesetStats {
+    /// Name and counts of each deck, in file order
+    pub decks: Vec<(String, TileStats)>,
+    /// Counts over the tiles of every deck
+    pub overall: TileStats,
+    pub cross: TileStats,
+}
+
+impl From<&TilesetConfig> for TilesetStats {
+    fn from(tileset: &TilesetConfig) -> Self {
+        let cross = std::iter::once(&tileset.cross.c)
+            .chain(
+                tileset
+                    .cross
+                    .arms()
+                    .into_iter()
+                    .flat_map(|(_, tiles)| tiles),
+            )
+            .collect();
+        let decks = tileset
+            .deck
+            .iter()
+            .map(|deck| (deck.name.clone(), deck.tiles.iter().collect()))
+            .collect();
+        let overall = tileset.deck.iter().flat_map(|deck| &deck.tiles).collect();
+
+        Self {
+            decks,
+            overall,
+            cross,
+        }
+    }
+}
+
+impl Display for TilesetStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Cross\n{}", self.cross)?;
+
+        for (idx, (name, stats)) in self.decks.iter().enumerate() {
+            writeln!(f, "Deck {} ({name})\n{stats}", idx + 1)?;
+        }
+
+        writeln!(f, "All decks\n{}", self.overall)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use toml::Table;
+
+    use super::*;
+    use crate::util::flags::OasisLayoutFlags;
+
+    fn shipped() -> TilesetConfig {
+        let table =
+            toml::from_str::<Table>(include_str!("../../../../godot/config/tileset.toml")).unwrap();
+
+        TilesetConfig::try_from(&table).unwrap()
+    }
+
+    #[test]
+    fn counts_a_tile() {
+        let tile = TileConfig {
+            is_cross: Some(false),
+            is_desert: Some(true),
+            oasis: Some(OasisLayoutFlags::N1 | OasisLayoutFlags::E1 | OasisLayoutFlags::S2),
+            treasure_n: Some(String::from("water")),
+            treasure_e: Some(String::from("none")),
+            treasure_s: Some(String::from("water")),
+            treasure_w: None,
+        };
+
+        let stats: TileStats = [&tile].into_iter().collect();
+
+        assert_eq!(stats.tiles, 1);
+        assert_eq!(stats.desert_tiles, 1);
+        assert_eq!(stats.desert_tiles_with_oasis, 1);
+        assert_eq!(stats.oases, 2);
+        assert_eq!(stats.tiles_without_oasis, 0);
+        assert_eq!(stats.treasures.get("water"), Some(&2));
+        assert_eq!(stats.treasure_count(), 2);
+        assert_eq!(stats.connections, [0, 1, 0, 0, 0, 0]);
+        assert_eq!(stats.desert_share(), 100.0);
+        assert_eq!(stats.desert_oasis_share(), 100.0);
+    }
+
+    #[test]
+    fn rotations_are_the_same_tile_type() {
+        let tile = |oasis, treasure_n: &str, treasure_e: &str| TileConfig {
+            is_cross: Some(false),
+            is_desert: Some(false),
+            oasis: Some(oasis),
+            treasure_n: Some(String::from(treasure_n)),
+            treasure_e: Some(String::from(treasure_e)),
+            treasure_s: Some(String::from("none")),
+            treasure_w: None,
+        };
+        let tiles = [
+            tile(OasisLayoutFlags::N1 | OasisLayoutFlags::E1, "water", "none"),
+            tile(OasisLayoutFlags::E1 | OasisLayoutFlags::S1, "none", "water"),
+            tile(OasisLayoutFlags::N1 | OasisLayoutFlags::S1, "none", "water"),
+            tile(OasisLayoutFlags::N1 | OasisLayoutFlags::E1, "none", "water"),
+        ];
+
+        let stats: TileStats = tiles.iter().collect();
+
+        assert_eq!(stats.distinct_tile_types(), 3);
+        assert_eq!(stats.connections, [0, 2, 0, 1, 0, 1]);
+    }
+
+    #[test]
+    fn covers_every_deck_of_the_shipped_tileset() {
+        let stats = TilesetStats::from(&shipped());
+
+        assert_eq!(stats.cross.tiles, 21);
+        assert_eq!(stats.decks.len(), 5);
+        assert!(stats.decks.iter().all(|(_, deck)| deck.tiles == 17));
+        assert_eq!(stats.overall.tiles, 5 * 17);
+        assert_eq!(
+            stats.overall.desert_tiles,
+            stats
+                .decks
+                .iter()
+                .map(|(_, deck)| deck.desert_tiles)
+                .sum::<usize>()
+        );
+        assert!(stats.overall.distinct_tile_types() <= stats.overall.tiles);
+        assert!(stats.to_string().contains("Deck 3 (3)"));
+        assert!(stats.to_string().contains("All decks"));
+    }
+}
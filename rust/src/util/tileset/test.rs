@@ -2,10 +2,13 @@
 mod tests {
     use assert_matches::assert_matches;
     use toml::Table;
+    use toml::Value;
 
+    use crate::util::flags::CardinalDirectionFlags;
     use crate::util::flags::OasisLayoutFlags;
-    use crate::util::loader::CrossConfig;
-    use crate::util::loader::TilesetConfig;
+    use crate::util::tileset::ConfigOasisFlags;
+    use crate::util::tileset::CrossConfig;
+    use crate::util::tileset::TilesetConfig;
 
     #[test]
     fn test_parse_tileset_config() {
@@ -885,9 +888,11 @@ treasure_w = "none"
         assert_matches!(
             tileset_config,
             TilesetConfig {
-                deck: [deck_one, _, deck_three, _, _],
+                deck,
                 cross: CrossConfig { c, n, e:_, w, s:_ }
-            } => {
+            } if deck.len() == 5 => {
+                let (deck_one, deck_three) = (&deck[0], &deck[2]);
+
                 assert_eq!(c.is_desert, Some(false));
                 assert_eq!(c.oasis, None);
                 assert_matches!(n, array => {
@@ -903,23 +908,148 @@ treasure_w = "none"
                     assert_eq!(array[2].oasis, None);
                 });
                 assert_matches!(deck_one, deck => {
-                    assert_eq!(deck.0[0].is_desert, Some(true));
-                    assert_eq!(deck.0[1].is_desert, Some(false));
-                    assert_eq!(deck.0[0].oasis, Some(OasisLayoutFlags::E1 | OasisLayoutFlags::S1));
-                    assert_eq!(deck.0[0].treasure_e, Some(String::from("salt")));
-                    assert_eq!(deck.0[0].treasure_s, Some(String::from("rumors")));
-                    assert_eq!(deck.0[1].oasis, Some(OasisLayoutFlags::N1));
-                    assert_eq!(deck.0[2].oasis, Some(OasisLayoutFlags::empty()));
+                    assert_eq!(deck.tiles[0].is_desert, Some(true));
+                    assert_eq!(deck.tiles[1].is_desert, Some(false));
+                    assert_eq!(deck.tiles[0].oasis, Some(OasisLayoutFlags::E1 | OasisLayoutFlags::S1));
+                    assert_eq!(deck.tiles[0].treasure_e, Some(String::from("salt")));
+                    assert_eq!(deck.tiles[0].treasure_s, Some(String::from("rumors")));
+                    assert_eq!(deck.tiles[1].oasis, Some(OasisLayoutFlags::N1));
+                    assert_eq!(deck.tiles[2].oasis, Some(OasisLayoutFlags::empty()));
                 });
                 assert_matches!(deck_three, deck => {
                     dbg!(&deck);
-                    assert_eq!(deck.0[0].is_desert, Some(true));
-                    assert_eq!(deck.0[1].is_desert, Some(false));
-                    assert_eq!(deck.0[0].oasis, Some(OasisLayoutFlags::empty()));
-                    assert_eq!(deck.0[1].oasis, Some(OasisLayoutFlags::E1));
-                    assert_eq!(deck.0[2].oasis, Some(OasisLayoutFlags::S1));
+                    assert_eq!(deck.tiles[0].is_desert, Some(true));
+                    assert_eq!(deck.tiles[1].is_desert, Some(false));
+                    assert_eq!(deck.tiles[0].oasis, Some(OasisLayoutFlags::empty()));
+                    assert_eq!(deck.tiles[1].oasis, Some(OasisLayoutFlags::E1));
+                    assert_eq!(deck.tiles[2].oasis, Some(OasisLayoutFlags::S1));
                 });
             }
         );
     }
+
+    fn shipped_tileset_with_decks(metadata: &[&str]) -> Result<TilesetConfig, &'static str> {
+        let shipped = include_str!("../../../../godot/config/tileset.toml");
+        let mut entries = shipped.split("[[decks]]\n");
+        let mut input = entries.next().unwrap().to_owned();
+
+        // Swap the shipped name and color for `metadata`, dropping the decks
+        // it has no entry for
+        for (entry, metadata) in entries.zip(metadata) {
+            let tiles = &entry[entry.find("[[decks.deck]]").unwrap()..];
+
+            input.push_str(&format!("[[decks]]\n{metadata}\n{tiles}"));
+        }
+
+        TilesetConfig::try_from(&toml::from_str::<Table>(&input).unwrap())
+    }
+
+    #[test]
+    fn shipped_decks_carry_their_metadata() {
+        let table =
+            toml::from_str::<Table>(include_str!("../../../../godot/config/tileset.toml")).unwrap();
+        let tileset = TilesetConfig::try_from(&table).unwrap();
+
+        assert_eq!(tileset.deck[0].name, "1");
+        assert_eq!(tileset.deck[0].color, "#fee17c");
+        assert_eq!(tileset.deck[4].color, "#c97db4");
+        assert_eq!(tileset.deck_unlocks(), [None; 5]);
+    }
+
+    #[test]
+    fn parses_deck_unlock_rules() {
+        let tileset = shipped_tileset_with_decks(&[
+            "unlocks_after = 2",
+            "icon = \"res://deck.png\"",
+            "",
+            "unlocks_after = 1",
+            "",
+        ])
+        .unwrap();
+
+        assert_eq!(tileset.deck[1].icon, Some(String::from("res://deck.png")));
+        assert_eq!(tileset.deck_unlocks(), [Some(1), None, None, Some(0), None]);
+    }
+
+    #[test]
+    fn rejects_invalid_deck_metadata() {
+        assert_matches!(
+            shipped_tileset_with_decks(&["color = \"yellow\"", "", "", "", ""]),
+            Err(_)
+        );
+        assert_matches!(
+            shipped_tileset_with_decks(&["unlocks_after = 1", "", "", "", ""]),
+            Err(_)
+        );
+        assert_matches!(
+            shipped_tileset_with_decks(&["unlocks_after = 6", "", "", "", ""]),
+            Err(_)
+        );
+        assert_eq!(
+            shipped_tileset_with_decks(&[
+                "unlocks_after = 2",
+                "unlocks_after = 3",
+                "unlocks_after = 1",
+                "",
+                ""
+            ])
+            .unwrap_err(),
+            "Deck unlock rules can't form a cycle"
+        );
+    }
+
+    #[test]
+    fn parses_any_number_of_decks() {
+        let tileset = shipped_tileset_with_decks(&["", "unlocks_after = 3", ""]).unwrap();
+
+        assert_eq!(tileset.deck.len(), 3);
+        assert_eq!(tileset.deck_unlocks(), [None, Some(2), None]);
+        assert_eq!(
+            shipped_tileset_with_decks(&["", "unlocks_after = 4", ""]).unwrap_err(),
+            "Deck unlocks_after must be the number of another deck"
+        );
+
+        let shipped = include_str!("../../../../godot/config/tileset.toml");
+        let mut table = toml::from_str::<Table>(shipped).unwrap();
+
+        table["decks"] = Value::Array(vec![]);
+
+        assert_eq!(
+            TilesetConfig::try_from(&table).unwrap_err(),
+            "Config requires between 1 and 255 tile decks"
+        );
+    }
+
+    #[test]
+    fn rejects_deck_tiles_that_arent_tables() {
+        let shipped = include_str!("../../../../godot/config/tileset.toml");
+        let mut table = toml::from_str::<Table>(shipped).unwrap();
+        let tiles = table["decks"][0]["deck"].as_array_mut().unwrap();
+
+        tiles[3] = Value::Integer(1);
+
+        assert_eq!(
+            TilesetConfig::try_from(&table).unwrap_err(),
+            "Could not parse value as tile config table"
+        );
+    }
+
+    #[test]
+    fn reads_oasis_flags_by_name_or_bits() {
+        let parse = |flags: &str| {
+            ConfigOasisFlags::try_from(&Value::String(flags.to_owned()))
+                .map(CardinalDirectionFlags::from)
+        };
+
+        assert_eq!(
+            parse("N | W"),
+            Ok(CardinalDirectionFlags::N | CardinalDirectionFlags::W)
+        );
+        assert_eq!(
+            parse("0x6"),
+            Ok(CardinalDirectionFlags::E | CardinalDirectionFlags::S)
+        );
+        assert_matches!(parse("0x10"), Err(_));
+        assert_matches!(parse("N | X"), Err(_));
+    }
 }
@@ -0,0 +1,410 @@
+use std::fmt::Display;
+
+use thiserror::Error;
+use toml::Table;
+use toml::Value;
+
+// Treasure ids that mean a side holds no treasure
+const NO_TREASURE_IDS: [&str; 2] = ["", "none"];
+
+/// Whether `id` marks a side without a treasure rather than naming one.
+pub fn is_no_treasure(id: &str) -> bool {
+    NO_TREASURE_IDS.contains(&id)
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum TreasureConfigError {
+    #[error("Expected treasures as a [[treasure]] array of tables")]
+    MissingTreasures,
+    #[error("Treasure #{0} is missing required key {1}")]
+    MissingKey(usize, &'static str),
+    #[error("Treasure #{0} key {1} has the wrong type, expected {2}")]
+    WrongType(usize, &'static str, &'static str),
+    #[error(
+        "Treasure {0} expected category to be one of water, goods, camels, rumors, received {1}"
+    )]
+    UnknownCategory(String, String),
+    #[error("Treasure {0} effect {1} must be a non-negative integer")]
+    InvalidEffect(String, String),
+    #[error("Treasure {0} is defined more than once")]
+    DuplicateId(String),
+    #[error("Treasure id {0} is reserved for sides without a treasure")]
+    ReservedId(String),
+    #[error("Treasure {0} icon {1} must stay inside the directory it's relative to")]
+    IconOutsideDirectory(String, String),
+}
+
+/// Whether the relative `path` stays inside the directory it's relative to,
+/// rather than climbing out of it with `..` or starting from the root.
+fn stays_inside(path: &str) -> bool {
+    if path.starts_with(['/', '\\']) {
+        return false;
+    }
+
+    let mut depth = 0usize;
+
+    for part in path.split(['/', '\\']) {
+        match part {
+            "" | "." => {}
+            ".." => match depth.checked_sub(1) {
+                Some(up) => depth = up,
+                None => return false,
+            },
+            _ => depth += 1,
+        }
+    }
+
+    true
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TreasureCategory {
+    Water,
+    Goods,
+    Camels,
+    Rumors,
+}
+
+impl TryFrom<&str> for TreasureCategory {
+    type Error = ();
+
+    fn try_from(value: &str) -> Result<Self, ()> {
+        match value {
+            "water" => Ok(Self::Water),
+            "goods" => Ok(Self::Goods),
+            "camels" => Ok(Self::Camels),
+            "rumors" => Ok(Self::Rumors),
+            _ => Err(()),
+        }
+    }
+}
+
+/// What collecting a treasure grants, e.g. 2 water.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreasureEffect {
+    pub resource: String,
+    pub amount: u32,
+}
+
+/// A kind of treasure tiles can hold, declared in `config/treasures.toml`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TreasureDefinition {
+    /// Referenced from the tileset's `treasure_n`, `treasure_e`, ... keys
+    pub id: String,
+    pub label: String,
+    /// Resource path of the icon texture
+    pub icon: String,
+    pub category: TreasureCategory,
+    pub effects: Vec<TreasureEffect>,
+}
+
+impl TreasureDefinition {
+    fn try_from_table(idx: usize, table: &Table) -> Result<Self, TreasureConfigError> {
+        let string = |key: &'static str| -> Result<String, TreasureConfigError> {
+            table
+                .get(key)
+                .ok_or(TreasureConfigError::MissingKey(idx, key))?
+                .as_str()
+                .map(str::to_owned)
+                .ok_or(TreasureConfigError::WrongType(idx, key, "a string"))
+        };
+
+        let id = string("id")?;
+
+        if is_no_treasure(&id) {
+            return Err(TreasureConfigError::ReservedId(id));
+        }
+
+        let category = string("category")?;
+        let category = TreasureCategory::try_from(category.as_str())
+            .map_err(|_| TreasureConfigError::UnknownCategory(id.clone(), category))?;
+
+        let effects = match table.get("effects") {
+            None => vec![],
+            Some(Value::Table(effects)) => effects
+                .iter()
+                .map(|(resource, amount)| {
+                    amount
+                        .as_integer()
+                        .and_then(|amount| u32::try_from(amount).ok())
+                        .map(|amount| TreasureEffect {
+                            resource: resource.clone(),
+                            amount,
+                        })
+                        .ok_or(TreasureConfigError::InvalidEffect(
+                            id.clone(),
+                            resource.clone(),
+                        ))
+                })
+                .collect::<Result<_, _>>()?,
+            Some(_) => return Err(TreasureConfigError::WrongType(idx, "effects", "a table")),
+        };
+
+        Ok(Self {
+            label: string("label")?,
+            icon: string("icon")?,
+            id,
+            category,
+            effects,
+        })
+    }
+}
+
+/// Every treasure defined in `config/treasures.toml`, in file order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TreasureCatalog {
+    definitions: Vec<TreasureDefinition>,
+}
+
+impl TreasureCatalog {
+    pub fn get(&self, id: &str) -> Option<&TreasureDefinition> {
+        self.definitions
+            .iter()
+            .find(|definition| definition.id == id)
+    }
+    pub fn definitions(&self) -> &[TreasureDefinition] {
+        &self.definitions
+    }
+    /// Reads icon paths that aren't `res://` or `user://` paths as relative
+    /// to `directory`, for catalogs that ship their own icons. Relative paths
+    /// that leave `directory` are rejected.
+    pub fn with_icons_under(mut self, directory: &str) -> Result<Self, TreasureConfigError> {
+        for definition in &mut self.definitions {
+            if definition.icon.contains("://") {
+                continue;
+            }
+
+            if !stays_inside(&definition.icon) {
+                return Err(TreasureConfigError::IconOutsideDirectory(
+                    definition.id.clone(),
+                    definition.icon.clone(),
+                ));
+            }
+
+            definition.icon = format!("{directory}{}", definition.icon);
+        }
+
+        Ok(self)
+    }
+    /// Checks that every treasure the tileset places is defined.
+    pub fn validate(
+        &self,
+        references: impl IntoIterator<Item = TreasureReference>,
+    ) -> Result<(), UndefinedTreasureError> {
+        for reference in references {
+            if !is_no_treasure(&reference.id) && self.get(&reference.id).is_none() {
+                return Err(UndefinedTreasureError(reference));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl TryFrom<&Table> for TreasureCatalog {
+    type Error = TreasureConfigError;
+
+    fn try_from(table: &Table) -> Result<Self, TreasureConfigError> {
+        let entries = table
+            .get("treasure")
+            .and_then(Value::as_array)
+            .ok_or(TreasureConfigError::MissingTreasures)?;
+
+        let mut definitions: Vec<TreasureDefinition> = vec![];
+
+        for (idx, entry) in entries.iter().enumerate() {
+            let entry = entry
+                .as_table()
+                .ok_or(TreasureConfigError::MissingTreasures)?;
+            let definition = TreasureDefinition::try_from_table(idx, entry)?;
+
+            if definitions.iter().any(|d| d.id == definition.id) {
+                return Err(TreasureConfigError::DuplicateId(definition.id));
+            }
+
+            definitions.push(definition);
+        }
+
+        Ok(Self { definitions })
+    }
+}
+
+/// Where in `config/tileset.toml` a tile is declared.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TileLocation {
+    /// A tile of the starting cross; `arm` is one of c, n, e, s, w
+    Cross { arm: char, index: usize },
+    /// A tile of one of the decks
+    Deck { deck: usize, index: usize },
+}
+
+impl Display for TileLocation {
+    /// Reads like the tileset's own tables, counting from 1 as its comments do.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TileLocation::Cross { arm: 'c', .. } => write!(f, "cross.c"),
+            TileLocation::Cross { arm, index } => write!(f, "cross.{arm} tile {}", index + 1),
+            TileLocation::Deck { deck, index } => {
+                write!(f, "deck {} tile {}", deck + 1, index + 1)
+            }
+        }
+    }
+}
+
+/// A treasure id placed on one side of a tile in the tileset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreasureReference {
+    pub location: TileLocation,
+    /// The tileset key, e.g. `treasure_n`
+    pub key: &'static str,
+    pub id: String,
+}
+
+#[derive(Error, Debug, PartialEq)]
+#[error("Undefined treasure {} at {}, {} in tileset.toml", .0.id, .0.location, .0.key)]
+pub struct UndefinedTreasureError(pub TreasureReference);
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+    use test_case::test_case;
+    use toml::Table;
+
+    use super::*;
+
+    fn parse(input: &str) -> Result<TreasureCatalog, TreasureConfigError> {
+        let table = toml::from_str::<Table>(input).unwrap();
+
+        TreasureCatalog::try_from(&table)
+    }
+
+    fn reference(id: &str) -> TreasureReference {
+        TreasureReference {
+            location: TileLocation::Deck { deck: 1, index: 4 },
+            key: "treasure_e",
+            id: id.to_owned(),
+        }
+    }
+
+    #[test]
+    fn the_shipped_treasures_parse() {
+        let shipped = include_str!("../../../godot/config/treasures.toml");
+        let catalog = parse(shipped).unwrap();
+
+        let salt = catalog.get("goods:salt").unwrap();
+
+        assert_eq!(salt.category, TreasureCategory::Goods);
+        assert_eq!(salt.icon, "res://assets/icons/treasure/goods/salt.png");
+        assert_eq!(catalog.get("double_water").unwrap().effects[0].amount, 2);
+    }
+
+    #[test]
+    fn new_treasures_need_no_code() {
+        let catalog = parse(
+            r#"
+[[treasure]]
+id = "goods:dates"
+label = "Dates"
+icon = "res://mods/dates.png"
+category = "goods"
+effects = { dates = 1, water = 1 }
+"#,
+        )
+        .unwrap();
+
+        let dates = catalog.get("goods:dates").unwrap();
+
+        assert_eq!(dates.label, "Dates");
+        assert_eq!(dates.effects.len(), 2);
+    }
+
+    #[test]
+    fn rejects_invalid_definitions() {
+        assert_eq!(parse(""), Err(TreasureConfigError::MissingTreasures));
+        assert_matches!(
+            parse("[[treasure]]\nid = \"water\"\ncategory = \"water\"\nicon = \"\""),
+            Err(TreasureConfigError::MissingKey(0, "label"))
+        );
+        assert_matches!(
+            parse("[[treasure]]\nid = \"gold\"\nlabel = \"\"\nicon = \"\"\ncategory = \"metal\""),
+            Err(TreasureConfigError::UnknownCategory(_, _))
+        );
+        assert_matches!(
+            parse("[[treasure]]\nid = \"none\"\nlabel = \"\"\nicon = \"\"\ncategory = \"water\""),
+            Err(TreasureConfigError::ReservedId(_))
+        );
+        assert_matches!(
+            parse(
+                "[[treasure]]\nid = \"water\"\nlabel = \"\"\nicon = \"\"\ncategory = \"water\"\neffects = { water = -1 }"
+            ),
+            Err(TreasureConfigError::InvalidEffect(_, _))
+        );
+    }
+
+    #[test_case("icons/gold.png" => true ; "inside")]
+    #[test_case("icons/../gold.png" => true ; "back up inside")]
+    #[test_case("../gold.png" => false ; "parent")]
+    #[test_case("icons/../../gold.png" => false ; "climbs out")]
+    #[test_case("..\\gold.png" => false ; "windows separator")]
+    #[test_case("/gold.png" => false ; "absolute")]
+    fn keeps_relative_icons_inside_their_directory(icon: &str) -> bool {
+        let catalog = parse(&format!(
+            "[[treasure]]\nid = \"gold\"\nlabel = \"\"\nicon = '{icon}'\ncategory = \"goods\""
+        ))
+        .unwrap();
+
+        match catalog.with_icons_under("user://mods/dunes/") {
+            Ok(catalog) => catalog.definitions()[0].icon == format!("user://mods/dunes/{icon}"),
+            Err(error) => {
+                assert_eq!(
+                    error,
+                    TreasureConfigError::IconOutsideDirectory("gold".to_owned(), icon.to_owned())
+                );
+                false
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_duplicate_ids() {
+        let entry =
+            "[[treasure]]\nid = \"water\"\nlabel = \"\"\nicon = \"\"\ncategory = \"water\"\n";
+
+        assert_eq!(
+            parse(&entry.repeat(2)),
+            Err(TreasureConfigError::DuplicateId("water".to_owned()))
+        );
+    }
+
+    #[test]
+    fn undefined_references_carry_their_location() {
+        let catalog = parse(include_str!("../../../godot/config/treasures.toml")).unwrap();
+
+        assert_eq!(
+            catalog.validate([reference("water"), reference("none"), reference("")]),
+            Ok(())
+        );
+
+        let error = catalog
+            .validate([reference("water"), reference("goods:gold")])
+            .unwrap_err();
+
+        assert_eq!(error, UndefinedTreasureError(reference("goods:gold")));
+        assert_eq!(
+            error.to_string(),
+            "Undefined treasure goods:gold at deck 2 tile 5, treasure_e in tileset.toml"
+        );
+    }
+
+    #[test]
+    fn cross_locations_read_like_the_tileset() {
+        assert_eq!(
+            TileLocation::Cross { arm: 'n', index: 2 }.to_string(),
+            "cross.n tile 3"
+        );
+        assert_eq!(
+            TileLocation::Cross { arm: 'c', index: 0 }.to_string(),
+            "cross.c"
+        );
+    }
+}
@@ -0,0 +1,101 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+use toml::Table;
+
+use grandfathers_of_the_sahara::util::scenario::Scenario;
+use grandfathers_of_the_sahara::util::tileset::CrossConfig;
+use grandfathers_of_the_sahara::util::tileset::TilesetConfig;
+
+const SCENARIOS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/scenarios");
+// Scenarios with `cross = true` start on this tileset's starting cross
+const TILESET: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../godot/config/tileset.toml");
+// Set to rewrite the golden files from the current output, see `just bless`
+const BLESS: &str = "BLESS";
+
+/// Plays the scenario at `path` and draws where it ends up. An action the
+/// rules reject ends the scenario, and is part of the snapshot.
+fn snapshot(path: &Path, cross: &CrossConfig) -> Result<String, String> {
+    let contents = fs::read_to_string(path).map_err(|e| format!("Couldn't read it: {e}"))?;
+    let table =
+        toml::from_str::<Table>(&contents).map_err(|e| format!("Couldn't parse it: {e}"))?;
+    let scenario = Scenario::try_from(&table).map_err(|e| e.to_string())?;
+
+    Ok(match scenario.play(cross) {
+        Ok(game) => format!("{}\n", game.snapshot()),
+        Err(error) => format!("Stopped at {error}\n"),
+    })
+}
+
+/// The lines that differ, numbered from 1.
+fn diff(expected: &str, actual: &str) -> String {
+    let expected: Vec<&str> = expected.lines().collect();
+    let actual: Vec<&str> = actual.lines().collect();
+
+    (0..expected.len().max(actual.len()))
+        .filter(|idx| expected.get(*idx) != actual.get(*idx))
+        .map(|idx| {
+            format!(
+                "{:>4} - {}\n     + {}",
+                idx + 1,
+                expected.get(idx).unwrap_or(&""),
+                actual.get(idx).unwrap_or(&"")
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[test]
+fn scenarios_match_their_golden_files() {
+    let bless = env::var_os(BLESS).is_some();
+    let tileset = toml::from_str::<Table>(&fs::read_to_string(TILESET).unwrap()).unwrap();
+    let cross = TilesetConfig::try_from(&tileset).unwrap().cross;
+    let mut scenarios: Vec<PathBuf> = fs::read_dir(SCENARIOS)
+        .unwrap()
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "toml")
+        })
+        .collect();
+    let mut failures = vec![];
+
+    scenarios.sort();
+
+    assert!(!scenarios.is_empty(), "No scenarios in {SCENARIOS}");
+
+    for scenario in scenarios {
+        let golden = scenario.with_extension("snap");
+        let actual = match snapshot(&scenario, &cross) {
+            Ok(actual) => actual,
+            Err(error) => {
+                failures.push(format!("{}: {error}", scenario.display()));
+                continue;
+            }
+        };
+
+        if bless {
+            fs::write(&golden, actual).unwrap();
+            continue;
+        }
+
+        match fs::read_to_string(&golden) {
+            Ok(expected) if expected == actual => {}
+            Ok(expected) => failures.push(format!(
+                "{} changed:\n{}",
+                golden.display(),
+                diff(&expected, &actual)
+            )),
+            Err(_) => failures.push(format!("{} is missing", golden.display())),
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "{}\n\nRun `just bless` if the changes are intended",
+        failures.join("\n\n")
+    );
+}